@@ -0,0 +1,121 @@
+//! Benchmarks of the slowest days, parsing and solving separately where the day allows it.
+//!
+//! Run with `cargo bench`, or `cargo bench -- day17` for a single day.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use advent_of_rust::advent_2020::day15::AdventDay15;
+use advent_of_rust::advent_2020::day16::AdventDay16;
+use advent_of_rust::advent_2020::day17::AdventDay17;
+use advent_of_rust::advent_2020::day18::AdventDay18;
+use advent_of_rust::advent_2020::day19_with_nom::AdventDay19;
+use advent_of_rust::advent_adapters::AdventState;
+use advent_of_rust::advents::{Advent, Parts};
+use advent_of_rust::helper::automaton::{self, Rules};
+
+fn fixture(day: u8, name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/2020")
+        .join(day.to_string())
+        .join(name);
+
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("could not read {}: {}", path.display(), err))
+}
+
+/// Benchmark parsing the input and each part on the parsed state
+fn bench_stateful<T: AdventState>(c: &mut Criterion, name: &str, input: &str) {
+    let mut group = c.benchmark_group(name);
+
+    group.bench_function("parse", |b| {
+        b.iter(|| T::new("test.txt", black_box(input.to_owned())))
+    });
+
+    let state = T::new("test.txt", input.to_owned()).expect("sample must parse");
+    group.bench_function("part1", |b| b.iter(|| black_box(&state).part1()));
+    group.bench_function("part2", |b| b.iter(|| black_box(&state).part2()));
+
+    group.finish();
+}
+
+fn day15(c: &mut Criterion) {
+    // Thirty million turns take seconds, a shorter game keeps the same access pattern
+    let day = AdventDay15::new("input.txt", "0,3,6".to_owned()).unwrap();
+
+    let mut group = c.benchmark_group("day15");
+    group.bench_function("2020 turns", |b| {
+        b.iter(|| black_box(&day).nth_spoken(2020))
+    });
+    group.bench_function("300k turns", |b| {
+        b.iter(|| black_box(&day).nth_spoken(300_000))
+    });
+    group.finish();
+}
+
+fn day16(c: &mut Criterion) {
+    // Parsing and solving are not separated for this day yet
+    let input = fixture(16, "test2.txt");
+
+    c.bench_function("day16/solve", |b| {
+        b.iter(|| AdventDay16.solve("test2.txt", black_box(input.clone()), Parts::All))
+    });
+}
+
+fn day17(c: &mut Criterion) {
+    bench_stateful::<AdventDay17>(c, "day17", &fixture(17, "test.txt"));
+}
+
+/// The hash-set engine alone, on the 4D sample the day checks its dense grid against
+fn sparse_automaton(c: &mut Criterion) {
+    let cells: HashSet<[isize; 4]> = fixture(17, "test.txt")
+        .lines()
+        .enumerate()
+        .flat_map(|(line, row)| {
+            row.char_indices()
+                .filter(|&(_, cell)| cell == '#')
+                .map(move |(column, _)| [line as isize, column as isize, 0, 0])
+        })
+        .collect();
+    let neighbors = |cell: &[isize; 4]| {
+        let cell = *cell;
+        (0..81)
+            .filter(|&combination| combination != 40)
+            .map(move |mut combination| {
+                let mut neighbor = cell;
+                for value in neighbor.iter_mut() {
+                    *value += combination % 3 - 1;
+                    combination /= 3;
+                }
+                neighbor
+            })
+    };
+
+    c.bench_function("automaton/sparse 4d", |b| {
+        b.iter(|| automaton::evolve(black_box(&cells), 6, neighbors, Rules::default()))
+    });
+}
+
+fn day18(c: &mut Criterion) {
+    bench_stateful::<AdventDay18>(c, "day18", &fixture(18, "test.txt"));
+}
+
+fn day19(c: &mut Criterion) {
+    // The messages of the sample a hundred times over, so the cost of each message dominates
+    let sample = fixture(19, "test2.txt");
+    let (rules, messages) = sample
+        .split_once("\n\n")
+        .expect("sample must have messages");
+    let input = format!(
+        "{}\n\n{}",
+        rules,
+        format!("{}\n", messages.trim_end()).repeat(100)
+    );
+
+    bench_stateful::<AdventDay19>(c, "day19", &input);
+}
+
+criterion_group!(benches, day15, day16, day17, sparse_automaton, day18, day19);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Output of a git command in the source folder, if git knows the answer
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|text| text.trim().to_owned())
+        .filter(|text| !text.is_empty())
+}
+
+/// Record the commit the runner is built from in `AOC_BUILD_COMMIT`, empty outside of a git
+/// checkout, so that manifests name the code that produced them rather than the one checked out
+/// when they are written
+fn main() {
+    let commit = git(&["rev-parse", "HEAD"]).unwrap_or_default();
+    println!("cargo:rustc-env=AOC_BUILD_COMMIT={}", commit);
+
+    // Build again when a commit is made or another one is checked out
+    if let Some(git_dir) = git(&["rev-parse", "--git-dir"]) {
+        let git_dir = Path::new(&git_dir);
+        println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+        println!(
+            "cargo:rerun-if-changed={}",
+            git_dir.join("packed-refs").display()
+        );
+        if let Some(branch) = git(&["symbolic-ref", "-q", "HEAD"]) {
+            println!("cargo:rerun-if-changed={}", git_dir.join(branch).display());
+        }
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
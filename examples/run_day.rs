@@ -0,0 +1,33 @@
+//! Run a single day through the library and check its answers, the way a wrapper script would.
+//!
+//! Run with `cargo run --example run_day`.
+
+use advent_of_rust::inputs::InMemoryProvider;
+use advent_of_rust::runner::{Runner, Selection};
+
+fn main() {
+    // Samples of the puzzle instead of the input files of the data folder
+    let inputs = InMemoryProvider::new()
+        .with(2020, 18, "test.txt", "2 * 3 + (4 * 5)")
+        .with(2020, 18, "input.txt", "1 + 2 * 3 + 4 * 5 + 6");
+
+    let report = Runner::new(advent_of_rust::advent_years())
+        .with_data_dir(std::env::temp_dir().join("advent-of-rust-example"))
+        .with_provider(inputs)
+        .run(Selection::Day(2020, 18))
+        .unwrap_or_else(|err| panic!("{}", err));
+
+    assert_eq!(report.answer(2020, 18, 1, "test.txt"), Some("26"));
+    assert_eq!(report.answer(2020, 18, 2, "test.txt"), Some("46"));
+    assert_eq!(report.answer(2020, 18, 1, "input.txt"), Some("71"));
+    assert_eq!(report.answer(2020, 18, 2, "input.txt"), Some("231"));
+    assert_eq!(report.failures().count(), 0);
+
+    for point in &report.points {
+        println!(
+            "{}: {}",
+            point.name(),
+            point.answer.as_deref().unwrap_or("-")
+        );
+    }
+}
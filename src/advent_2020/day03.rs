@@ -0,0 +1,66 @@
+use crate::advent_adapters::AdventState;
+use crate::advents::PartResult;
+use crate::helper::grid::Grid;
+
+pub struct AdventDay03 {
+    /// Whether each position of the map has a tree
+    trees: Grid<bool>,
+}
+
+impl AdventDay03 {
+    /// Count the trees hit going `right` columns and `down` rows at a time from the top-left
+    fn trees_on_slope(&self, right: usize, down: usize) -> usize {
+        (0..self.trees.height())
+            .step_by(down)
+            .enumerate()
+            .filter(|&(step, row)| self.trees.get_wrapping(row, step * right) == Some(&true))
+            .count()
+    }
+
+    fn step1(&self) -> usize {
+        self.trees_on_slope(3, 1)
+    }
+
+    fn step2(&self) -> u64 {
+        [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+            .iter()
+            .map(|&(right, down)| self.trees_on_slope(right, down) as u64)
+            .product()
+    }
+}
+
+impl AdventState for AdventDay03 {
+    const INPUT_FILES: &'static [&'static str] = &["test.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Toboggan Trajectory");
+
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        let trees = Grid::parse(&input_content, |cell| match cell {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .map_err(|err| err.to_string())?;
+
+        Ok(Self { trees })
+    }
+
+    fn part1(&self) -> PartResult {
+        Ok(self.step1().into())
+    }
+
+    fn part2(&self) -> PartResult {
+        Ok(self.step2().into())
+    }
+}
+
+#[test]
+fn test_sample() {
+    let day = AdventDay03::new(
+        "test.txt",
+        include_str!("../../data/2020/3/test.txt").into(),
+    )
+    .unwrap();
+
+    assert_eq!(day.step1(), 7);
+    assert_eq!(day.step2(), 336);
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use crate::advent_adapters::AdventState;
+use crate::advents::PartResult;
+use crate::helper::parse::blocks;
+
+/// Fields every passport must have, `cid` being optional
+const REQUIRED_FIELDS: [&str; 7] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+
+const EYE_COLORS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+struct Passport {
+    fields: HashMap<String, String>,
+}
+
+impl Passport {
+    fn parse(block: &str) -> Result<Self, String> {
+        let fields = block
+            .split_whitespace()
+            .map(|entry| {
+                entry
+                    .split_once(':')
+                    .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                    .ok_or_else(|| format!("malformed passport field '{}'", entry))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { fields })
+    }
+
+    fn has_required_fields(&self) -> bool {
+        REQUIRED_FIELDS
+            .iter()
+            .all(|&field| self.fields.contains_key(field))
+    }
+
+    fn is_valid(&self) -> bool {
+        self.has_required_fields()
+            && self
+                .fields
+                .iter()
+                .all(|(key, value)| is_valid_field(key, value))
+    }
+}
+
+fn is_valid_field(key: &str, value: &str) -> bool {
+    match key {
+        "byr" => is_year_between(value, 1920, 2002),
+        "iyr" => is_year_between(value, 2010, 2020),
+        "eyr" => is_year_between(value, 2020, 2030),
+        "hgt" => is_valid_height(value),
+        "hcl" => value.strip_prefix('#').is_some_and(|color| {
+            color.len() == 6 && color.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+        }),
+        "ecl" => EYE_COLORS.contains(&value),
+        "pid" => value.len() == 9 && value.chars().all(|c| c.is_ascii_digit()),
+        "cid" => true,
+        _ => false,
+    }
+}
+
+fn is_year_between(value: &str, min: u16, max: u16) -> bool {
+    value.len() == 4
+        && value
+            .parse::<u16>()
+            .is_ok_and(|year| (min..=max).contains(&year))
+}
+
+fn is_valid_height(value: &str) -> bool {
+    let (number, range) = if let Some(number) = value.strip_suffix("cm") {
+        (number, 150..=193)
+    } else if let Some(number) = value.strip_suffix("in") {
+        (number, 59..=76)
+    } else {
+        return false;
+    };
+
+    !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+        && number
+            .parse::<u16>()
+            .is_ok_and(|height| range.contains(&height))
+}
+
+pub struct AdventDay04 {
+    passports: Vec<Passport>,
+}
+
+impl AdventDay04 {
+    fn step1(&self) -> usize {
+        self.passports
+            .iter()
+            .filter(|passport| passport.has_required_fields())
+            .count()
+    }
+
+    fn step2(&self) -> usize {
+        self.passports
+            .iter()
+            .filter(|passport| passport.is_valid())
+            .count()
+    }
+}
+
+impl AdventState for AdventDay04 {
+    const INPUT_FILES: &'static [&'static str] = &["test.txt", "test2.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Passport Processing");
+
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        let passports: Vec<_> = blocks(&input_content)
+            .map(Passport::parse)
+            .collect::<Result<_, _>>()?;
+
+        if passports.is_empty() {
+            return Err("no passports in the input".to_owned());
+        }
+
+        Ok(Self { passports })
+    }
+
+    fn part1(&self) -> PartResult {
+        Ok(self.step1().into())
+    }
+
+    fn part2(&self) -> PartResult {
+        Ok(self.step2().into())
+    }
+}
+
+#[test]
+fn test_field_validation() {
+    let examples = [
+        ("byr", "2002", true),
+        ("byr", "2003", false),
+        ("byr", "1920", true),
+        ("byr", "1919", false),
+        ("byr", "02002", false),
+        ("iyr", "2010", true),
+        ("iyr", "2021", false),
+        ("eyr", "2030", true),
+        ("eyr", "2019", false),
+        ("hgt", "60in", true),
+        ("hgt", "190cm", true),
+        ("hgt", "190in", false),
+        ("hgt", "190", false),
+        ("hgt", "149cm", false),
+        ("hgt", "cm", false),
+        ("hgt", "+60in", false),
+        ("hcl", "#123abc", true),
+        ("hcl", "#123abz", false),
+        ("hcl", "123abc", false),
+        ("hcl", "#123ABC", false),
+        ("hcl", "#123abcd", false),
+        ("ecl", "brn", true),
+        ("ecl", "wat", false),
+        ("pid", "000000001", true),
+        ("pid", "0123456789", false),
+        ("pid", "12345678a", false),
+        ("cid", "anything", true),
+        ("foo", "bar", false),
+    ];
+
+    for &(key, value, expected) in &examples {
+        assert_eq!(is_valid_field(key, value), expected, "{}:{}", key, value);
+    }
+}
+
+#[test]
+fn test_samples() {
+    let day = AdventDay04::new(
+        "test.txt",
+        include_str!("../../data/2020/4/test.txt").into(),
+    )
+    .unwrap();
+    assert_eq!(day.step1(), 2);
+    assert_eq!(day.step2(), 2);
+
+    let day = AdventDay04::new(
+        "test2.txt",
+        include_str!("../../data/2020/4/test2.txt").into(),
+    )
+    .unwrap();
+    assert_eq!(day.step1(), 8);
+    assert_eq!(day.step2(), 4);
+}
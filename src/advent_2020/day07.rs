@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use nom::Finish;
+
+use crate::advent_adapters::AdventState;
+use crate::advents::PartResult;
+use crate::helper;
+use crate::helper::memo::Memo;
+use crate::helper::search;
+
+type ParsingError<'a> = helper::nom::VerboseError<&'a str>;
+
+const TARGET_BAG: &str = "shiny gold";
+
+/// A rule stating how many bags of each color a bag must contain
+#[derive(Debug, PartialEq, Eq)]
+struct Rule<'a> {
+    color: &'a str,
+    contents: Vec<(usize, &'a str)>,
+}
+
+impl<'a> Rule<'a> {
+    fn parse(input: &'a str) -> nom::IResult<&'a str, Self, ParsingError<'a>> {
+        nom::error::context(
+            "rule",
+            nom::combinator::map(
+                nom::sequence::terminated(
+                    nom::sequence::separated_pair(
+                        Self::parse_color,
+                        nom::bytes::complete::tag(" bags contain "),
+                        Self::parse_contents,
+                    ),
+                    nom::character::complete::char('.'),
+                ),
+                |(color, contents)| Self { color, contents },
+            ),
+        )(input)
+    }
+
+    /// Colors are always an adjective followed by a color, like `shiny gold`
+    fn parse_color(input: &'a str) -> nom::IResult<&'a str, &'a str, ParsingError<'a>> {
+        nom::error::context(
+            "color",
+            nom::combinator::recognize(nom::sequence::separated_pair(
+                nom::character::complete::alpha1,
+                nom::character::complete::char(' '),
+                nom::character::complete::alpha1,
+            )),
+        )(input)
+    }
+
+    fn parse_contents(
+        input: &'a str,
+    ) -> nom::IResult<&'a str, Vec<(usize, &'a str)>, ParsingError<'a>> {
+        nom::branch::alt((
+            nom::combinator::value(Vec::new(), nom::bytes::complete::tag("no other bags")),
+            nom::multi::separated_list1(nom::bytes::complete::tag(", "), Self::parse_content),
+        ))(input)
+    }
+
+    fn parse_content(input: &'a str) -> nom::IResult<&'a str, (usize, &'a str), ParsingError<'a>> {
+        nom::error::context(
+            "content",
+            nom::sequence::pair(
+                nom::combinator::map_res(nom::character::complete::digit1, str::parse),
+                nom::sequence::delimited(
+                    nom::character::complete::char(' '),
+                    Self::parse_color,
+                    nom::branch::alt((
+                        nom::bytes::complete::tag(" bags"),
+                        nom::bytes::complete::tag(" bag"),
+                    )),
+                ),
+            ),
+        )(input)
+    }
+}
+
+#[derive(Debug)]
+pub struct AdventDay07 {
+    /// Colors and counts of the bags directly inside each bag
+    contents: HashMap<String, Vec<(usize, String)>>,
+    /// Colors of the bags that directly contain each bag
+    containers: HashMap<String, Vec<String>>,
+}
+
+impl AdventDay07 {
+    fn parse(input: &str) -> Result<Self, String> {
+        let mut contents = HashMap::new();
+        let mut containers: HashMap<String, Vec<String>> = HashMap::new();
+
+        for (line, text) in input.lines().enumerate() {
+            let (_, rule) = nom::combinator::all_consuming(Rule::parse)(text)
+                .finish()
+                .map_err(|err| format!("line {}: {}", line + 1, err))?;
+
+            for &(_, inner) in &rule.contents {
+                containers
+                    .entry(inner.to_owned())
+                    .or_default()
+                    .push(rule.color.to_owned());
+            }
+
+            let inner = rule
+                .contents
+                .into_iter()
+                .map(|(count, color)| (count, color.to_owned()))
+                .collect();
+
+            if contents.insert(rule.color.to_owned(), inner).is_some() {
+                return Err(format!(
+                    "line {}: duplicate rule for {}",
+                    line + 1,
+                    rule.color
+                ));
+            }
+        }
+
+        if contents.is_empty() {
+            return Err("no rules in the input".to_owned());
+        }
+
+        Ok(Self {
+            contents,
+            containers,
+        })
+    }
+
+    /// Number of bag colors that can eventually contain the target bag
+    fn step1(&self) -> usize {
+        search::reachable(TARGET_BAG, |color| {
+            self.containers
+                .get(*color)
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+        })
+        .len()
+    }
+
+    /// Number of bags inside the target bag
+    fn step2(&self) -> usize {
+        self.bags_inside(&mut Memo::new(), TARGET_BAG)
+    }
+
+    fn bags_inside<'a>(&'a self, memo: &mut Memo<&'a str, usize>, color: &'a str) -> usize {
+        memo.get_or_insert_with(color, |memo| {
+            self.contents
+                .get(color)
+                .into_iter()
+                .flatten()
+                .map(|(count, inner)| count * (1 + self.bags_inside(memo, inner)))
+                .sum()
+        })
+    }
+}
+
+impl AdventState for AdventDay07 {
+    const INPUT_FILES: &'static [&'static str] = &["test.txt", "test2.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Handy Haversacks");
+
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        Self::parse(&input_content)
+    }
+
+    fn part1(&self) -> PartResult {
+        Ok(self.step1().into())
+    }
+
+    fn part2(&self) -> PartResult {
+        Ok(self.step2().into())
+    }
+}
+
+#[test]
+fn test_parse_rule() {
+    assert_eq!(
+        Rule::parse("light red bags contain 1 bright white bag, 2 muted yellow bags."),
+        Ok((
+            "",
+            Rule {
+                color: "light red",
+                contents: vec![(1, "bright white"), (2, "muted yellow")],
+            }
+        ))
+    );
+    assert_eq!(
+        Rule::parse("faded blue bags contain no other bags."),
+        Ok((
+            "",
+            Rule {
+                color: "faded blue",
+                contents: vec![],
+            }
+        ))
+    );
+
+    assert!(AdventDay07::parse("light red bags contain some bags.")
+        .unwrap_err()
+        .starts_with("line 1: Parse error:"));
+}
+
+#[test]
+fn test_samples() {
+    let day = AdventDay07::parse(include_str!("../../data/2020/7/test.txt")).unwrap();
+    assert_eq!(day.step1(), 4);
+    assert_eq!(day.step2(), 32);
+
+    let day = AdventDay07::parse(include_str!("../../data/2020/7/test2.txt")).unwrap();
+    assert_eq!(day.step1(), 0);
+    assert_eq!(day.step2(), 126);
+}
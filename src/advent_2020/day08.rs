@@ -0,0 +1,81 @@
+use crate::advent_adapters::AdventState;
+use crate::advents::{Answer, PartResult};
+use crate::helper::vm::{self, Exit, Instruction, Machine};
+
+pub struct AdventDay08 {
+    program: Vec<Instruction>,
+}
+
+impl AdventDay08 {
+    /// Accumulator right before any instruction runs a second time
+    fn step1(&self) -> Result<i64, String> {
+        let mut machine = Machine::new(&self.program);
+
+        match machine.run_until_loop_or_halt() {
+            Exit::Loop => Ok(machine.accumulator()),
+            exit => Err(format!(
+                "program stopped at instruction {} without looping: {:?}",
+                machine.pc(),
+                exit
+            )),
+        }
+    }
+
+    /// Accumulator of the only program that halts after swapping one jmp/nop instruction
+    fn step2(&self) -> Result<i64, String> {
+        let mut program = self.program.clone();
+
+        for index in 0..program.len() {
+            let original = program[index];
+            program[index] = match original {
+                Instruction::Jmp(arg) => Instruction::Nop(arg),
+                Instruction::Nop(arg) => Instruction::Jmp(arg),
+                Instruction::Acc(_) => continue,
+            };
+
+            let mut machine = Machine::new(&program);
+            if machine.run_until_loop_or_halt() == Exit::Halted {
+                return Ok(machine.accumulator());
+            }
+
+            program[index] = original;
+        }
+
+        Err("no single swap makes the program halt".to_owned())
+    }
+}
+
+impl AdventState for AdventDay08 {
+    const INPUT_FILES: &'static [&'static str] = &["test.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Handheld Halting");
+
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        let program = vm::parse_program(&input_content)?;
+
+        if program.is_empty() {
+            return Err("the program is empty".to_owned());
+        }
+
+        Ok(Self { program })
+    }
+
+    fn part1(&self) -> PartResult {
+        self.step1().map(Answer::from)
+    }
+
+    fn part2(&self) -> PartResult {
+        self.step2().map(Answer::from)
+    }
+}
+
+#[test]
+fn test_sample() {
+    let day = AdventDay08::new(
+        "test.txt",
+        include_str!("../../data/2020/8/test.txt").into(),
+    )
+    .unwrap();
+
+    assert_eq!(day.step1(), Ok(5));
+    assert_eq!(day.step2(), Ok(8));
+}
@@ -0,0 +1,139 @@
+use crate::advent_adapters::AdventState;
+use crate::advents::{Answer, PartResult};
+
+/// Largest joltage difference an adapter can take
+const MAX_GAP: u32 = 3;
+
+pub struct AdventDay10 {
+    /// The whole chain sorted by joltage: the outlet, every adapter, and the device
+    chain: Vec<u32>,
+}
+
+impl AdventDay10 {
+    fn parse(input: &str) -> Result<Self, String> {
+        let mut adapters: Vec<u32> = input
+            .lines()
+            .enumerate()
+            .map(|(line, text)| {
+                text.trim()
+                    .parse()
+                    .map_err(|_| format!("line {}: invalid joltage '{}'", line + 1, text))
+            })
+            .collect::<Result<_, _>>()?;
+
+        if adapters.is_empty() {
+            return Err("no adapters in the input".to_owned());
+        }
+
+        adapters.sort_unstable();
+
+        let device = adapters[adapters.len() - 1] + MAX_GAP;
+        let chain: Vec<_> = std::iter::once(0)
+            .chain(adapters)
+            .chain(std::iter::once(device))
+            .collect();
+
+        for pair in chain.windows(2) {
+            match pair[1] - pair[0] {
+                0 => return Err(format!("duplicate adapter of {} jolts", pair[0])),
+                gap if gap > MAX_GAP => {
+                    return Err(format!(
+                        "gap of {} jolts between {} and {} can't be bridged",
+                        gap, pair[0], pair[1]
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { chain })
+    }
+
+    /// Number of 1-jolt differences multiplied by the number of 3-jolt differences
+    fn step1(&self) -> usize {
+        let count_gaps = |gap| {
+            self.chain
+                .windows(2)
+                .filter(|pair| pair[1] - pair[0] == gap)
+                .count()
+        };
+
+        count_gaps(1) * count_gaps(3)
+    }
+
+    /// Number of distinct arrangements of adapters connecting the outlet to the device.
+    ///
+    /// `ways[i]` counts the arrangements ending at the i-th joltage of the chain, which is the sum
+    /// of the ways of the (at most three) previous joltages within reach.
+    ///
+    /// This can't overflow for puzzle inputs: they have about a hundred adapters with runs of at
+    /// most four consecutive 1-jolt differences. Each run takes five adapters including the 3-jolt
+    /// gap closing it and multiplies the count by at most 7, keeping the total under
+    /// 7^20 ≈ 8·10^16. Other inputs report the overflow instead of a wrong answer.
+    fn step2(&self) -> Result<u64, String> {
+        let mut ways = vec![0u64; self.chain.len()];
+        ways[0] = 1;
+
+        for i in 1..self.chain.len() {
+            let reachable = (i.saturating_sub(MAX_GAP as usize)..i)
+                .filter(|&j| self.chain[i] - self.chain[j] <= MAX_GAP);
+
+            for j in reachable {
+                ways[i] = ways[i]
+                    .checked_add(ways[j])
+                    .ok_or("the number of arrangements overflows a u64")?;
+            }
+        }
+
+        Ok(ways[ways.len() - 1])
+    }
+}
+
+impl AdventState for AdventDay10 {
+    const INPUT_FILES: &'static [&'static str] = &["test.txt", "test2.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Adapter Array");
+
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        Self::parse(&input_content)
+    }
+
+    fn part1(&self) -> PartResult {
+        Ok(self.step1().into())
+    }
+
+    fn part2(&self) -> PartResult {
+        self.step2().map(Answer::from)
+    }
+}
+
+#[test]
+fn test_samples() {
+    let day = AdventDay10::parse(include_str!("../../data/2020/10/test.txt")).unwrap();
+    assert_eq!(day.step1(), 7 * 5);
+    assert_eq!(day.step2(), Ok(8));
+
+    let day = AdventDay10::parse(include_str!("../../data/2020/10/test2.txt")).unwrap();
+    assert_eq!(day.step1(), 22 * 10);
+    assert_eq!(day.step2(), Ok(19208));
+}
+
+#[test]
+fn test_invalid_chains() {
+    assert_eq!(
+        AdventDay10::parse("1\n4\n4\n5").err(),
+        Some("duplicate adapter of 4 jolts".to_owned())
+    );
+    assert_eq!(
+        AdventDay10::parse("1\n2\n6").err(),
+        Some("gap of 4 jolts between 2 and 6 can't be bridged".to_owned())
+    );
+    assert_eq!(
+        AdventDay10::parse("4\n5").err(),
+        Some("gap of 4 jolts between 0 and 4 can't be bridged".to_owned())
+    );
+    assert_eq!(
+        AdventDay10::parse("1\nx").err(),
+        Some("line 2: invalid joltage 'x'".to_owned())
+    );
+    assert!(AdventDay10::parse("").is_err());
+}
@@ -0,0 +1,138 @@
+use crate::advent_adapters::AdventState;
+use crate::advents::PartResult;
+use crate::helper::grid::{Grid, DIRECTIONS8};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Seat {
+    Floor,
+    Empty,
+    Occupied,
+}
+
+pub struct AdventDay11 {
+    seats: Grid<Seat>,
+}
+
+impl AdventDay11 {
+    /// Occupied seats once the layout stabilizes, considering the adjacent seats
+    fn step1(&self) -> usize {
+        settle(
+            &self.seats,
+            |seats, row, column| seats.neighbors8(row, column).collect(),
+            4,
+        )
+    }
+
+    /// Occupied seats once the layout stabilizes, considering the first seat visible in each
+    /// direction
+    fn step2(&self) -> usize {
+        settle(
+            &self.seats,
+            |seats, row, column| {
+                DIRECTIONS8
+                    .iter()
+                    .filter_map(|&direction| {
+                        seats
+                            .ray(row, column, direction)
+                            .find(|&(r, c)| seats.get(r, c) != Some(&Seat::Floor))
+                    })
+                    .collect()
+            },
+            5,
+        )
+    }
+}
+
+/// Apply the seating rules until nothing changes, returning the number of occupied seats.
+///
+/// `neighbors` lists the seats that affect a given seat, and a seat is vacated when at least
+/// `threshold` of them are occupied. The neighbors of each seat are computed once, since seats
+/// never become floor or the other way around.
+fn settle(
+    seats: &Grid<Seat>,
+    neighbors: impl Fn(&Grid<Seat>, usize, usize) -> Vec<(usize, usize)>,
+    threshold: usize,
+) -> usize {
+    let neighbors = Grid::from_fn(seats.height(), seats.width(), |row, column| {
+        neighbors(seats, row, column)
+    });
+
+    let mut current = seats.clone();
+    loop {
+        let next = step(&current, &neighbors, threshold);
+
+        if next == current {
+            return current
+                .iter()
+                .filter(|&&seat| seat == Seat::Occupied)
+                .count();
+        }
+
+        current = next;
+    }
+}
+
+fn step(seats: &Grid<Seat>, neighbors: &Grid<Vec<(usize, usize)>>, threshold: usize) -> Grid<Seat> {
+    Grid::from_fn(seats.height(), seats.width(), |row, column| {
+        let occupied = || {
+            neighbors
+                .get(row, column)
+                .into_iter()
+                .flatten()
+                .filter(|&&(r, c)| seats.get(r, c) == Some(&Seat::Occupied))
+                .count()
+        };
+
+        match seats.get(row, column) {
+            Some(Seat::Empty) if occupied() == 0 => Seat::Occupied,
+            Some(Seat::Occupied) if occupied() >= threshold => Seat::Empty,
+            Some(&seat) => seat,
+            None => unreachable!("both grids have the same size"),
+        }
+    })
+}
+
+impl AdventState for AdventDay11 {
+    const INPUT_FILES: &'static [&'static str] = &["test.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Seating System");
+
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        let seats = Grid::parse(&input_content, |cell| match cell {
+            '.' => Some(Seat::Floor),
+            'L' => Some(Seat::Empty),
+            '#' => Some(Seat::Occupied),
+            _ => None,
+        })
+        .map_err(|err| err.to_string())?;
+
+        Ok(Self { seats })
+    }
+
+    fn part1(&self) -> PartResult {
+        Ok(self.step1().into())
+    }
+
+    fn part2(&self) -> PartResult {
+        Ok(self.step2().into())
+    }
+}
+
+#[test]
+fn test_sample() {
+    let day = AdventDay11::new(
+        "test.txt",
+        include_str!("../../data/2020/11/test.txt").into(),
+    )
+    .unwrap();
+
+    assert_eq!(day.step1(), 37);
+    assert_eq!(day.step2(), 26);
+}
+
+#[test]
+fn test_stabilizes_without_seats() {
+    let day = AdventDay11::new("floor", "....\n....\n....\n".into()).unwrap();
+
+    assert_eq!(day.step1(), 0);
+    assert_eq!(day.step2(), 0);
+}
@@ -0,0 +1,143 @@
+use std::convert::TryFrom;
+
+use crate::advent_adapters::AdventState;
+use crate::advents::{Answer, PartResult};
+use crate::helper::math::crt;
+
+pub struct AdventDay13 {
+    earliest_departure: u64,
+    /// Bus IDs along with their position in the schedule, skipping the `x` entries
+    buses: Vec<(usize, u64)>,
+}
+
+impl AdventDay13 {
+    fn parse(input: &str) -> Result<Self, String> {
+        let mut lines = input.lines();
+
+        let earliest_departure = lines
+            .next()
+            .ok_or("missing earliest departure")?
+            .trim()
+            .parse()
+            .map_err(|err| format!("invalid earliest departure: {}", err))?;
+
+        let buses = Self::parse_schedule(lines.next().ok_or("missing bus schedule")?)?;
+
+        Ok(Self {
+            earliest_departure,
+            buses,
+        })
+    }
+
+    fn parse_schedule(schedule: &str) -> Result<Vec<(usize, u64)>, String> {
+        let buses: Vec<_> = schedule
+            .trim()
+            .split(',')
+            .enumerate()
+            .filter(|&(_, entry)| entry != "x")
+            .map(|(offset, entry)| match entry.parse() {
+                Ok(0) | Err(_) => Err(format!("invalid bus ID '{}'", entry)),
+                Ok(id) => Ok((offset, id)),
+            })
+            .collect::<Result<_, _>>()?;
+
+        if buses.is_empty() {
+            return Err("no buses in service".to_owned());
+        }
+
+        Ok(buses)
+    }
+
+    /// ID of the first bus to depart after the earliest departure multiplied by the wait for it
+    fn step1(&self) -> u64 {
+        let wait = |id: u64| (id - self.earliest_departure % id) % id;
+
+        self.buses
+            .iter()
+            .map(|&(_, id)| (wait(id), id))
+            .min()
+            .map(|(wait, id)| wait * id)
+            .expect("there is at least one bus")
+    }
+
+    /// Earliest timestamp where each bus departs as many minutes after it as its position in the
+    /// schedule.
+    ///
+    /// Bus `id` at offset `o` departs at `t + o` when `t ≡ -o (mod id)`, so this is a
+    /// Chinese Remainder Theorem instance. The product of the IDs goes beyond 10^14 for puzzle
+    /// inputs, so everything is computed in `i128` to keep the intermediate products in range.
+    fn step2(&self) -> Result<i128, String> {
+        let congruences: Vec<_> = self
+            .buses
+            .iter()
+            .map(|&(offset, id)| {
+                let offset = i128::try_from(offset).expect("offsets fit in an i128");
+                (-offset, i128::from(id))
+            })
+            .collect();
+
+        crt(&congruences)
+            .map(|(timestamp, _)| timestamp)
+            .ok_or_else(|| "the buses never depart in sequence".to_owned())
+    }
+}
+
+impl AdventState for AdventDay13 {
+    const INPUT_FILES: &'static [&'static str] = &["test.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Shuttle Search");
+
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        Self::parse(&input_content)
+    }
+
+    fn part1(&self) -> PartResult {
+        Ok(self.step1().into())
+    }
+
+    fn part2(&self) -> PartResult {
+        self.step2().map(Answer::from)
+    }
+}
+
+#[test]
+fn test_sample() {
+    let day = AdventDay13::parse(include_str!("../../data/2020/13/test.txt")).unwrap();
+
+    assert_eq!(day.buses, [(0, 7), (1, 13), (4, 59), (6, 31), (7, 19)]);
+    assert_eq!(day.step1(), 295);
+    assert_eq!(day.step2(), Ok(1068781));
+}
+
+#[test]
+fn test_schedules() {
+    for &(schedule, expected) in &[
+        ("17,x,13,19", 3417),
+        ("67,7,59,61", 754018),
+        ("67,x,7,59,61", 779210),
+        ("67,7,x,59,61", 1261476),
+        ("1789,37,47,1889", 1202161486),
+    ] {
+        let day = AdventDay13 {
+            earliest_departure: 0,
+            buses: AdventDay13::parse_schedule(schedule).unwrap(),
+        };
+
+        assert_eq!(day.step2(), Ok(expected), "schedule {}", schedule);
+    }
+}
+
+#[test]
+fn test_invalid_schedules() {
+    assert_eq!(
+        AdventDay13::parse_schedule("x,x").err(),
+        Some("no buses in service".to_owned())
+    );
+    assert_eq!(
+        AdventDay13::parse_schedule("7,y").err(),
+        Some("invalid bus ID 'y'".to_owned())
+    );
+    assert_eq!(
+        AdventDay13::parse_schedule("0,7").err(),
+        Some("invalid bus ID '0'".to_owned())
+    );
+}
@@ -1,44 +1,249 @@
-use crate::advents::Advent;
-use std::collections::HashMap;
+use crate::advent_adapters::AdventState;
+use crate::advents::PartResult;
+use crate::helper::parse;
+use std::io::IsTerminal;
 
-pub struct AdventDay15;
+const LOG_TARGET: &str = "aoc::y2020::d15";
 
-impl Advent for AdventDay15 {
-    fn get_index(&self) -> u8 {
-        15
+/// Turn of the answer to each part
+const PART1_TURN: usize = 2020;
+const PART2_TURN: usize = 30_000_000;
+
+/// Turns whose spoken number part 2 reports as soon as it reaches them. Part 1 reports its own
+/// answer, see `with_milestones` to add it
+const MILESTONES: &[usize] = &[PART2_TURN];
+
+/// Turns between two reports of the progress of a game
+const PROGRESS_EVERY: usize = 5_000_000;
+
+pub struct AdventDay15 {
+    seeds: Vec<usize>,
+    /// Turns reported by part 2, in order, see `with_milestones`
+    milestones: Vec<usize>,
+}
+
+impl AdventDay15 {
+    /// Also report the numbers spoken at these turns while playing part 2
+    pub fn with_milestones(mut self, turns: &[usize]) -> Self {
+        self.milestones.extend_from_slice(turns);
+        self.milestones.sort_unstable();
+        self.milestones.dedup();
+        self
+    }
+
+    /// Number spoken at the given turn, counting from 1. Panics on turn 0
+    pub fn nth_spoken(&self, turn: usize) -> usize {
+        self.play(&[turn], |_, _| {})
     }
 
-    fn process_input(&self, mut data: Vec<String>) {
-        let mut seq_state: Vec<usize> = data
-            .pop()
-            .unwrap()
-            .split(',')
-            .map(|s| s.parse().unwrap())
-            .collect();
+    /// Play until the last of the ordered `milestones`, calling `on_milestone` with each one and
+    /// the number spoken at that turn as soon as it is reached, and return the number spoken at
+    /// the last milestone.
+    /// Long games log their progress, unless stderr is not a terminal or `-q` is given. Panics
+    /// on turn 0, the turns counting from 1
+    pub fn play(&self, milestones: &[usize], mut on_milestone: impl FnMut(usize, usize)) -> usize {
+        assert!(
+            !milestones.contains(&0),
+            "the turns of day 15 are counted from 1"
+        );
+        let last_turn = milestones.last().copied().unwrap_or_default();
+        let show_progress = last_turn >= PROGRESS_EVERY
+            && log::log_enabled!(target: LOG_TARGET, log::Level::Warn)
+            && std::io::stderr().is_terminal();
+
+        let mut seq_state = self.seeds.clone();
+        let mut milestones = milestones.iter().copied().peekable();
+        let mut spoken = None;
+
+        // Turns of the starting numbers are already known
+        while let Some(turn) = milestones.next_if(|&turn| turn < seq_state.len()) {
+            spoken = Some(seq_state[turn - 1]);
+            on_milestone(turn, seq_state[turn - 1]);
+        }
 
         let mut next_value = seq_state.pop().unwrap();
         let mut current_turn = seq_state.len() + 1;
 
-        let mut entries: HashMap<usize, usize> = seq_state
-            .iter()
-            .cloned()
-            .enumerate()
-            .map(|(v, k)| (k, v + 1))
-            .collect();
+        // Turn each number was last spoken at, 0 when it wasn't yet. Numbers spoken after the
+        // starting ones are ages, always less than the turn, so the table is as long as the game
+        // unless a starting number is larger. At 4 bytes per turn it takes 120 MB for part 2,
+        // a bit more than the 107 MB of a map of the numbers spoken, but it is about 3x faster
+        let largest_seed = self.seeds.iter().copied().max().unwrap_or_default();
+        let size = last_turn.max(largest_seed + 1);
+        assert!(
+            size <= u32::MAX as usize,
+            "{} turns or starting number {} don't fit the table of day 15",
+            last_turn,
+            largest_seed
+        );
+        let mut last_seen = vec![0u32; size];
+        for (turn, &number) in seq_state.iter().enumerate() {
+            last_seen[number] = turn as u32 + 1;
+        }
+
+        for milestone in milestones {
+            while current_turn < milestone {
+                let last_occurrence = &mut last_seen[next_value];
+
+                next_value = match *last_occurrence {
+                    0 => 0,
+                    turn => current_turn - turn as usize,
+                };
+                *last_occurrence = current_turn as u32;
 
-        while current_turn < 30_000_000 {
-            if current_turn == 2020 {
-                println!("The response for stage 1 is: {}", next_value);
+                current_turn += 1;
+
+                if show_progress && current_turn.is_multiple_of(PROGRESS_EVERY) {
+                    log::warn!(
+                        target: LOG_TARGET,
+                        "turn {} of {} ({}%)",
+                        current_turn,
+                        last_turn,
+                        current_turn * 100 / last_turn
+                    );
+                }
             }
 
-            let last_occurrence = entries.entry(next_value).or_insert(current_turn);
+            spoken = Some(next_value);
+            on_milestone(milestone, next_value);
+        }
+
+        spoken.unwrap_or(next_value)
+    }
+}
 
-            next_value = current_turn - *last_occurrence;
-            *last_occurrence = current_turn;
+impl AdventState for AdventDay15 {
+    const INPUT_FILES: &'static [&'static str] = &["input.txt"];
+    const TITLE: Option<&'static str> = Some("Rambunctious Recitation");
+    // The second part plays 30 million turns
+    const SLOW: bool = true;
 
-            current_turn += 1;
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        let seeds: Vec<usize> = parse::numbers(&input_content, ",")
+            .map_err(|err| format!("invalid starting numbers: {}", err))?;
+        if let Some(seed) = seeds.iter().find(|&&seed| seed >= u32::MAX as usize) {
+            return Err(format!("starting number {} is too large", seed));
         }
 
-        println!("The response for stage 2 is: {}", next_value);
+        Ok(Self {
+            seeds,
+            milestones: MILESTONES.to_vec(),
+        })
+    }
+
+    fn part1(&self) -> PartResult {
+        Ok(self.nth_spoken(PART1_TURN).into())
+    }
+
+    fn part2(&self) -> PartResult {
+        let mut answer = None;
+        self.play(&self.milestones, |turn, spoken| {
+            print_answer!(format!("turn {}", turn), spoken);
+            if turn == PART2_TURN {
+                answer = Some(spoken);
+            }
+        });
+
+        Ok(answer.expect("the milestones include part 2").into())
+    }
+}
+
+/// Starting numbers of the examples of the puzzle, with the numbers spoken at the turns of both
+/// parts
+#[cfg(test)]
+const EXAMPLES: &[(&str, usize, usize)] = &[
+    ("0,3,6", 436, 175_594),
+    ("1,3,2", 1, 2578),
+    ("2,1,3", 10, 3_544_142),
+    ("1,2,3", 27, 261_214),
+    ("2,3,1", 78, 6_895_259),
+    ("3,2,1", 438, 18),
+    ("3,1,2", 1836, 362),
+];
+
+/// Numbers spoken at each of `turns` by the game starting with `seeds`
+#[cfg(test)]
+fn spoken(seeds: &str, turns: impl IntoIterator<Item = usize>) -> Vec<usize> {
+    let day = AdventDay15::new("test.txt", seeds.to_owned()).unwrap();
+    turns.into_iter().map(|turn| day.nth_spoken(turn)).collect()
+}
+
+#[test]
+fn test_examples() {
+    for &(seeds, part1, _) in EXAMPLES {
+        assert_eq!(spoken(seeds, [PART1_TURN]), [part1], "{}", seeds);
     }
+
+    assert_eq!(spoken("0,3,6", 1..=10), [0, 3, 6, 0, 3, 3, 1, 0, 4, 0]);
+}
+
+/// Thirty million turns of each example, run with `cargo test --release -- --ignored
+/// test_examples_part2`
+#[ignore]
+#[test]
+fn test_examples_part2() {
+    for &(seeds, _, part2) in EXAMPLES {
+        assert_eq!(spoken(seeds, [PART2_TURN]), [part2], "{}", seeds);
+    }
+}
+
+#[test]
+fn test_degenerate_seeds() {
+    assert_eq!(spoken("0", 1..=10), [0, 0, 1, 0, 2, 0, 2, 2, 1, 6]);
+
+    // A repeated starting number is last spoken at its last turn
+    assert_eq!(spoken("1,1", [1, 2, 3, 10, PART1_TURN]), [1; 5]);
+    assert_eq!(spoken("3,1,3", 1..=10), [3, 1, 3, 2, 0, 0, 1, 5, 0, 3]);
+    assert_eq!(spoken("2,2,2", [4, 10, PART1_TURN]), [1, 3, 32]);
+}
+
+#[test]
+fn test_milestones() {
+    let day = AdventDay15::new("test.txt", "0,3,6".to_owned())
+        .unwrap()
+        .with_milestones(&[10, 2, 4]);
+    assert_eq!(day.milestones, [2, 4, 10, PART2_TURN]);
+
+    let mut reached = Vec::new();
+    let last = day.play(&[2, 4, 10, 2020], |turn, spoken| {
+        reached.push((turn, spoken))
+    });
+
+    assert_eq!(reached, [(2, 3), (4, 0), (10, 0), (2020, 436)]);
+    assert_eq!(last, 436);
+    assert_eq!(day.nth_spoken(2020), 436);
+    assert_eq!(day.nth_spoken(3), 6);
+    assert_eq!(day.nth_spoken(1), 0);
+}
+
+#[test]
+fn test_memory() {
+    use crate::helper::counting_alloc::peak_memory;
+
+    // The last-seen turns take 4 bytes per turn played
+    let day = AdventDay15::new("test.txt", "0,3,6".to_owned()).unwrap();
+    let (spoken, peak) = peak_memory(|| day.nth_spoken(1_000_000));
+    assert_eq!(spoken, 130);
+    assert!(peak <= 4_000_100, "peak of {} bytes", peak);
+
+    // The table grows to fit starting numbers larger than the turns played
+    let day = AdventDay15::new("test.txt", "9000,9000".to_owned()).unwrap();
+    assert_eq!(day.nth_spoken(3), 1);
+    assert_eq!(day.nth_spoken(4), 0);
+
+    assert!(AdventDay15::new("test.txt", "0,5000000000".to_owned()).is_err());
+}
+
+#[test]
+#[should_panic(expected = "the turns of day 15 are counted from 1")]
+fn test_turn_zero() {
+    let day = AdventDay15::new("test.txt", "0,3,6".to_owned()).unwrap();
+    day.nth_spoken(0);
+}
+
+#[test]
+#[should_panic(expected = "don't fit the table of day 15")]
+fn test_too_many_turns() {
+    let day = AdventDay15::new("test.txt", "0,3,6".to_owned()).unwrap();
+    day.nth_spoken(u32::MAX as usize + 1);
 }
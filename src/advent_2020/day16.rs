@@ -1,7 +1,17 @@
+use std::fmt;
 use std::iter::FromIterator;
 use std::str::FromStr;
 
-use crate::advents::Advent;
+use itertools::Itertools;
+
+use crate::advents::{Advent, DayResult, Parts};
+use crate::helper::bitset::BitSet64;
+use crate::helper::parse;
+
+const LOG_TARGET: &str = "aoc::y2020::d16";
+
+/// Fields multiplied together in the answer to part 2, the samples have none
+const DEPARTURE_FIELDS: usize = 6;
 
 pub struct AdventDay16;
 
@@ -32,131 +42,355 @@ impl PossibleFields {
     fn fits(&self, value: usize) -> bool {
         self.0.iter().any(|v| v.fits(value))
     }
+
+    /// Indices of the fields that accept the given value
+    fn fitting_fields(&self, value: usize) -> BitSet64 {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| field.fits(value))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
 }
 
-impl AdventDay16 {
-    fn process_sample(&self, data: String) {
-        let mut lines = data.split('\n');
+struct TicketNotes {
+    fields: PossibleFields,
+    my_ticket: Vec<usize>,
+    nearby_tickets: Vec<Vec<usize>>,
+}
+
+impl TicketNotes {
+    fn parse(data: &str) -> Result<Self, Day16Error> {
+        let mut lines = data.lines();
 
         // Parse possible fields
         let fields: PossibleFields = lines
             .by_ref()
             .take_while(|l| !l.is_empty())
             .map(FromStr::from_str)
-            .collect::<Result<_, _>>()
-            .expect("could not parse possible fields");
+            .collect::<Result<_, String>>()
+            .map_err(|err| Day16Error::Malformed(format!("invalid field, {}", err)))?;
 
-        // Sanity check
-        assert_eq!(lines.next().unwrap(), "your ticket:");
+        expect_line(&mut lines, "your ticket:")?;
 
         let my_ticket: Vec<usize> = lines
             .next()
-            .expect("missing 'my ticket' line")
-            .split(',')
-            .map(FromStr::from_str)
-            .collect::<Result<_, _>>()
-            .expect("invalid 'my ticket' line");
+            .ok_or_else(|| Day16Error::Malformed("missing 'my ticket' line".to_owned()))
+            .and_then(|line| {
+                parse::numbers(line, ",").map_err(|err| {
+                    Day16Error::Malformed(format!("invalid 'my ticket' line, {}", err))
+                })
+            })?;
 
-        // Sanity check
-        assert_eq!(lines.next().unwrap(), "");
-        assert_eq!(lines.next().unwrap(), "nearby tickets:");
+        expect_line(&mut lines, "")?;
+        expect_line(&mut lines, "nearby tickets:")?;
 
-        let mut nearby_tickets: Vec<Vec<usize>> = lines
+        let nearby_tickets: Vec<Vec<usize>> = lines
             .take_while(|s| !s.is_empty())
-            .map(|line| {
-                line.split(',')
-                    .map(FromStr::from_str)
-                    .collect::<Result<_, _>>()
-            })
+            .map(|line| parse::numbers(line, ","))
             .collect::<Result<_, _>>()
-            .expect("could not parse nearby tickets");
+            .map_err(|err| Day16Error::Malformed(format!("invalid nearby ticket, {}", err)))?;
+
+        if let Some(ticket) = nearby_tickets
+            .iter()
+            .find(|ticket| ticket.len() != my_ticket.len())
+        {
+            return Err(Day16Error::Malformed(format!(
+                "a nearby ticket has {} values, mine has {}",
+                ticket.len(),
+                my_ticket.len()
+            )));
+        }
+
+        Ok(Self {
+            fields,
+            my_ticket,
+            nearby_tickets,
+        })
+    }
 
-        // Step 1: Calculate the scanning error rate
-        let ticket_scanning_error_rate: usize = nearby_tickets
+    /// Check each value of my ticket fits at least one field, the puzzle guarantees it is valid
+    fn check_my_ticket(&self) -> Result<(), Day16Error> {
+        match self
+            .my_ticket
             .iter()
-            .flat_map(|v| v.iter())
-            .filter(|&&v| !fields.fits(v))
-            .copied()
-            .sum();
+            .enumerate()
+            .find(|(_, &value)| !self.fields.fits(value))
+        {
+            Some((column, &value)) => Err(Day16Error::InvalidTicket { column, value }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Skip the next line of the notes, which must be `expected`
+fn expect_line<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    expected: &str,
+) -> Result<(), Day16Error> {
+    match lines.next() {
+        Some(line) if line == expected => Ok(()),
+        found => Err(Day16Error::Malformed(format!(
+            "expected {:?}, found {}",
+            expected,
+            found.map_or("the end of the notes".to_owned(), |line| format!(
+                "{:?}",
+                line
+            ))
+        ))),
+    }
+}
 
-        println!("Answer to step 1 is: {}", ticket_scanning_error_rate);
+/// Why the notes can't be solved
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Day16Error {
+    /// The notes don't follow the layout of the puzzle
+    Malformed(String),
+    /// More fields than the candidates of a column can track
+    TooManyFields(usize),
+    /// A value of my ticket fits no field
+    InvalidTicket { column: usize, value: usize },
+    /// The tickets leave these columns with more than one possible field, or none
+    Unsolved(Vec<usize>),
+    /// The field assigned to a column is not one of the notes
+    UnknownField { column: usize, field: String },
+    /// A value of my ticket doesn't fit the field assigned to its column
+    FieldMismatch {
+        column: usize,
+        value: usize,
+        field: String,
+    },
+    /// My ticket has `found` departure columns instead of the `expected` ones
+    DepartureFields { expected: usize, found: usize },
+    /// The product of these departure values overflows
+    Overflow(Vec<u64>),
+}
 
-        // Discard all invalid tickets
-        for i in (0..nearby_tickets.len()).rev() {
-            if nearby_tickets[i].iter().any(|&f| !fields.fits(f)) {
-                nearby_tickets.swap_remove(i);
+impl fmt::Display for Day16Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Day16Error::Malformed(reason) => write!(f, "malformed notes: {}", reason),
+            Day16Error::TooManyFields(count) => write!(
+                f,
+                "{} fields, at most {} are supported",
+                count,
+                BitSet64::CAPACITY
+            ),
+            Day16Error::InvalidTicket { column, value } => write!(
+                f,
+                "column {} of my ticket, {}, fits no field",
+                column, value
+            ),
+            Day16Error::Unsolved(columns) => write!(
+                f,
+                "could not solve the fields of columns {}",
+                columns.iter().join(", ")
+            ),
+            Day16Error::UnknownField { column, field } => write!(
+                f,
+                "column {} was assigned an unknown field {}",
+                column, field
+            ),
+            Day16Error::FieldMismatch {
+                column,
+                value,
+                field,
+            } => write!(
+                f,
+                "column {} of my ticket, {}, does not fit its field {}",
+                column, value, field
+            ),
+            Day16Error::DepartureFields { expected, found } => {
+                write!(f, "expected {} departure fields, found {}", expected, found)
             }
+            Day16Error::Overflow(values) => write!(f, "the product of {:?} overflows", values),
         }
-        println!("{} valid tickets", nearby_tickets.len());
-
-        let field_solution = self.solve_fields(&fields, &nearby_tickets);
-
-        let solution: usize = my_ticket
-            .into_iter()
-            .zip(field_solution.into_iter())
-            .filter_map(|(field_val, name)| {
-                if name.starts_with("departure") {
-                    Some(field_val)
-                } else {
-                    None
-                }
-            })
-            .product();
+    }
+}
+
+/// Answers found in the notes, see `solve`
+#[derive(Debug, PartialEq, Eq)]
+struct Day16Solution {
+    /// Sum of the values of the nearby tickets that fit no field, the answer to part 1
+    error_rate: usize,
+    /// Name of the field of each column of the tickets, which fails on its own when the tickets
+    /// don't tell the fields apart, leaving the answer to part 1 intact
+    field_order: Result<Vec<String>, Day16Error>,
+    /// Product of the departure values of my ticket, the answer to part 2. It fails with the field
+    /// order, or when the field order doesn't fit my ticket
+    departure_product: Result<u64, Day16Error>,
+}
 
-        println!("Answer to step 2 is: {}", solution);
+/// Check each value of my ticket fits the field assigned to its column
+fn check_solved_ticket(
+    fields: &PossibleFields,
+    field_solution: &[String],
+    my_ticket: &[usize],
+) -> Result<(), Day16Error> {
+    for (column, (name, &value)) in field_solution.iter().zip(my_ticket).enumerate() {
+        let field = fields
+            .0
+            .iter()
+            .find(|field| &field.name == name)
+            .ok_or_else(|| Day16Error::UnknownField {
+                column,
+                field: name.clone(),
+            })?;
+
+        if !field.fits(value) {
+            return Err(Day16Error::FieldMismatch {
+                column,
+                value,
+                field: name.clone(),
+            });
+        }
     }
 
-    fn solve_fields<'a>(
-        &self,
-        fields: &'a PossibleFields,
-        nearby_fields: &[Vec<usize>],
-    ) -> Vec<&'a str> {
-        let mut field_possibilities: Vec<Vec<&PossibleField>> =
-            vec![fields.0.iter().collect(); nearby_fields[0].len()];
+    Ok(())
+}
+
+/// Solve both parts from the notes
+fn solve(data: &str) -> Result<Day16Solution, Day16Error> {
+    let notes = TicketNotes::parse(data)?;
+    notes.check_my_ticket()?;
+
+    let TicketNotes {
+        fields,
+        my_ticket,
+        mut nearby_tickets,
+    } = notes;
+
+    // Step 1: Calculate the scanning error rate
+    let error_rate: usize = nearby_tickets
+        .iter()
+        .flat_map(|v| v.iter())
+        .filter(|&&v| !fields.fits(v))
+        .copied()
+        .sum();
+
+    // Discard all invalid tickets
+    for i in (0..nearby_tickets.len()).rev() {
+        if nearby_tickets[i].iter().any(|&f| !fields.fits(f)) {
+            nearby_tickets.swap_remove(i);
+        }
+    }
+    log::debug!(target: LOG_TARGET, "{} valid tickets", nearby_tickets.len());
+
+    let field_order = solve_fields(&fields, my_ticket.len(), &nearby_tickets);
+    if let Ok(field_order) = &field_order {
+        log::debug!(
+            target: LOG_TARGET,
+            "Resolved fields:\n{}",
+            field_table(field_order, &my_ticket)
+        );
+    }
 
-        let mut field_solution: Vec<Option<&str>> = vec![None; field_possibilities.len()];
+    let product = field_order.clone().and_then(|field_order| {
+        check_solved_ticket(&fields, &field_order, &my_ticket)?;
+        departure_product(&field_order, &my_ticket)
+    });
 
-        for ticket in nearby_fields {
-            for (field_idx, &value) in ticket.iter().enumerate() {
-                let field_desc = &mut field_possibilities[field_idx];
+    Ok(Day16Solution {
+        error_rate,
+        field_order,
+        departure_product: product,
+    })
+}
 
-                let exclusion: Vec<_> = field_desc
-                    .iter()
-                    .enumerate()
-                    .rev()
-                    .filter(|(_, &field)| !field.fits(value))
-                    .map(|(idx, _)| idx)
-                    .collect();
+/// Assign a field to each of the `columns` of the tickets, returning the name of the field of
+/// each column. Without a valid nearby ticket, every field fits every column.
+///
+/// The candidate fields of each column are tracked as a set of indices into `fields.0`, so
+/// at most 64 fields are supported.
+fn solve_fields(
+    fields: &PossibleFields,
+    columns: usize,
+    nearby_fields: &[Vec<usize>],
+) -> Result<Vec<String>, Day16Error> {
+    if fields.0.len() > BitSet64::CAPACITY {
+        return Err(Day16Error::TooManyFields(fields.0.len()));
+    }
 
-                for idx in exclusion {
-                    field_desc.swap_remove(idx);
-                }
-            }
+    let all_fields = BitSet64::full(fields.0.len());
+    let mut field_possibilities = vec![all_fields; columns];
+
+    for ticket in nearby_fields {
+        for (candidates, &value) in field_possibilities.iter_mut().zip(ticket) {
+            *candidates &= fields.fitting_fields(value);
         }
+    }
+
+    let mut field_solution: Vec<Option<String>> = vec![None; field_possibilities.len()];
 
-        'outer: loop {
-            for field_idx in 0..field_possibilities.len() {
-                if field_possibilities[field_idx].len() == 1 {
-                    let field = field_possibilities[field_idx].pop().unwrap();
-                    for desc in field_possibilities.iter_mut() {
-                        if let Some(remove_idx) = desc.iter().position(|&f| f.name == field.name) {
-                            desc.swap_remove(remove_idx);
-                        }
-                    }
+    while let Some(field_idx) = field_possibilities.iter().position(BitSet64::is_single) {
+        let field = field_possibilities[field_idx];
+        for candidates in field_possibilities.iter_mut() {
+            *candidates -= field;
+        }
 
-                    field_solution[field_idx] = Some(&field.name);
+        let name_idx = field.iter().next().unwrap();
+        field_solution[field_idx] = Some(fields.0[name_idx].name.clone());
+    }
 
-                    continue 'outer;
-                }
-            }
-            break;
+    let unsolved: Vec<_> = field_solution.iter().positions(Option::is_none).collect();
+    if !unsolved.is_empty() {
+        return Err(Day16Error::Unsolved(unsolved));
+    }
+
+    Ok(field_solution.into_iter().flatten().collect())
+}
+
+/// Product of the values of my ticket in the `departure` columns
+fn departure_product(field_solution: &[String], my_ticket: &[usize]) -> Result<u64, Day16Error> {
+    let values: Vec<_> = field_solution
+        .iter()
+        .zip(my_ticket)
+        .filter(|(name, _)| name.starts_with("departure"))
+        .map(|(_, &value)| value as u64)
+        .collect();
+
+    match values.len() {
+        0 => log::warn!(
+            target: LOG_TARGET,
+            "no departure field found, the answer is the empty product"
+        ),
+        DEPARTURE_FIELDS => {}
+        found => {
+            return Err(Day16Error::DepartureFields {
+                expected: DEPARTURE_FIELDS,
+                found,
+            })
         }
+    }
+
+    values.iter().try_fold(1u64, |product, &value| {
+        product
+            .checked_mul(value)
+            .ok_or_else(|| Day16Error::Overflow(values.clone()))
+    })
+}
 
-        field_solution
-            .into_iter()
-            .collect::<Option<_>>()
-            .expect("could not solve all fields")
+/// Table of the field of each column, with the value of my ticket in that column
+fn field_table(field_solution: &[String], my_ticket: &[usize]) -> String {
+    let width = field_solution
+        .iter()
+        .map(String::len)
+        .chain(std::iter::once("field".len()))
+        .max()
+        .unwrap_or_default();
+
+    let mut table = format!("column  {:<width$}  value\n", "field", width = width);
+    for (column, (name, value)) in field_solution.iter().zip(my_ticket).enumerate() {
+        table += &format!(
+            "{:>6}  {:<width$}  {}\n",
+            column,
+            name,
+            value,
+            width = width
+        );
     }
+    table
 }
 
 impl Advent for AdventDay16 {
@@ -164,6 +398,10 @@ impl Advent for AdventDay16 {
         16
     }
 
+    fn title(&self) -> Option<&str> {
+        Some("Ticket Translation")
+    }
+
     fn get_input_names(&self) -> Vec<String> {
         vec![
             "test.txt".to_owned(),
@@ -172,14 +410,19 @@ impl Advent for AdventDay16 {
         ]
     }
 
-    fn process_input(&self, data: Vec<String>) {
-        data.into_iter()
-            .zip(["test", "test2", "real"])
-            .for_each(|(d, name)| {
-                println!();
-                println!("Processing '{}' input", name);
-                self.process_sample(d);
-            })
+    fn solve(&self, _input_file: &str, input: String, parts: Parts) -> DayResult {
+        let solution = solve(&input).map_err(|err| err.to_string())?;
+
+        let mut results = Vec::new();
+        if parts.includes(1) {
+            results.push((1, Ok(solution.error_rate.into())));
+        }
+        if parts.includes(2) {
+            let product = solution.departure_product.map_err(|err| err.to_string());
+            results.push((2, product.map(Into::into)));
+        }
+
+        Ok(results)
     }
 }
 
@@ -201,10 +444,10 @@ impl FromStr for ValidValue {
 }
 
 impl FromStr for PossibleField {
-    type Err = &'static str;
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (name, options) = s.split_once(": ").ok_or("malformed header line")?;
+        let (name, options) = parse::key_value(s, ": ").map_err(|err| err.to_string())?;
 
         Ok(Self {
             name: name.to_owned(),
@@ -221,3 +464,206 @@ impl FromIterator<PossibleField> for PossibleFields {
         Self(iter.into_iter().collect())
     }
 }
+
+#[test]
+fn test_solve() {
+    let solution = solve(include_str!("../../data/2020/16/test.txt")).unwrap();
+    assert_eq!(solution.error_rate, 71);
+
+    let solution = solve(include_str!("../../data/2020/16/test2.txt")).unwrap();
+    assert_eq!(
+        solution,
+        Day16Solution {
+            error_rate: 0,
+            field_order: Ok(vec![
+                "row".to_owned(),
+                "class".to_owned(),
+                "seat".to_owned()
+            ]),
+            // The samples have no departure fields
+            departure_product: Ok(1),
+        }
+    );
+}
+
+#[test]
+fn test_unsolved_fields() {
+    let sample = include_str!("../../data/2020/16/test2.txt");
+    let (notes, _) = sample.split_once("nearby tickets:").unwrap();
+    let ambiguous = format!("{}nearby tickets:\n3,3,3\n", notes);
+
+    // Both the row and the seat fit every column, which only fails part 2
+    let solution = solve(&ambiguous).unwrap();
+    assert_eq!(solution.error_rate, 0);
+    assert_eq!(
+        solution.field_order,
+        Err(Day16Error::Unsolved(vec![0, 1, 2]))
+    );
+    assert_eq!(
+        solution.departure_product,
+        Err(Day16Error::Unsolved(vec![0, 1, 2]))
+    );
+
+    let results = AdventDay16
+        .solve("test2.txt", ambiguous, Parts::All)
+        .unwrap();
+    assert_eq!(results[0], (1, Ok(0u8.into())));
+    assert_eq!(
+        results[1],
+        (
+            2,
+            Err("could not solve the fields of columns 0, 1, 2".to_owned())
+        )
+    );
+}
+
+#[test]
+fn test_invalid_nearby_tickets() {
+    let sample = include_str!("../../data/2020/16/test2.txt");
+    let (notes, _) = sample.split_once("nearby tickets:").unwrap();
+    let invalid = format!("{}nearby tickets:\n3,30,3\n20,3,3\n", notes);
+
+    // No valid ticket is left to constrain the fields, part 2 can't be answered
+    let solution = solve(&invalid).unwrap();
+    assert_eq!(solution.error_rate, 50);
+    assert_eq!(
+        solution.departure_product,
+        Err(Day16Error::Unsolved(vec![0, 1, 2]))
+    );
+}
+
+#[test]
+fn test_solve_fields() {
+    let notes = TicketNotes::parse(include_str!("../../data/2020/16/test2.txt")).unwrap();
+
+    let field_solution = solve_fields(&notes.fields, 3, &notes.nearby_tickets);
+
+    assert_eq!(
+        field_solution,
+        Ok(vec![
+            "row".to_owned(),
+            "class".to_owned(),
+            "seat".to_owned()
+        ])
+    );
+
+    // Both the row and the seat fit every column of the only ticket
+    assert_eq!(
+        solve_fields(&notes.fields, 3, &[vec![3, 3, 3]]),
+        Err(Day16Error::Unsolved(vec![0, 1, 2]))
+    );
+
+    // Without a ticket, nothing tells the fields apart
+    assert_eq!(
+        solve_fields(&notes.fields, 3, &[]),
+        Err(Day16Error::Unsolved(vec![0, 1, 2]))
+    );
+}
+
+#[test]
+fn test_field_table() {
+    let notes = TicketNotes::parse(include_str!("../../data/2020/16/test2.txt")).unwrap();
+    let field_solution = solve_fields(&notes.fields, 3, &notes.nearby_tickets).unwrap();
+
+    assert_eq!(
+        field_table(&field_solution, &notes.my_ticket)
+            .lines()
+            .collect::<Vec<_>>(),
+        [
+            "column  field  value",
+            "     0  row    11",
+            "     1  class  12",
+            "     2  seat   13",
+        ]
+    );
+}
+
+#[test]
+fn test_departure_product() {
+    let fields = |names: &[&str]| {
+        names
+            .iter()
+            .map(|&name| name.to_owned())
+            .collect::<Vec<_>>()
+    };
+    let departures = fields(&[
+        "departure location",
+        "row",
+        "departure station",
+        "departure platform",
+        "departure track",
+        "departure date",
+        "departure time",
+    ]);
+
+    assert_eq!(
+        departure_product(&departures, &[2, 100, 3, 4, 5, 6, 7]),
+        Ok(5040)
+    );
+    assert_eq!(
+        departure_product(&fields(&["row", "seat"]), &[11, 13]),
+        Ok(1)
+    );
+    assert_eq!(
+        departure_product(&departures[..3], &[2, 100, 3])
+            .unwrap_err()
+            .to_string(),
+        "expected 6 departure fields, found 2"
+    );
+    assert!(
+        departure_product(&departures, &[usize::MAX, 0, 2, 1, 1, 1, 1])
+            .unwrap_err()
+            .to_string()
+            .ends_with("overflows")
+    );
+}
+
+#[test]
+fn test_check_my_ticket() {
+    let sample = include_str!("../../data/2020/16/test2.txt");
+    let with_my_ticket = |ticket: &str| {
+        AdventDay16.solve("test2.txt", sample.replace("11,12,13", ticket), Parts::All)
+    };
+
+    assert!(with_my_ticket("11,12,13").is_ok());
+    assert_eq!(
+        with_my_ticket("11,12,25"),
+        Err("column 2 of my ticket, 25, fits no field".to_owned())
+    );
+
+    // 7 fits the class field, but the first column is the row
+    let results = with_my_ticket("7,12,13").unwrap();
+    assert_eq!(results[0], (1, Ok(0u8.into())));
+    assert_eq!(
+        results[1],
+        (
+            2,
+            Err("column 0 of my ticket, 7, does not fit its field row".to_owned())
+        )
+    );
+}
+
+#[test]
+fn test_malformed_notes() {
+    let sample = include_str!("../../data/2020/16/test.txt");
+    let malformed = |notes: &str| match solve(notes) {
+        Err(Day16Error::Malformed(reason)) => reason,
+        other => panic!("unexpected result {:?}", other),
+    };
+
+    assert_eq!(
+        malformed(""),
+        "expected \"your ticket:\", found the end of the notes"
+    );
+    assert_eq!(
+        malformed(&sample.replace("nearby tickets:", "nearby:")),
+        "expected \"nearby tickets:\", found \"nearby:\""
+    );
+    assert!(malformed(&sample.replace("row: 6-11", "row 6-11")).starts_with("invalid field"));
+    assert!(malformed(&sample.replace("7,1,14", "7,x,14")).starts_with("invalid 'my ticket'"));
+    assert!(malformed(&sample.replace("55,2,20", "55,2,z")).starts_with("invalid nearby ticket"));
+    assert_eq!(
+        malformed(&sample.replace("38,6,12", "38,6")),
+        "a nearby ticket has 2 values, mine has 3"
+    );
+}
@@ -1,148 +1,630 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 use crate::advent_adapters::AdventState;
+use crate::advents::PartResult;
+use crate::helper::automaton::{self, Rules};
+use crate::helper::display::render_slices;
+
+const LOG_TARGET: &str = "aoc::y2020::d17";
+
+const CYCLES: usize = 6;
+
+/// Most cycles a `DenseGrid` is padded for, it is then rebuilt around the active cells so that
+/// its size follows them rather than the number of cycles
+const GRID_CYCLES: usize = CYCLES;
+
+/// Set this variable to a number of cycles to run instead of `CYCLES`, stopping early when a
+/// generation repeats an earlier one
+const CYCLES_VAR: &str = "AOC_2020_17_CYCLES";
+
+/// Names of the dimensions beyond the initial plane, as the puzzle text prints them
+const EXTRA_DIMENSIONS: [&str; 2] = ["z", "w"];
 
 #[derive(Clone)]
 pub struct AdventDay17 {
-    active_cells: HashSet<Coordinates>,
+    /// Active cells of the initial plane as `(line, column)` pairs
+    initial_plane: Vec<(isize, isize)>,
+    /// Log every generation, only done for the samples since the real input gets huge
+    show_generations: bool,
+    cycles: usize,
+    /// Stop evolving once a generation repeats an earlier one, see `with_cycles`
+    detect_repeats: bool,
 }
 
 impl AdventDay17 {
-    fn solve_step1(&mut self) -> usize {
-        for _ in 0..6 {
-            self.cycle_3d();
+    /// Day starting from the initial plane alone, `#` marking its active cells, without logging
+    /// the generations
+    pub fn from_plane(plane: &str) -> Self {
+        Self {
+            show_generations: false,
+            cycles: CYCLES,
+            detect_repeats: false,
+            initial_plane: plane
+                .split('\n')
+                .enumerate()
+                .flat_map(|(line_index, line)| {
+                    line.chars()
+                        .enumerate()
+                        .filter(|&(_, char)| char == '#')
+                        .map(move |(column_index, _)| (line_index as isize, column_index as isize))
+                })
+                .collect(),
         }
+    }
 
-        self.active_cells.len()
+    /// Run this many cycles instead of `CYCLES`, stopping early when a generation repeats an
+    /// earlier one, translations aside
+    pub fn with_cycles(mut self, cycles: usize) -> Self {
+        self.cycles = cycles;
+        self.detect_repeats = true;
+        self
+    }
+
+    fn solve_step1(&self) -> usize {
+        self.solve::<3>()
+    }
+
+    fn solve_step2(&self) -> usize {
+        self.solve::<4>()
+    }
+
+    fn solve<const N: usize>(&self) -> usize {
+        let mut dimension = self.pocket_dimension::<N>();
+
+        dimension.evolve(self.cycles, Rules::default());
+
+        dimension.active_count()
     }
 
-    fn solve_step2(&mut self) -> usize {
-        for _ in 0..6 {
-            self.cycle_4d();
+    /// Embed the initial plane into an N-dimensional space, with every extra dimension at 0
+    fn pocket_dimension<const N: usize>(&self) -> PocketDimension<N> {
+        assert!(N >= 2, "the initial plane needs at least two dimensions");
+
+        PocketDimension {
+            show_generations: self.show_generations,
+            detect_repeats: self.detect_repeats,
+            active_cells: self
+                .initial_plane
+                .iter()
+                .map(|&(line, column)| {
+                    let mut cell = [0; N];
+                    cell[0] = line;
+                    cell[1] = column;
+                    cell
+                })
+                .collect(),
+        }
+    }
+}
+
+/// How an evolution ended, see `PocketDimension::detect_repeats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Evolution {
+    /// Every cycle ran, or repeats were not looked for
+    Completed,
+    /// The generation after `cycles` cycles stays the same from then on
+    Stabilized { cycles: usize },
+    /// The generation after `start` cycles comes back every `length` cycles, wherever it moved
+    Cycle { start: usize, length: usize },
+}
+
+impl fmt::Display for Evolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Evolution::Completed => f.write_str("completed"),
+            Evolution::Stabilized { cycles } => write!(f, "stabilized after {} cycles", cycles),
+            Evolution::Cycle { start, length } => write!(
+                f,
+                "cycle of length {} detected starting at generation {}",
+                length, start
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PocketDimension<const N: usize> {
+    active_cells: HashSet<[isize; N]>,
+    show_generations: bool,
+    /// Stop evolving once a generation repeats an earlier one, translations aside
+    detect_repeats: bool,
+}
+
+impl<const N: usize> PocketDimension<N> {
+    /// Run the cycles, stopping early when `detect_repeats` is set and a generation repeats
+    fn evolve(&mut self, cycles: usize, rules: Rules) -> Evolution {
+        let show_generations =
+            self.show_generations && log::log_enabled!(target: LOG_TARGET, log::Level::Debug);
+        let mut grid = DenseGrid::new(&self.active_cells, cycles.min(GRID_CYCLES));
+
+        if show_generations {
+            log::debug!(target: LOG_TARGET, "Before any cycles:\n\n{}", self.render());
+        }
+
+        // Generation seen with each set of canonical cells
+        let mut generations = HashMap::new();
+        if self.detect_repeats {
+            generations.insert(canonical(&self.active_cells), 0);
         }
 
+        let mut evolution = Evolution::Completed;
+        let mut cycles_run = cycles;
+        for cycle in 1..=cycles {
+            grid.cycle(rules);
+
+            if show_generations {
+                let generation = Self {
+                    active_cells: grid.active_cells(),
+                    show_generations: false,
+                    detect_repeats: false,
+                };
+                log::debug!(
+                    target: LOG_TARGET,
+                    "After {} cycle{}:\n\n{}",
+                    cycle,
+                    if cycle == 1 { "" } else { "s" },
+                    generation.render()
+                );
+            }
+
+            if self.detect_repeats {
+                let cells = canonical(&grid.active_cells());
+                if let Some(&start) = generations.get(&cells) {
+                    evolution = match cycle - start {
+                        1 => Evolution::Stabilized { cycles: start },
+                        length => Evolution::Cycle { start, length },
+                    };
+                    cycles_run = cycle;
+                    log::info!(target: LOG_TARGET, "{}", evolution);
+                    break;
+                }
+                generations.insert(cells, cycle);
+            }
+        }
+
+        let active_cells = grid.active_cells();
+
+        debug_assert_eq!(
+            active_cells,
+            self.evolve_sparse(cycles_run, rules),
+            "dense and sparse engines must agree"
+        );
+
+        self.active_cells = active_cells;
+        evolution
+    }
+
+    /// Same as `evolve`, but using the hash-set engine and returning the final generation
+    fn evolve_sparse(&self, cycles: usize, rules: Rules) -> HashSet<[isize; N]> {
+        automaton::evolve(&self.active_cells, cycles, neighbors, rules)
+    }
+
+    fn active_count(&self) -> usize {
         self.active_cells.len()
     }
 
-    fn cycle_3d(&mut self) {
-        self.active_cells = self
-            .active_cells
-            .iter()
-            // Expand cloud of possibly affected cells
-            .flat_map(|coordinate| coordinate.neighbors_3d())
-            // Collect possibly affected cells in a set
-            .collect::<HashSet<_>>()
-            .into_iter()
-            // Filter only cells that are active in the new generation
-            .filter(|c| {
-                let active_neighbors = c
-                    .neighbors_3d()
-                    .filter(|n| self.active_cells.contains(n))
-                    .count();
-
-                active_neighbors == 3 || (self.active_cells.contains(c) && active_neighbors == 2)
+    /// Slices of the active cells, laid out like in the puzzle text
+    fn render(&self) -> String {
+        render_slices(&self.active_cells, &EXTRA_DIMENSIONS[..N - 2])
+    }
+}
+
+/// Cells translated so the minimum of each coordinate is 0, in order, so generations that only
+/// moved are equal
+fn canonical<const N: usize>(cells: &HashSet<[isize; N]>) -> Vec<[isize; N]> {
+    let mut min = [isize::MAX; N];
+    for cell in cells {
+        for dim in 0..N {
+            min[dim] = min[dim].min(cell[dim]);
+        }
+    }
+
+    let mut canonical: Vec<_> = cells
+        .iter()
+        .map(|cell| {
+            let mut translated = *cell;
+            for (value, &min) in translated.iter_mut().zip(min.iter()) {
+                *value -= min;
+            }
+            translated
+        })
+        .collect();
+    canonical.sort_unstable();
+    canonical
+}
+
+/// Dense N-dimensional grid over the bounding box of every cell that can become active
+/// within a few cycles.
+///
+/// The active region grows by at most one cell per cycle in each direction, so the bounding box
+/// of the active cells padded by `cycles + 1` guarantees that every active cell has all of its
+/// neighbors inside the grid for that many cycles, and neighbors can be found by plain index
+/// arithmetic. The grid is then rebuilt around the active cells.
+struct DenseGrid<const N: usize> {
+    origin: [isize; N],
+    strides: [usize; N],
+    remaining_cycles: usize,
+    /// Flat index offset from a cell to each of its neighbors
+    neighbor_deltas: Vec<isize>,
+    cells: Vec<bool>,
+    neighbor_counts: Vec<u8>,
+}
+
+impl<const N: usize> DenseGrid<N> {
+    /// Grid padded for `cycles` cycles before it has to be rebuilt
+    fn new(active_cells: &HashSet<[isize; N]>, cycles: usize) -> Self {
+        let padding = cycles as isize + 1;
+
+        let mut min = [0; N];
+        let mut max = [0; N];
+        for (pos, cell) in active_cells.iter().enumerate() {
+            for dim in 0..N {
+                if pos == 0 || cell[dim] < min[dim] {
+                    min[dim] = cell[dim];
+                }
+                if pos == 0 || cell[dim] > max[dim] {
+                    max[dim] = cell[dim];
+                }
+            }
+        }
+
+        let mut origin = [0; N];
+        let mut strides = [0; N];
+        let mut size = 1;
+        for dim in (0..N).rev() {
+            origin[dim] = min[dim] - padding;
+            strides[dim] = size;
+            size *= (max[dim] - min[dim] + 1 + 2 * padding) as usize;
+        }
+
+        let neighbor_deltas = neighbor_offsets::<N>()
+            .map(|offset| {
+                offset
+                    .iter()
+                    .zip(strides.iter())
+                    .map(|(&delta, &stride)| delta * stride as isize)
+                    .sum()
             })
             .collect();
+
+        let mut grid = Self {
+            origin,
+            strides,
+            remaining_cycles: cycles,
+            neighbor_deltas,
+            cells: vec![false; size],
+            neighbor_counts: vec![0; size],
+        };
+
+        for cell in active_cells {
+            let index = grid.index(cell);
+            grid.cells[index] = true;
+        }
+
+        grid
+    }
+
+    fn index(&self, cell: &[isize; N]) -> usize {
+        cell.iter()
+            .zip(self.origin.iter())
+            .zip(self.strides.iter())
+            .map(|((&value, &origin), &stride)| (value - origin) as usize * stride)
+            .sum()
+    }
+
+    fn cycle(&mut self, rules: Rules) {
+        if self.remaining_cycles == 0 {
+            *self = Self::new(&self.active_cells(), GRID_CYCLES);
+        }
+        self.remaining_cycles -= 1;
+
+        let Self {
+            cells,
+            neighbor_counts,
+            neighbor_deltas,
+            ..
+        } = self;
+
+        neighbor_counts.iter_mut().for_each(|count| *count = 0);
+
+        for (index, _) in cells.iter().enumerate().filter(|(_, &active)| active) {
+            for &delta in neighbor_deltas.iter() {
+                neighbor_counts[(index as isize + delta) as usize] += 1;
+            }
+        }
+
+        for (cell, &count) in cells.iter_mut().zip(neighbor_counts.iter()) {
+            *cell = rules.next_state(*cell, count as usize);
+        }
     }
 
-    fn cycle_4d(&mut self) {
-        self.active_cells = self
-            .active_cells
+    fn active_cells(&self) -> HashSet<[isize; N]> {
+        self.cells
             .iter()
-            // Expand cloud of possibly affected cells
-            .flat_map(|coordinate| coordinate.neighbors_4d())
-            // Collect possibly affected cells in a set
-            .collect::<HashSet<_>>()
-            .into_iter()
-            // Filter only cells that are active in the new generation
-            .filter(|c| {
-                let active_neighbors = c
-                    .neighbors_4d()
-                    .filter(|n| self.active_cells.contains(n))
-                    .count();
-
-                active_neighbors == 3 || (self.active_cells.contains(c) && active_neighbors == 2)
+            .enumerate()
+            .filter(|(_, &active)| active)
+            .map(|(mut index, _)| {
+                let mut cell = self.origin;
+                for (value, &stride) in cell.iter_mut().zip(self.strides.iter()) {
+                    *value += (index / stride) as isize;
+                    index %= stride;
+                }
+                cell
             })
-            .collect();
+            .collect()
     }
 }
 
+/// All the `3^N - 1` offsets from a cell to its neighbors
+fn neighbor_offsets<const N: usize>() -> impl Iterator<Item = [isize; N]> {
+    (0..3usize.pow(N as u32))
+        .map(|mut combination| {
+            let mut offset = [0; N];
+            for value in offset.iter_mut() {
+                *value = (combination % 3) as isize - 1;
+                combination /= 3;
+            }
+            offset
+        })
+        .filter(|offset| offset.iter().any(|&v| v != 0))
+}
+
+fn neighbors<const N: usize>(cell: &[isize; N]) -> impl Iterator<Item = [isize; N]> {
+    let cell = *cell;
+
+    neighbor_offsets::<N>().map(move |offset| {
+        let mut neighbor = cell;
+        for (value, delta) in neighbor.iter_mut().zip(offset.iter()) {
+            *value += delta;
+        }
+        neighbor
+    })
+}
+
 impl AdventState for AdventDay17 {
     const INPUT_FILES: &'static [&'static str] = &["test.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Conway Cubes");
 
-    fn new(_input_file: &'static str, input_content: String) -> Self {
-        Self {
-            active_cells: input_content
-                .split('\n')
-                .enumerate()
-                .flat_map(|(line_index, line)| {
-                    line.chars()
-                        .enumerate()
-                        .filter(|&(_, char)| char == '#')
-                        .map(move |(column_index, _)| {
-                            Coordinates(line_index as isize, column_index as isize, 0, 0)
-                        })
-                })
-                .collect(),
+    fn new(input_file: &str, input_content: String) -> Result<Self, String> {
+        Self::new_borrowed(input_file, &input_content)
+    }
+
+    fn new_borrowed(input_file: &str, input_content: &str) -> Result<Self, String> {
+        let day = Self {
+            show_generations: input_file != "input.txt",
+            ..Self::from_plane(input_content)
+        };
+
+        match std::env::var(CYCLES_VAR) {
+            Err(_) => Ok(day),
+            Ok(cycles) => cycles
+                .parse()
+                .map(|cycles| day.with_cycles(cycles))
+                .map_err(|_| {
+                    format!(
+                        "{} must be a number of cycles, not {:?}",
+                        CYCLES_VAR, cycles
+                    )
+                }),
         }
     }
 
-    fn run(self) {
-        println!("Solution for step 1: {}", self.clone().solve_step1());
-        println!("Solution for step 2: {}", self.clone().solve_step2());
+    fn part1(&self) -> PartResult {
+        Ok(self.solve_step1().into())
+    }
+
+    fn part2(&self) -> PartResult {
+        Ok(self.solve_step2().into())
     }
 }
 
-#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
-struct Coordinates(isize, isize, isize, isize);
+/// Initial plane of the example of the puzzle
+#[cfg(test)]
+const SAMPLE_PLANE: &str = ".#.\n..#\n###";
 
-impl Coordinates {
-    fn neighbors_3d(&self) -> impl Iterator<Item = Coordinates> {
-        let &Coordinates(sx, sy, sz, sw) = self;
-        (-1..=1)
-            .flat_map(move |x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| (x, y, z))))
-            .filter(|&(x, y, z)| !(x == y && y == z && z == 0))
-            .map(move |(x, y, z)| Coordinates(sx + x, sy + y, sz + z, sw))
-    }
+#[cfg(test)]
+fn assert_reciprocal_neighbors<const N: usize>() {
+    let coord = [0; N];
 
-    fn neighbors_4d(&self) -> impl Iterator<Item = Coordinates> {
-        let &Coordinates(sx, sy, sz, sw) = self;
+    assert_eq!(neighbors(&coord).count(), 3usize.pow(N as u32) - 1);
 
-        (-1..=1)
-            .flat_map(move |x| {
-                (-1..=1).flat_map(move |y| {
-                    (-1..=1).flat_map(move |z| (-1..=1).map(move |w| (x, y, z, w)))
-                })
-            })
-            .filter(|&(x, y, z, w)| !(x == y && y == z && z == w && w == 0))
-            .map(move |(x, y, z, w)| Coordinates(sx + x, sy + y, sz + z, sw + w))
+    for neighbor in neighbors(&coord) {
+        neighbors(&neighbor)
+            .position(|c| c == coord)
+            .expect("neighbors must be reciprocated");
     }
 }
 
 #[test]
 fn test_coordinate_3d() {
-    let coord = Coordinates(0, 0, 0, 0);
+    assert_reciprocal_neighbors::<3>();
+}
+
+#[test]
+fn test_coordinate_4d() {
+    assert_reciprocal_neighbors::<4>();
+}
 
-    assert_eq!(coord.neighbors_3d().count(), 26);
+#[test]
+fn test_cycle_5d() {
+    assert_reciprocal_neighbors::<5>();
 
-    for neighbor in coord.neighbors_3d() {
-        neighbor
-            .neighbors_3d()
-            .position(|c| c == coord)
-            .expect("neighbors must be reciprocated");
+    let day = AdventDay17::new(
+        "test.txt",
+        include_str!("../../data/2020/17/test.txt").into(),
+    )
+    .unwrap();
+
+    let mut dimension = day.pocket_dimension::<5>();
+    dimension.evolve(1, Rules::default());
+
+    assert_eq!(dimension.active_count(), 83);
+}
+
+/// Active cells of the sample after each of the puzzle's cycles, to tell which one goes wrong
+#[cfg(test)]
+fn sample_counts<const N: usize>(day: &AdventDay17) -> Vec<usize> {
+    (1..=CYCLES)
+        .map(|cycles| {
+            let mut dimension = day.pocket_dimension::<N>();
+            dimension.evolve(cycles, Rules::default());
+            dimension.active_count()
+        })
+        .collect()
+}
+
+#[test]
+fn test_evolve_3d_sample() {
+    let day = AdventDay17::from_plane(SAMPLE_PLANE);
+
+    assert_eq!(sample_counts::<3>(&day), [11, 21, 38, 58, 101, 112]);
+    assert_eq!(day.solve_step1(), 112);
+}
+
+#[test]
+fn test_evolve_4d_sample() {
+    let day = AdventDay17::from_plane(SAMPLE_PLANE);
+
+    assert_eq!(sample_counts::<4>(&day), [29, 60, 320, 188, 1056, 848]);
+    assert_eq!(day.solve_step2(), 848);
+}
+
+#[test]
+fn test_grid_growth() {
+    // Padded for a single cycle, the grid is rebuilt every cycle as the sample grows
+    let dimension = AdventDay17::from_plane(SAMPLE_PLANE).pocket_dimension::<3>();
+    let mut grid = DenseGrid::new(&dimension.active_cells, 1);
+    for _ in 0..10 {
+        grid.cycle(Rules::default());
     }
+
+    assert_eq!(
+        grid.active_cells(),
+        dimension.evolve_sparse(10, Rules::default())
+    );
 }
 
 #[test]
-fn test_coordinate_4d() {
-    let coord = Coordinates(0, 0, 0, 0);
+#[ignore = "benchmark, run with --ignored --nocapture"]
+fn bench_engines() {
+    let day = AdventDay17::new(
+        "input.txt",
+        include_str!("../../data/2020/17/input.txt").into(),
+    )
+    .unwrap();
+    let dimension = day.pocket_dimension::<4>();
 
-    assert_eq!(coord.neighbors_4d().count(), 80);
+    let start = std::time::Instant::now();
+    let sparse = dimension.evolve_sparse(CYCLES, Rules::default());
+    print_info!("sparse engine: {:?}", start.elapsed());
 
-    for neighbor in coord.neighbors_4d() {
-        neighbor
-            .neighbors_4d()
-            .position(|c| c == coord)
-            .expect("neighbors must be reciprocated");
+    let start = std::time::Instant::now();
+    let mut grid = DenseGrid::new(&dimension.active_cells, CYCLES);
+    for _ in 0..CYCLES {
+        grid.cycle(Rules::default());
     }
+    let dense = grid.active_cells();
+    print_info!("dense engine: {:?}", start.elapsed());
+
+    assert_eq!(dense, sparse);
+}
+
+#[test]
+fn test_render_first_cycle() {
+    let day = AdventDay17::new(
+        "test.txt",
+        include_str!("../../data/2020/17/test.txt").into(),
+    )
+    .unwrap();
+
+    let mut dimension = day.pocket_dimension::<3>();
+    insta::assert_snapshot!("initial", dimension.render());
+
+    dimension.evolve(1, Rules::default());
+    insta::assert_snapshot!("first_cycle", dimension.render());
+}
+
+#[cfg(test)]
+fn dimension_3d(cells: &[[isize; 3]]) -> PocketDimension<3> {
+    PocketDimension {
+        active_cells: cells.iter().copied().collect(),
+        show_generations: false,
+        detect_repeats: true,
+    }
+}
+
+#[test]
+fn test_canonical() {
+    let cells: HashSet<_> = [[3, -1, 2], [4, 0, 2]].iter().copied().collect();
+    let moved: HashSet<_> = [[0, 0, -5], [1, 1, -5]].iter().copied().collect();
+
+    assert_eq!(canonical(&cells), [[0, 0, 0], [1, 1, 0]]);
+    assert_eq!(canonical(&cells), canonical(&moved));
+}
+
+#[test]
+fn test_still_life() {
+    // A flat block, each cell having three neighbors and every other cell at most four
+    let block = [[0, 0, 0], [0, 1, 0], [1, 0, 0], [1, 1, 0]];
+    let mut dimension = dimension_3d(&block);
+
+    assert_eq!(
+        dimension.evolve(10, Rules::default()),
+        Evolution::Stabilized { cycles: 0 }
+    );
+    assert_eq!(dimension.active_cells, dimension_3d(&block).active_cells);
+    assert_eq!(
+        Evolution::Stabilized { cycles: 0 }.to_string(),
+        "stabilized after 0 cycles"
+    );
+
+    // A day running more cycles stops as soon as the block repeats, the grid staying the size
+    // of the block
+    let day = AdventDay17::from_plane("##\n##").with_cycles(1_000_000);
+    assert_eq!(day.solve_step1(), 4);
+    assert!(day.pocket_dimension::<3>().detect_repeats);
+    assert!(!AdventDay17::from_plane("##\n##").detect_repeats);
+}
+
+#[test]
+fn test_oscillators() {
+    let rules = Rules {
+        birth: &[4],
+        survival: &[2],
+    };
+
+    // Four cells around a corner, back after two cycles
+    let mut corner = dimension_3d(&[[0, 0, 0], [0, 0, 1], [0, 1, 0], [1, 0, 0]]);
+    let evolution = corner.evolve(10, rules);
+    assert_eq!(
+        evolution,
+        Evolution::Cycle {
+            start: 0,
+            length: 2
+        }
+    );
+    assert_eq!(
+        evolution.to_string(),
+        "cycle of length 2 detected starting at generation 0"
+    );
+
+    // A line with a cell aside only starts oscillating after the first cycle
+    let rules = Rules {
+        birth: &[4],
+        survival: &[1],
+    };
+    let mut line = dimension_3d(&[[0, 0, 0], [0, 0, 1], [0, 0, 2], [1, 1, 0]]);
+    assert_eq!(
+        line.evolve(10, rules),
+        Evolution::Cycle {
+            start: 1,
+            length: 2
+        }
+    );
+
+    // Nothing is looked for by default
+    let mut corner = dimension_3d(&[[0, 0, 0], [0, 0, 1], [0, 1, 0], [1, 0, 0]]);
+    corner.detect_repeats = false;
+    assert_eq!(corner.evolve(10, rules), Evolution::Completed);
 }
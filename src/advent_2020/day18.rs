@@ -1,14 +1,133 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt::{self, Debug, Display, Formatter, Write};
-use std::str::FromStr;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::slice;
 
-use crate::advent_adapters::AdventState;
+use itertools::Itertools;
+
+use crate::advents::Repl;
+use crate::helper;
+
+#[cfg(test)]
+use crate::advent_adapters::{AdventState, StatefulAdvent};
+#[cfg(test)]
+use crate::advents::{Advent, Answer, PartResult, Parts};
+
+const LOG_TARGET: &str = "aoc::y2020::d18";
 
 type ParserResult<'a, O> = nom::IResult<&'a str, O, nom::error::VerboseError<&'a str>>;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Operation {
     Add,
+    Sub,
     Mul,
+    Div,
+    Pow,
+}
+
+impl Operation {
+    const ALL: [Operation; 5] = [
+        Operation::Add,
+        Operation::Sub,
+        Operation::Mul,
+        Operation::Div,
+        Operation::Pow,
+    ];
+
+    fn symbol(self) -> char {
+        match self {
+            Operation::Add => '+',
+            Operation::Sub => '-',
+            Operation::Mul => '*',
+            Operation::Div => '/',
+            Operation::Pow => '^',
+        }
+    }
+
+    fn from_symbol(symbol: char) -> Option<Self> {
+        Self::ALL.iter().copied().find(|op| op.symbol() == symbol)
+    }
+
+    /// Whether a chain of this operation groups from the right, like `2 ^ 3 ^ 2 = 2 ^ 9`
+    fn is_right_associative(self) -> bool {
+        matches!(self, Operation::Pow)
+    }
+
+    fn apply(self, left: i128, right: i128) -> Result<i128, ArithmeticError> {
+        let result = match self {
+            Operation::Add => left.checked_add(right),
+            Operation::Sub => left.checked_sub(right),
+            Operation::Mul => left.checked_mul(right),
+            Operation::Div if right == 0 => return Err(ArithmeticError::DivisionByZero(left)),
+            Operation::Div => left.checked_div(right),
+            Operation::Pow => u32::try_from(right)
+                .ok()
+                .and_then(|exponent| left.checked_pow(exponent)),
+        }
+        .ok_or(ArithmeticError::Overflow(self, left, right))?;
+
+        if result < 0 {
+            Err(ArithmeticError::Negative(self, left, right))
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char(self.symbol())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ArithmeticError {
+    /// The result of the operation doesn't fit in an `i128`
+    Overflow(Operation, i128, i128),
+    /// The result of the operation is negative
+    Negative(Operation, i128, i128),
+    DivisionByZero(i128),
+    /// The operation is not part of the precedence table
+    Unsupported(Operation),
+}
+
+impl Display for ArithmeticError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ArithmeticError::Overflow(op, left, right) => {
+                write!(f, "{} {} {} overflows", left, op, right)
+            }
+            ArithmeticError::Negative(op, left, right) => {
+                write!(f, "{} {} {} is negative", left, op, right)
+            }
+            ArithmeticError::DivisionByZero(left) => write!(f, "{} / 0 divides by zero", left),
+            ArithmeticError::Unsupported(op) => {
+                write!(f, "operation '{}' is not supported in this mode", op)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum EvaluationError {
+    /// The expression on the given 1-based line could not be evaluated
+    Expression(usize, ArithmeticError),
+    /// Every expression can be evaluated, but their sum overflows
+    Sum,
+}
+
+impl Display for EvaluationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EvaluationError::Expression(line, err) => {
+                write!(f, "expression on line {}: {}", line, err)
+            }
+            EvaluationError::Sum => f.write_str("the sum of all expressions overflows"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -22,8 +141,7 @@ impl Display for Token {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Token::Lit(v) => f.write_fmt(format_args!("{}", v)),
-            Token::Operation(Operation::Add) => f.write_char('+'),
-            Token::Operation(Operation::Mul) => f.write_char('*'),
+            Token::Operation(op) => Display::fmt(op, f),
             Token::Expr(v) => f.write_fmt(format_args!("({})", v)),
         }
     }
@@ -51,75 +169,326 @@ impl Display for Expr {
     }
 }
 
-fn decimal(input: &str) -> ParserResult<usize> {
-    nom::combinator::map_res(
-        nom::combinator::recognize(nom::multi::many1(nom::sequence::terminated(
-            nom::character::complete::one_of("0123456789"),
-            nom::multi::many0(nom::character::complete::char('_')),
-        ))),
-        usize::from_str,
-    )(input)
+/// Binding power of each operation, higher values bind tighter
+type PrecedenceTable = [(Operation, u8)];
+
+/// Step 1 evaluates left to right regardless of the operation
+const STEP1_PRECEDENCE: &PrecedenceTable = &[(Operation::Add, 1), (Operation::Mul, 1)];
+
+/// Step 2 evaluates additions before multiplications
+const STEP2_PRECEDENCE: &PrecedenceTable = &[(Operation::Add, 2), (Operation::Mul, 1)];
+
+/// Usual arithmetic precedence, for using the engine as a calculator
+const CONVENTIONAL_PRECEDENCE: &PrecedenceTable = &[
+    (Operation::Add, 1),
+    (Operation::Sub, 1),
+    (Operation::Mul, 2),
+    (Operation::Div, 2),
+    (Operation::Pow, 3),
+];
+
+/// Operations evaluated while evaluating an expression, see `Expr::evaluate_observed`
+struct Reduction<'e> {
+    /// Expression, or parenthesized sub-expression, the operations are part of
+    expr: &'e Expr,
+    /// Tokens of `expr` replaced by `value`
+    span: Range<usize>,
+    value: i128,
 }
 
 impl Expr {
-    fn parse(input: &str) -> Self {
-        nom::combinator::all_consuming(nom::error::context("root parser", Self::parse_expr))(input)
-            .map(|(_, expr)| expr)
-            .expect("could not parse expression")
+    fn evaluate(&self, precedence: &PrecedenceTable) -> Result<i128, ArithmeticError> {
+        self.evaluate_observed(precedence, &mut |_| {})
     }
 
-    fn parse_expr(input: &str) -> ParserResult<Self> {
+    /// Same as `evaluate`, calling `observer` after each operation with every token reduced so
+    /// far in its expression
+    fn evaluate_observed<'e>(
+        &'e self,
+        precedence: &PrecedenceTable,
+        observer: &mut dyn FnMut(Reduction<'e>),
+    ) -> Result<i128, ArithmeticError> {
+        self.climb(&mut self.tokens.iter().peekable(), precedence, 0, observer)
+    }
+
+    /// Precedence climbing over the tokens, consuming every operation binding at least as tight
+    /// as `min_precedence`. Operations of the same precedence are left-associative unless the
+    /// operation itself is right-associative.
+    fn climb<'e>(
+        &'e self,
+        tokens: &mut Peekable<slice::Iter<'e, Token>>,
+        precedence: &PrecedenceTable,
+        min_precedence: u8,
+        observer: &mut dyn FnMut(Reduction<'e>),
+    ) -> Result<i128, ArithmeticError> {
+        let start = self.tokens.len() - tokens.len();
+        let mut value = Self::operand(tokens.next(), precedence, observer)?;
+
+        while let Some(&&Token::Operation(op)) = tokens.peek() {
+            let op_precedence = precedence
+                .iter()
+                .find(|(entry, _)| *entry == op)
+                .map(|&(_, p)| p)
+                .ok_or(ArithmeticError::Unsupported(op))?;
+
+            if op_precedence < min_precedence {
+                break;
+            }
+            tokens.next();
+
+            let right_precedence = if op.is_right_associative() {
+                op_precedence
+            } else {
+                op_precedence + 1
+            };
+
+            let right = self.climb(tokens, precedence, right_precedence, observer)?;
+            value = op.apply(value, right)?;
+            observer(Reduction {
+                expr: self,
+                span: start..self.tokens.len() - tokens.len(),
+                value,
+            });
+        }
+
+        Ok(value)
+    }
+
+    fn operand<'e>(
+        token: Option<&'e Token>,
+        precedence: &PrecedenceTable,
+        observer: &mut dyn FnMut(Reduction<'e>),
+    ) -> Result<i128, ArithmeticError> {
+        match token {
+            Some(&Token::Lit(v)) => Ok(v as i128),
+            Some(Token::Expr(inner)) => inner.evaluate_observed(precedence, observer),
+            _ => unreachable!("operations must be surrounded by operands"),
+        }
+    }
+
+    /// Steps of the evaluation, from the expression itself to its value like
+    /// `1 + 2 * 3`, `3 * 3` and `9` in step 1, each one replacing the last operations evaluated
+    /// by their result
+    fn explain(
+        &self,
+        precedence: &PrecedenceTable,
+    ) -> (Vec<String>, Result<i128, ArithmeticError>) {
+        let mut reduced: HashMap<*const Expr, Vec<(Range<usize>, i128)>> = HashMap::new();
+        let mut steps = vec![self.to_string()];
+
+        let result = self.evaluate_observed(precedence, &mut |reduction| {
+            reduced
+                .entry(reduction.expr)
+                .or_default()
+                .push((reduction.span, reduction.value));
+
+            let mut step = String::new();
+            self.render_reduced(&reduced, &mut step);
+            if steps.last() != Some(&step) {
+                steps.push(step);
+            }
+        });
+
+        (steps, result)
+    }
+
+    /// Render the expression with the widest reduced spans replaced by their value, and the
+    /// sub-expressions reduced to a value written without their parentheses
+    fn render_reduced(
+        &self,
+        reduced: &HashMap<*const Expr, Vec<(Range<usize>, i128)>>,
+        out: &mut String,
+    ) {
+        let spans = reduced
+            .get(&(self as *const Expr))
+            .map_or(&[][..], Vec::as_slice);
+        let widest = |position: usize| {
+            spans
+                .iter()
+                .filter(|(span, _)| span.start == position)
+                .max_by_key(|(span, _)| span.end)
+        };
+
+        let mut position = 0;
+        while position < self.tokens.len() {
+            if position > 0 {
+                out.push(' ');
+            }
+
+            if let Some((span, value)) = widest(position) {
+                write!(out, "{}", value).unwrap();
+                position = span.end;
+                continue;
+            }
+
+            match &self.tokens[position] {
+                Token::Expr(inner) => {
+                    let mut rendered = String::new();
+                    inner.render_reduced(reduced, &mut rendered);
+                    let fully_reduced =
+                        reduced
+                            .get(&(&**inner as *const Expr))
+                            .is_some_and(|spans| {
+                                spans
+                                    .iter()
+                                    .any(|(span, _)| *span == (0..inner.tokens.len()))
+                            });
+
+                    if fully_reduced {
+                        out.push_str(&rendered);
+                    } else {
+                        write!(out, "({})", rendered).unwrap();
+                    }
+                }
+                token => write!(out, "{}", token).unwrap(),
+            }
+            position += 1;
+        }
+    }
+}
+
+impl Expr {
+    /// Parse a single expression, rendering parse failures with the position of the error
+    fn parse(input: &str) -> Result<Self, String> {
+        helper::nom::parse_all_pretty(nom::error::context("root parser", Self::parse_expr), input)
+    }
+
+    fn parse_expr(input: &str) -> ParserResult<'_, Self> {
         nom::combinator::map(
-            nom::multi::many1(nom::branch::alt((
-                Self::parse_operator,
+            nom::sequence::pair(
                 Self::parse_operand,
-            ))),
-            |tokens| Self { tokens },
+                nom::multi::many0(nom::sequence::pair(
+                    Self::parse_operator,
+                    // An operator must always be followed by an operand
+                    nom::combinator::cut(Self::parse_operand),
+                )),
+            ),
+            |(head, tail)| {
+                let mut tokens = Vec::with_capacity(1 + tail.len() * 2);
+                tokens.push(head);
+                for (operator, operand) in tail {
+                    tokens.push(operator);
+                    tokens.push(operand);
+                }
+
+                Self { tokens }
+            },
         )(input)
     }
 
-    fn parse_lit(input: &str) -> ParserResult<Token> {
-        nom::error::context("parsing literal", nom::combinator::map(decimal, Token::Lit))(input)
+    fn parse_lit(input: &str) -> ParserResult<'_, Token> {
+        nom::error::context(
+            "parsing literal",
+            nom::combinator::map(
+                helper::nom::unsigned(helper::nom::UnsignedFormat::DECIMAL),
+                Token::Lit,
+            ),
+        )(input)
     }
 
-    fn parse_operand(input: &str) -> ParserResult<Token> {
-        nom::sequence::delimited(
-            nom::character::complete::space0,
-            nom::branch::alt((
-                Self::parse_lit,
-                nom::error::context(
-                    "expression operand",
-                    nom::combinator::map(
-                        nom::sequence::delimited(
-                            nom::character::complete::char('('),
+    fn parse_operand(input: &str) -> ParserResult<'_, Token> {
+        helper::nom::ws(nom::branch::alt((
+            Self::parse_lit,
+            nom::error::context(
+                "expression operand",
+                nom::combinator::map(
+                    nom::sequence::preceded(
+                        nom::character::complete::char('('),
+                        nom::combinator::cut(nom::sequence::terminated(
                             Self::parse_expr,
                             nom::character::complete::char(')'),
-                        ),
-                        |expr| Token::Expr(Box::new(expr)),
+                        )),
                     ),
+                    |expr| Token::Expr(Box::new(expr)),
                 ),
-            )),
-            nom::character::complete::space0,
-        )(input)
+            ),
+        )))(input)
     }
 
-    fn parse_operator(input: &str) -> ParserResult<Token> {
+    fn parse_operator(input: &str) -> ParserResult<'_, Token> {
         nom::error::context(
             "parsing operator",
-            nom::branch::alt((Self::parse_addition, Self::parse_multiplication)),
+            helper::nom::ws(nom::combinator::map(
+                nom::combinator::map_opt(nom::character::complete::anychar, Operation::from_symbol),
+                Token::Operation,
+            )),
         )(input)
     }
+}
+
+/// Precedence modes of the calculator, by the name given to `:mode`
+const MODES: &[(&str, &PrecedenceTable)] = &[
+    ("1", STEP1_PRECEDENCE),
+    ("2", STEP2_PRECEDENCE),
+    ("normal", CONVENTIONAL_PRECEDENCE),
+];
+
+const CALCULATOR_HELP: &str = "\
+Type an expression to evaluate it, or a command:
+  :mode         show the precedence mode
+  :mode 1       evaluate left to right, like step 1
+  :mode 2       evaluate additions first, like step 2
+  :mode normal  use the usual precedence (the default)
+  :help         show this help
+Ctrl-D exits";
+
+/// Calculator over the parser and the evaluator of the puzzle, in one of the `MODES`
+#[derive(Debug)]
+struct Calculator {
+    mode: usize,
+}
 
-    fn parse_addition(input: &str) -> ParserResult<Token> {
-        nom::combinator::map(nom::character::complete::char('+'), |_| {
-            Token::Operation(Operation::Add)
-        })(input)
+impl Default for Calculator {
+    fn default() -> Self {
+        Self {
+            mode: MODES.len() - 1,
+        }
     }
+}
 
-    fn parse_multiplication(input: &str) -> ParserResult<Token> {
-        nom::combinator::map(nom::character::complete::char('*'), |_| {
-            Token::Operation(Operation::Mul)
-        })(input)
+impl Calculator {
+    fn command(&mut self, command: &str) -> String {
+        let words: Vec<_> = command.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["mode"] => format!("mode {}", MODES[self.mode].0),
+            ["mode", name] => match MODES.iter().position(|(mode, _)| mode == name) {
+                Some(mode) => {
+                    self.mode = mode;
+                    format!("mode {}", name)
+                }
+                None => format!(
+                    "unknown mode {}, the modes are {}",
+                    name,
+                    MODES.iter().map(|(mode, _)| mode).join(", ")
+                ),
+            },
+            ["help"] => CALCULATOR_HELP.to_owned(),
+            _ => format!("unknown command :{}, see :help", command),
+        }
+    }
+}
+
+impl Repl for Calculator {
+    fn prompt(&self) -> String {
+        format!("mode {}> ", MODES[self.mode].0)
+    }
+
+    fn eval(&mut self, line: &str) -> Option<String> {
+        if line.trim().is_empty() {
+            return None;
+        }
+        if let Some(command) = line.trim().strip_prefix(':') {
+            return Some(self.command(command));
+        }
+
+        Some(match Expr::parse(line) {
+            Ok(expr) => match expr.evaluate(MODES[self.mode].1) {
+                Ok(value) => value.to_string(),
+                Err(err) => format!("error: {}", err),
+            },
+            Err(err) => err.trim_end().to_owned(),
+        })
     }
 }
 
@@ -129,112 +498,370 @@ pub struct AdventDay18 {
 }
 
 impl AdventDay18 {
-    fn parse(input: String) -> Self {
-        Self {
-            content: input.trim().split('\n').map(Expr::parse).collect(),
+    /// Parse every line of the input, reporting all the invalid lines at once
+    fn parse(input: String) -> Result<Self, String> {
+        let mut content = Vec::new();
+        let mut errors = Vec::new();
+
+        for (line, text) in input.trim_end().split('\n').enumerate() {
+            match Expr::parse(text) {
+                Ok(expr) => content.push(expr),
+                Err(err) => errors.push(format!("line {}:\n{}", line + 1, err)),
+            }
         }
-    }
 
-    fn step1(&self) -> usize {
-        self.content
-            .iter()
-            .map(|expr| Self::reduce_expression(expr, Self::step1_evaluator))
-            .sum()
+        if errors.is_empty() {
+            Ok(Self { content })
+        } else {
+            Err(errors.join("\n"))
+        }
     }
 
-    fn step2(&self) -> usize {
-        self.content
-            .iter()
-            .map(|expr| Self::reduce_expression(expr, Self::step2_evaluator))
-            .sum()
+    fn step1(&self) -> Result<i128, EvaluationError> {
+        self.evaluate_all("step 1", STEP1_PRECEDENCE)
     }
 
-    fn step1_evaluator(tokens: &[Token]) -> usize {
-        let mut value = match tokens.first() {
-            Some(&Token::Lit(v)) => v,
-            _ => unreachable!("the first token should always be a literal at this point"),
-        };
+    fn step2(&self) -> Result<i128, EvaluationError> {
+        self.evaluate_all("step 2", STEP2_PRECEDENCE)
+    }
 
-        assert_eq!(tokens.len() % 2, 1, "there must be an odd number of tokens");
+    /// Sum of every expression, logging the steps of each evaluation under `-v`
+    fn evaluate_all(
+        &self,
+        step: &str,
+        precedence: &PrecedenceTable,
+    ) -> Result<i128, EvaluationError> {
+        let explain = log::log_enabled!(target: LOG_TARGET, log::Level::Debug);
 
-        for pair in (&tokens[1..]).chunks_exact(2) {
-            match pair {
-                [Token::Operation(Operation::Add), Token::Lit(v)] => {
-                    value += *v;
-                }
-                [Token::Operation(Operation::Mul), Token::Lit(v)] => {
-                    value *= *v;
+        self.content
+            .iter()
+            .enumerate()
+            .try_fold(0i128, |total, (line, expr)| {
+                let value = if explain {
+                    let (steps, value) = expr.explain(precedence);
+                    log::debug!(
+                        target: LOG_TARGET,
+                        "{}, line {}: {}",
+                        step,
+                        line + 1,
+                        steps.join("  →  ")
+                    );
+                    value
+                } else {
+                    expr.evaluate(precedence)
                 }
-                _ => unreachable!(),
-            }
-        }
+                .map_err(|err| EvaluationError::Expression(line + 1, err))?;
 
-        value
+                total.checked_add(value).ok_or(EvaluationError::Sum)
+            })
     }
+}
 
-    fn step2_evaluator(tokens: &[Token]) -> usize {
-        let mut tokens = tokens.to_vec();
+stateful_day! {
+    AdventDay18 {
+        title: "Operation Order",
+        repl: Calculator::default(),
+        input_files: ["test.txt", "input.txt"],
+        parse(input) => Self::parse(input),
+        part1(day) => day.step1(),
+        part2(day) => day.step2(),
+    }
+}
 
-        while let Some(pos) = tokens
-            .iter()
-            .position(|t| matches!(t, Token::Operation(Operation::Add)))
-        {
-            // Remove the operator
-            tokens.remove(pos);
-
-            // Remove the right operand that is now on its place
-            let right = match tokens.remove(pos) {
-                Token::Lit(v) => v,
-                _ => unreachable!(),
-            };
+#[test]
+fn test_parsing() {
+    let input = "4 + (5 + 8)";
 
-            let left_handle = &mut tokens[pos - 1];
-            let left = match *left_handle {
-                Token::Lit(v) => v,
-                _ => unreachable!(),
-            };
+    let expr = Expr::parse(input).unwrap();
 
-            *left_handle = Token::Lit(left + right);
-        }
+    print_info!("result: {}", expr);
+    print_info!("result: {:?}", expr);
+}
 
-        // Only literals and multiplication tokens left, fallback to step 1
-        Self::step1_evaluator(&tokens)
+#[test]
+fn test_overflow() {
+    let day = AdventDay18::parse(
+        "1 + 2\n3 * (9223372036854775808 * 9223372036854775808 * 2)\n5".to_owned(),
+    )
+    .unwrap();
+
+    let expected =
+        EvaluationError::Expression(2, ArithmeticError::Overflow(Operation::Mul, 1 << 126, 2));
+    assert_eq!(day.step1(), Err(expected.clone()));
+    assert_eq!(day.step2(), Err(expected));
+    assert_eq!(
+        day.step1().unwrap_err().to_string(),
+        format!("expression on line 2: {} * 2 overflows", 1i128 << 126)
+    );
+
+    let day = AdventDay18::parse(
+        "9223372036854775808 * 9223372036854775808\n9223372036854775808 * 9223372036854775808"
+            .to_owned(),
+    )
+    .unwrap();
+    assert_eq!(day.step1(), Err(EvaluationError::Sum));
+}
+
+#[test]
+fn test_precedence_tables() {
+    let examples = [
+        ("1 + 2 * 3 + 4 * 5 + 6", 71, 231),
+        ("1 + (2 * 3) + (4 * (5 + 6))", 51, 51),
+        ("2 * 3 + (4 * 5)", 26, 46),
+        ("5 + (8 * 3 + 9 + 3 * 4 * 3)", 437, 1445),
+        ("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))", 12240, 669060),
+        (
+            "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2",
+            13632,
+            23340,
+        ),
+    ];
+
+    for &(input, step1, step2) in &examples {
+        let expr = Expr::parse(input).unwrap();
+
+        assert_eq!(expr.evaluate(STEP1_PRECEDENCE), Ok(step1), "{}", input);
+        assert_eq!(expr.evaluate(STEP2_PRECEDENCE), Ok(step2), "{}", input);
     }
+}
 
-    fn reduce_expression(expr: &Expr, f: fn(&[Token]) -> usize) -> usize {
-        let reduced_expression: Vec<_> = expr
-            .tokens
-            .iter()
-            .map(|token| match token {
-                Token::Expr(inner) => Token::Lit(Self::reduce_expression(&inner, f)),
-                &Token::Lit(v) => Token::Lit(v),
-                &Token::Operation(op) => Token::Operation(op),
-            })
-            .collect();
+#[test]
+fn test_conventional_precedence() {
+    let examples = [
+        ("2 + 3 * 4 - 10 / 2", 9),
+        ("10 - 4 - 3", 3),
+        ("100 / 10 / 5", 2),
+        ("2 ^ 3 ^ 2", 512),
+        ("(1 + 2) ^ 2 - 4 / 2 * 3", 3),
+        ("7 / 2", 3),
+    ];
+
+    for &(input, expected) in &examples {
+        assert_eq!(
+            Expr::parse(input)
+                .unwrap()
+                .evaluate(CONVENTIONAL_PRECEDENCE),
+            Ok(expected),
+            "{}",
+            input
+        );
+    }
+
+    // Precedence of the original operations is unchanged in the puzzle modes
+    let expr = Expr::parse("2 * 3 + 4").unwrap();
+    assert_eq!(expr.evaluate(STEP1_PRECEDENCE), Ok(10));
+    assert_eq!(expr.evaluate(STEP2_PRECEDENCE), Ok(14));
+    assert_eq!(expr.evaluate(CONVENTIONAL_PRECEDENCE), Ok(10));
+}
 
-        f(&reduced_expression)
+#[test]
+fn test_arithmetic_errors() {
+    let examples = [
+        ("4 / (2 - 2)", ArithmeticError::DivisionByZero(4)),
+        ("1 + 2 - 4", ArithmeticError::Negative(Operation::Sub, 3, 4)),
+        ("2 ^ 127", ArithmeticError::Overflow(Operation::Pow, 2, 127)),
+    ];
+
+    for &(input, expected) in &examples {
+        assert_eq!(
+            Expr::parse(input)
+                .unwrap()
+                .evaluate(CONVENTIONAL_PRECEDENCE),
+            Err(expected),
+            "{}",
+            input
+        );
     }
+
+    assert_eq!(
+        Expr::parse("3 - 1").unwrap().evaluate(STEP1_PRECEDENCE),
+        Err(ArithmeticError::Unsupported(Operation::Sub))
+    );
+    assert_eq!(
+        ArithmeticError::DivisionByZero(4).to_string(),
+        "4 / 0 divides by zero"
+    );
+}
+
+#[test]
+fn test_evaluation_allocations() {
+    use crate::helper::counting_alloc::count_allocations;
+
+    let day = AdventDay18::parse(include_str!("../../data/2020/18/input.txt").to_owned()).unwrap();
+
+    let start = std::time::Instant::now();
+    let ((step1, step2), allocations) = count_allocations(|| (day.step1(), day.step2()));
+    print_info!("evaluated both steps in {:?}", start.elapsed());
+
+    assert_eq!(step1, Ok(4940631886147));
+    assert_eq!(step2, Ok(283582817678281));
+    assert_eq!(allocations, 0, "evaluation must not allocate");
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        Expr::parse("(1 + 2").unwrap_err(),
+        "0: at line 1:\n(1 + 2\n      ^\nexpected ')', got end of input\n\n\
+         1: at line 1, in expression operand:\n(1 + 2\n^\n\n\
+         2: at line 1, in root parser:\n(1 + 2\n^\n\n"
+    );
+    assert_eq!(
+        Expr::parse("1 + * 2").unwrap_err(),
+        "0: at line 1:\n1 + * 2\n    ^\nexpected '(', found *\n\n\
+         1: at line 1, in expression operand:\n1 + * 2\n    ^\n\n\
+         2: at line 1, in Alt:\n1 + * 2\n    ^\n\n\
+         3: at line 1, in root parser:\n1 + * 2\n^\n\n"
+    );
+
+    let err = AdventDay18::parse("1 + 2\n(1 + 2\n3 * 4\n1 + * 2\n".to_owned()).unwrap_err();
+    let lines: Vec<_> = err.lines().filter(|l| l.starts_with("line ")).collect();
+    assert_eq!(lines, ["line 2:", "line 4:"]);
 }
 
-impl AdventState for AdventDay18 {
-    const INPUT_FILES: &'static [&'static str] = &["test.txt", "input.txt"];
+#[test]
+fn test_stateful_day_expansion() {
+    struct HandWritten(AdventDay18);
+
+    impl AdventState for HandWritten {
+        const INPUT_FILES: &'static [&'static str] = &["test.txt", "input.txt"];
+        const TITLE: Option<&'static str> = Some("Operation Order");
 
-    fn new(_input_file: &'static str, input_content: String) -> Self {
-        Self::parse(input_content)
+        fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+            AdventDay18::parse(input_content).map(Self)
+        }
+
+        fn part1(&self) -> PartResult {
+            self.0
+                .step1()
+                .map(Answer::from)
+                .map_err(|err| err.to_string())
+        }
+
+        fn part2(&self) -> PartResult {
+            self.0
+                .step2()
+                .map(Answer::from)
+                .map_err(|err| err.to_string())
+        }
     }
 
-    fn run(self) {
-        println!("Answer to step 1: {}", self.step1());
-        println!("Answer to step 2: {}", self.step2());
+    let from_macro = StatefulAdvent::<AdventDay18>::new(18);
+    let hand_written = StatefulAdvent::<HandWritten>::new(18);
+
+    assert_eq!(from_macro.title(), hand_written.title());
+    assert_eq!(from_macro.get_input_names(), hand_written.get_input_names());
+
+    for input in [
+        "1 + 2 * 3 + 4 * 5 + 6\n2 * 3 + (4 * 5)",
+        "9223372036854775808 * 9223372036854775808 * 2",
+        "1 + ",
+    ] {
+        assert_eq!(
+            from_macro.solve("test.txt", input.to_owned(), Parts::All),
+            hand_written.solve("test.txt", input.to_owned(), Parts::All)
+        );
     }
 }
 
 #[test]
-fn test_parsing() {
-    let input = "4 + (5 + 8)";
+fn test_blanks() {
+    let spaced = Expr::parse("1 + (2 * 3)").unwrap().to_string();
+
+    for input in [
+        "1\t+\t(2\t*\t3)",
+        "  1   +  (  2 *   3 )  ",
+        "1+(2*3)",
+        "\t1 \t+ (2 *\t\t3)\t",
+    ] {
+        assert_eq!(
+            Expr::parse(input).unwrap().to_string(),
+            spaced,
+            "{:?}",
+            input
+        );
+    }
 
-    let expr = Expr::parse(input);
+    assert!(Expr::parse("1 +\n2").is_err());
+}
+
+#[test]
+fn test_plain_decimal_literals() {
+    assert!(Expr::parse("1_0 + 2").is_err());
+    assert!(Expr::parse("0x10 + 2").is_err());
+    assert_eq!(
+        Expr::parse("10 + 2").unwrap().evaluate(STEP1_PRECEDENCE),
+        Ok(12)
+    );
+}
+
+#[test]
+fn test_calculator() {
+    let mut calculator = Calculator::default();
+
+    assert_eq!(calculator.prompt(), "mode normal> ");
+    assert_eq!(calculator.eval("  "), None);
+    assert_eq!(calculator.eval("2 * 3 + 4").as_deref(), Some("10"));
+    assert_eq!(calculator.eval(":mode 2").as_deref(), Some("mode 2"));
+    assert_eq!(calculator.prompt(), "mode 2> ");
+    assert_eq!(calculator.eval("2 * 3 + 4").as_deref(), Some("14"));
+    assert_eq!(
+        calculator.eval("1 - 2").as_deref(),
+        Some("error: operation '-' is not supported in this mode")
+    );
+    assert_eq!(
+        calculator.eval(":mode 3").as_deref(),
+        Some("unknown mode 3, the modes are 1, 2, normal")
+    );
+    assert_eq!(calculator.eval(":mode").as_deref(), Some("mode 2"));
+    assert_eq!(
+        calculator.eval(":quit").as_deref(),
+        Some("unknown command :quit, see :help")
+    );
+    assert_eq!(
+        calculator.eval("(1 + 2").as_deref(),
+        Some(Expr::parse("(1 + 2").unwrap_err().trim_end())
+    );
+
+    // The day hands out the same calculator
+    let mut repl = StatefulAdvent::<AdventDay18>::new(18).repl().unwrap();
+    assert_eq!(repl.eval(":mode 1").as_deref(), Some("mode 1"));
+    assert_eq!(repl.eval("2 * 3 + 4 * 5").as_deref(), Some("50"));
+}
 
-    println!("result: {}", expr);
-    println!("result: {:?}", expr);
+#[test]
+fn test_explain() {
+    let examples = [
+        "1 + (2 * 3) + (4 * (5 + 6))",
+        "2 * 3 + (4 * 5)",
+        "5 + (8 * 3 + 9 + 3 * 4 * 3)",
+        "5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))",
+        "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2",
+    ];
+
+    let mut traces = String::new();
+    for (step, precedence) in [("step 1", STEP1_PRECEDENCE), ("step 2", STEP2_PRECEDENCE)] {
+        for input in examples {
+            let expr = Expr::parse(input).unwrap();
+            let (steps, value) = expr.explain(precedence);
+
+            assert_eq!(value, expr.evaluate(precedence), "{}", input);
+            assert_eq!(steps.last(), Some(&value.unwrap().to_string()), "{}", input);
+            writeln!(traces, "{}:\n  {}", step, steps.join("\n  → ")).unwrap();
+        }
+    }
+    insta::assert_snapshot!("explain", traces);
+
+    assert_eq!(
+        Expr::parse("1 + 2 * 3")
+            .unwrap()
+            .explain(STEP1_PRECEDENCE)
+            .0,
+        ["1 + 2 * 3", "3 * 3", "9"]
+    );
+    let (steps, value) = Expr::parse("(1 + 2) * (3 - 4)")
+        .unwrap()
+        .explain(CONVENTIONAL_PRECEDENCE);
+    assert_eq!(steps, ["(1 + 2) * (3 - 4)", "3 * (3 - 4)"]);
+    assert_eq!(value, Err(ArithmeticError::Negative(Operation::Sub, 3, 4)));
 }
@@ -1,17 +1,28 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryFrom;
+use std::fmt::{self, Write};
 use std::str::FromStr;
 
-use nom::Finish;
 use nom::Parser;
 
 use crate::advent_adapters::AdventState;
+use crate::advents::PartResult;
 use crate::helper;
 use crate::helper::nom::VerboseError;
 use itertools::Itertools;
 
 type ParsingError<'a> = helper::nom::VerboseError<&'a str>;
 
+const LOG_TARGET: &str = "aoc::y2020::d19";
+
+/// Set this variable to write the grammar of part 2 to `GRAMMAR_FILE`, before and after its
+/// rules are replaced and simplified
+const GRAMMAR_VAR: &str = "AOC_2020_19_BNF";
+const GRAMMAR_FILE: &str = "data/2020/19/grammar.bnf";
+
+/// Rules part 2 makes recursive on purpose
+const RECURSIVE_RULES: &[usize] = &[8, 11];
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 enum Rule {
     Lit(String),
@@ -21,7 +32,7 @@ enum Rule {
 }
 
 impl Rule {
-    fn parse(input: &str) -> nom::IResult<&str, Self, ParsingError> {
+    fn parse(input: &str) -> nom::IResult<&str, Self, ParsingError<'_>> {
         nom::branch::alt((
             Self::parse_alternative,
             Self::parse_sequence,
@@ -30,7 +41,7 @@ impl Rule {
         ))(input)
     }
 
-    fn parse_lit(input: &str) -> nom::IResult<&str, Self, ParsingError> {
+    fn parse_lit(input: &str) -> nom::IResult<&str, Self, ParsingError<'_>> {
         nom::combinator::map(
             nom::sequence::delimited(
                 nom::character::complete::char('"'),
@@ -41,20 +52,20 @@ impl Rule {
         )(input)
     }
 
-    fn parse_ref(input: &str) -> nom::IResult<&str, Self, ParsingError> {
+    fn parse_ref(input: &str) -> nom::IResult<&str, Self, ParsingError<'_>> {
         nom::combinator::map(nom::character::complete::digit1, |x: &str| {
             x.parse().map(Self::Ref).unwrap()
         })(input)
     }
 
-    fn parse_sequence(input: &str) -> nom::IResult<&str, Self, ParsingError> {
+    fn parse_sequence(input: &str) -> nom::IResult<&str, Self, ParsingError<'_>> {
         nom::combinator::map(
             nom::multi::separated_list1(nom::character::complete::space1, Self::parse_ref),
             Self::Sequence,
         )(input)
     }
 
-    fn parse_alternative(input: &str) -> nom::IResult<&str, Self, ParsingError> {
+    fn parse_alternative(input: &str) -> nom::IResult<&str, Self, ParsingError<'_>> {
         nom::combinator::map(
             nom::multi::separated_list1(nom::bytes::complete::tag(" | "), Self::parse_sequence),
             |v| {
@@ -68,6 +79,35 @@ impl Rule {
     }
 }
 
+impl Rule {
+    /// Numbers of the rules this one refers to directly, in order
+    fn references(&self) -> Vec<usize> {
+        match self {
+            Rule::Lit(_) => Vec::new(),
+            Rule::Ref(idx) => vec![*idx],
+            Rule::Sequence(v) | Rule::Alternative(v) => {
+                v.iter().flat_map(Rule::references).collect()
+            }
+        }
+    }
+
+    /// Right-hand side of the rule in BNF, like `r42 r31 | r42 r11 r31`
+    fn to_bnf(&self) -> String {
+        match self {
+            Rule::Lit(lit) => format!("{:?}", lit),
+            Rule::Ref(idx) => format!("r{}", idx),
+            Rule::Sequence(v) => v
+                .iter()
+                .map(|rule| match rule {
+                    Rule::Alternative(_) => format!("( {} )", rule.to_bnf()),
+                    _ => rule.to_bnf(),
+                })
+                .join(" "),
+            Rule::Alternative(v) => v.iter().map(Rule::to_bnf).join(" | "),
+        }
+    }
+}
+
 impl<'a> TryFrom<&'a str> for Rule {
     type Error = ParsingError<'a>;
 
@@ -85,37 +125,50 @@ struct RuleSet {
 }
 
 impl RuleSet {
-    fn parse(input: &str) -> Result<(&str, Self), ParsingError> {
-        nom::combinator::map(
-            nom::sequence::terminated(
-                nom::multi::separated_list1(
-                    nom::character::complete::line_ending,
-                    nom::sequence::separated_pair(
-                        nom::combinator::map_res(nom::character::complete::digit1, usize::from_str),
-                        nom::bytes::complete::tag(": "),
-                        Rule::parse,
+    /// Parse the rules at the start of the input, returning the messages following them
+    fn parse(input: &str) -> Result<(&str, Self), ParsingError<'_>> {
+        helper::nom::parse_all(
+            nom::sequence::pair(
+                nom::combinator::map(
+                    nom::sequence::terminated(
+                        nom::multi::separated_list1(
+                            nom::character::complete::line_ending,
+                            nom::sequence::separated_pair(
+                                nom::combinator::map_res(
+                                    nom::character::complete::digit1,
+                                    usize::from_str,
+                                ),
+                                nom::bytes::complete::tag(": "),
+                                Rule::parse,
+                            ),
+                        ),
+                        nom::sequence::pair(
+                            nom::character::complete::line_ending,
+                            nom::character::complete::line_ending,
+                        ),
                     ),
+                    |rules| Self {
+                        rules: rules.into_iter().collect(),
+                    },
                 ),
-                nom::bytes::complete::tag("\n\n"),
+                nom::combinator::rest,
             ),
-            |rules| Self {
-                rules: rules.into_iter().collect(),
-            },
-        )(input)
-        .finish()
+            input,
+        )
+        .map(|(rules, messages)| (messages, rules))
     }
 
-    fn parse_with_rule<'a>(&self, rule_idx: usize, input: &'a str) -> Result<(), RuleError<'a>> {
+    fn parse_with_rule(&self, rule_idx: usize, input: &str) -> Result<(), RuleError> {
         let rule = self.rules.get(&rule_idx).ok_or(RuleError::RuleNotFound)?;
 
-        nom::combinator::all_consuming(helper::nom::owned_context(
-            format!("Rule::Ref({})", rule_idx),
-            self.rule_to_parser(rule),
-        ))
-        .parse(input)
-        .finish()
-        .map(|(_, o)| o)
-        .map_err(RuleError::ParsingError)
+        helper::nom::parse_all(
+            helper::nom::owned_context(
+                format!("Rule::Ref({})", rule_idx),
+                self.rule_to_parser(rule),
+            ),
+            input,
+        )
+        .map_err(|err| RuleError::ParsingError(err.into_owned()))
     }
 
     fn rule_to_parser<'a, 'c>(
@@ -171,41 +224,229 @@ impl RuleSet {
         }
     }
 
+    /// Anchored regular expression matching the same messages as the rule, for rules without
+    /// recursion. References to missing rules never match, like with `parse_with_rule`
+    fn to_regex(&self, rule_idx: usize) -> Result<String, CycleError> {
+        let mut regex = String::from("^");
+        self.write_regex(&Rule::Ref(rule_idx), &mut Vec::new(), &mut regex)?;
+        regex.push('$');
+
+        Ok(regex)
+    }
+
+    /// Append the expansion of `rule` to `out`, `path` being the references leading to it
+    fn write_regex(
+        &self,
+        rule: &Rule,
+        path: &mut Vec<usize>,
+        out: &mut String,
+    ) -> Result<(), CycleError> {
+        match rule {
+            Rule::Lit(lit) => out.push_str(&regex::escape(lit)),
+            Rule::Ref(idx) => {
+                if let Some(start) = path.iter().position(|entry| entry == idx) {
+                    let mut cycle = path[start..].to_vec();
+                    cycle.push(*idx);
+                    return Err(CycleError(cycle));
+                }
+
+                match self.rules.get(idx) {
+                    // Nothing follows the end of the text, without needing the unicode
+                    // features an empty class like `[^\s\S]` would
+                    None => out.push_str("\\z."),
+                    Some(rule) => {
+                        path.push(*idx);
+                        self.write_regex(rule, path, out)?;
+                        path.pop();
+                    }
+                }
+            }
+            Rule::Sequence(v) => {
+                for rule in v {
+                    self.write_regex(rule, path, out)?;
+                }
+            }
+            Rule::Alternative(v) => {
+                out.push_str("(?:");
+                for (pos, rule) in v.iter().enumerate() {
+                    if pos > 0 {
+                        out.push('|');
+                    }
+                    self.write_regex(rule, path, out)?;
+                }
+                out.push(')');
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every cycle of references between the rules, found by a depth-first search from each rule
+    /// in order. Each cycle starts and ends with the same rule, like `CycleError`
+    fn find_cycles(&self) -> Vec<Vec<usize>> {
+        let mut cycles = Vec::new();
+        let mut visited = BTreeSet::new();
+
+        for &idx in self.rules.keys() {
+            self.visit_references(idx, &mut Vec::new(), &mut visited, &mut cycles);
+        }
+
+        cycles
+    }
+
+    fn visit_references(
+        &self,
+        idx: usize,
+        path: &mut Vec<usize>,
+        visited: &mut BTreeSet<usize>,
+        cycles: &mut Vec<Vec<usize>>,
+    ) {
+        if let Some(start) = path.iter().position(|&entry| entry == idx) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(idx);
+            cycles.push(cycle);
+            return;
+        }
+        if !visited.insert(idx) {
+            return;
+        }
+
+        if let Some(rule) = self.rules.get(&idx) {
+            path.push(idx);
+            for reference in rule.references() {
+                self.visit_references(reference, path, visited, cycles);
+            }
+            path.pop();
+        }
+    }
+
+    /// Fail on the cycles going through any rule other than the `allowed` ones, the nom parsers
+    /// never returning on some of them
+    fn check_cycles(&self, allowed: &[usize]) -> Result<(), String> {
+        let cycles = self.find_cycles();
+        let (expected, unexpected): (Vec<_>, Vec<_>) = cycles
+            .into_iter()
+            .partition(|cycle| cycle.iter().all(|idx| allowed.contains(idx)));
+
+        for cycle in &expected {
+            log::debug!(target: LOG_TARGET, "{}", CycleError(cycle.clone()));
+        }
+
+        match unexpected.as_slice() {
+            [] => Ok(()),
+            cycles => Err(cycles
+                .iter()
+                .map(|cycle| CycleError(cycle.clone()).to_string())
+                .join(", ")),
+        }
+    }
+
+    /// Matcher of the rule, a regex when it refers to no recursive rule and the regex can be
+    /// built, or the compiled rules
+    fn matcher(&self, rule_idx: usize) -> Result<Matcher, String> {
+        let regex = self
+            .to_regex(rule_idx)
+            .map_err(|err| err.to_string())
+            .and_then(|regex| {
+                regex::Regex::new(&regex)
+                    .map_err(|err| format!("invalid regex for rule {}: {}", rule_idx, err))
+            });
+
+        match regex {
+            Ok(regex) => Ok(Matcher::Regex(regex)),
+            Err(err) => {
+                log::debug!(target: LOG_TARGET, "Matching with the compiled rules, {}", err);
+                CompiledRules::new(self, rule_idx).map(Matcher::Compiled)
+            }
+        }
+    }
+
+    /// Every rule in BNF, in the order of their numbers
+    fn to_bnf(&self) -> String {
+        let mut bnf = String::new();
+        for (idx, rule) in &self.rules {
+            writeln!(bnf, "r{} ::= {} ;", idx, rule.to_bnf()).unwrap();
+        }
+        bnf
+    }
+
     fn merge_rules(&mut self, entries: impl IntoIterator<Item = (usize, Rule)>) {
         self.rules.extend(entries)
     }
 
-    fn simplify(&mut self) {
+    /// Rules that are part of a cycle or refer to one, see `find_cycles`
+    fn rules_reaching_cycles(&self) -> BTreeSet<usize> {
+        let mut reaching: BTreeSet<_> = self.find_cycles().into_iter().flatten().collect();
+
+        // Grow the set until no rule refers to it without being part of it
         loop {
-            let mut changes = Vec::new();
+            let referring: Vec<_> = self
+                .rules
+                .iter()
+                .filter(|(idx, rule)| {
+                    !reaching.contains(idx)
+                        && rule.references().iter().any(|r| reaching.contains(r))
+                })
+                .map(|(&idx, _)| idx)
+                .collect();
 
-            for (&idx, rule) in &self.rules {
-                if let Some(simplified) = self.simplify_rule(idx, rule) {
-                    changes.push((idx, simplified))
-                }
+            if referring.is_empty() {
+                return reaching;
             }
+            reaching.extend(referring);
+        }
+    }
 
-            if changes.is_empty() {
-                break;
+    /// Rules outside of `skipped`, each one after the rules it refers to
+    fn reverse_topological_order(&self, skipped: &BTreeSet<usize>) -> Vec<usize> {
+        fn visit(
+            rules: &BTreeMap<usize, Rule>,
+            idx: usize,
+            skipped: &BTreeSet<usize>,
+            order: &mut Vec<usize>,
+            visited: &mut BTreeSet<usize>,
+        ) {
+            if skipped.contains(&idx) || !visited.insert(idx) {
+                return;
             }
+            if let Some(rule) = rules.get(&idx) {
+                for reference in rule.references() {
+                    visit(rules, reference, skipped, order, visited);
+                }
+                order.push(idx);
+            }
+        }
 
-            for (idx, rule) in changes {
-                self.rules.insert(idx, rule);
+        let mut order = Vec::new();
+        let mut visited = BTreeSet::new();
+        for &idx in self.rules.keys() {
+            visit(&self.rules, idx, skipped, &mut order, &mut visited);
+        }
+        order
+    }
+
+    /// Replace the references to rules matching a single literal by that literal, in one pass
+    /// where each rule comes after the ones it refers to. Rules that are recursive or refer to
+    /// recursive ones are left as they are
+    fn simplify(&mut self) {
+        let recursive = self.rules_reaching_cycles();
+
+        for idx in self.reverse_topological_order(&recursive) {
+            if let Some(simplified) = self.simplify_rule(&self.rules[&idx]) {
+                self.rules.insert(idx, simplified);
             }
         }
     }
-    fn simplify_rule(&self, current_idx: usize, rule: &Rule) -> Option<Rule> {
+
+    fn simplify_rule(&self, rule: &Rule) -> Option<Rule> {
         match rule {
-            Rule::Ref(idx) if *idx != current_idx => self
+            Rule::Ref(idx) => self
                 .rules
                 .get(idx)
                 .filter(|r| matches!(r, Rule::Lit(_)))
                 .cloned(),
             Rule::Sequence(v) => {
-                let simplifications: Vec<_> = v
-                    .iter()
-                    .map(|r| self.simplify_rule(current_idx, r))
-                    .collect();
+                let simplifications: Vec<_> = v.iter().map(|r| self.simplify_rule(r)).collect();
 
                 if simplifications.iter().all(Option::is_none) {
                     return None;
@@ -232,10 +473,7 @@ impl RuleSet {
                 }
             }
             Rule::Alternative(v) => {
-                let simplifications: Vec<_> = v
-                    .iter()
-                    .map(|r| self.simplify_rule(current_idx, r))
-                    .collect();
+                let simplifications: Vec<_> = v.iter().map(|r| self.simplify_rule(r)).collect();
 
                 if simplifications.iter().all(Option::is_none) {
                     return None;
@@ -258,10 +496,235 @@ impl RuleSet {
     }
 }
 
+/// Rules referring to themselves, the first one of the cycle being repeated at its end
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CycleError(Vec<usize>);
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rule {} refers to itself through {}",
+            self.0[0],
+            self.0.iter().join(" > ")
+        )
+    }
+}
+
+/// Check the matcher and the regular expression of the rule agree on every message
+fn cross_check(rules: &RuleSet, rule_idx: usize, messages: &[&str]) -> Result<(), String> {
+    let regex = rules
+        .to_regex(rule_idx)
+        .map_err(|err| format!("rule {} has no regular expression: {}", rule_idx, err))?;
+    log::debug!(target: LOG_TARGET, "Regex of rule {}: {}", rule_idx, regex);
+
+    let regex = regex::Regex::new(&regex)
+        .map_err(|err| format!("invalid regex for rule {}: {}", rule_idx, err))?;
+
+    let compiled = CompiledRules::new(rules, rule_idx)?;
+
+    let disagreements: Vec<_> = messages
+        .iter()
+        .filter(|message| regex.is_match(message) != compiled.matches(message))
+        .collect();
+
+    if disagreements.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "the matcher and the regex of rule {} disagree on {} messages: {}",
+            rule_idx,
+            disagreements.len(),
+            disagreements.iter().join(", ")
+        ))
+    }
+}
+
+/// Rule of `CompiledRules`, its references checked and its literals interned
+#[derive(Debug)]
+enum CompiledRule {
+    /// Index of the literal in `CompiledRules::literals`
+    Lit(usize),
+    /// Number of a rule of the set, which exists
+    Ref(usize),
+    Sequence(Vec<CompiledRule>),
+    Alternative(Vec<CompiledRule>),
+    /// Reference to a missing rule, which never matches like with `parse_with_rule`
+    Never,
+}
+
+/// Rules compiled once into a table indexed by their numbers, matching the messages without
+/// building any parser for them. Unlike the nom parsers, the matching backtracks into every
+/// alternative, so a looping rule like `8: 42 | 42 8` leaves to the following rules whatever
+/// part of the message they can match
+#[derive(Debug)]
+struct CompiledRules {
+    rules: Vec<CompiledRule>,
+    literals: Vec<String>,
+    root: usize,
+}
+
+impl CompiledRules {
+    fn new(rules: &RuleSet, root: usize) -> Result<Self, String> {
+        if !rules.rules.contains_key(&root) {
+            return Err(format!("rule {} not found", root));
+        }
+
+        let len = rules.rules.keys().next_back().map_or(0, |&idx| idx + 1);
+        let mut interned = BTreeMap::new();
+        let compiled = (0..len)
+            .map(|idx| match rules.rules.get(&idx) {
+                Some(rule) => Self::compile(rule, &rules.rules, &mut interned),
+                None => CompiledRule::Never,
+            })
+            .collect();
+
+        let mut literals = vec![String::new(); interned.len()];
+        for (lit, id) in interned {
+            literals[id] = lit;
+        }
+
+        Ok(Self {
+            rules: compiled,
+            literals,
+            root,
+        })
+    }
+
+    fn compile(
+        rule: &Rule,
+        rules: &BTreeMap<usize, Rule>,
+        interned: &mut BTreeMap<String, usize>,
+    ) -> CompiledRule {
+        match rule {
+            Rule::Lit(lit) => {
+                let next_id = interned.len();
+                CompiledRule::Lit(*interned.entry(lit.clone()).or_insert(next_id))
+            }
+            Rule::Ref(idx) if rules.contains_key(idx) => CompiledRule::Ref(*idx),
+            Rule::Ref(_) => CompiledRule::Never,
+            Rule::Sequence(v) => CompiledRule::Sequence(
+                v.iter()
+                    .map(|r| Self::compile(r, rules, interned))
+                    .collect(),
+            ),
+            Rule::Alternative(v) => CompiledRule::Alternative(
+                v.iter()
+                    .map(|r| Self::compile(r, rules, interned))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn matches(&self, message: &str) -> bool {
+        let message = message.as_bytes();
+        self.match_rule(&self.rules[self.root], message, 0, &mut |end| {
+            end == message.len()
+        })
+    }
+
+    /// Match `rule` from `pos` in the message, handing each position it can end at to `rest`
+    /// until `rest` accepts one
+    fn match_rule(
+        &self,
+        rule: &CompiledRule,
+        message: &[u8],
+        pos: usize,
+        rest: &mut dyn FnMut(usize) -> bool,
+    ) -> bool {
+        match rule {
+            CompiledRule::Lit(id) => {
+                let lit = self.literals[*id].as_bytes();
+                message[pos..].starts_with(lit) && rest(pos + lit.len())
+            }
+            CompiledRule::Ref(idx) => self.match_rule(&self.rules[*idx], message, pos, rest),
+            CompiledRule::Sequence(v) => self.match_sequence(v, message, pos, rest),
+            CompiledRule::Alternative(v) => v
+                .iter()
+                .any(|rule| self.match_rule(rule, message, pos, &mut *rest)),
+            CompiledRule::Never => false,
+        }
+    }
+
+    fn match_sequence(
+        &self,
+        rules: &[CompiledRule],
+        message: &[u8],
+        pos: usize,
+        rest: &mut dyn FnMut(usize) -> bool,
+    ) -> bool {
+        match rules.split_first() {
+            None => rest(pos),
+            Some((first, others)) => self.match_rule(first, message, pos, &mut |end| {
+                self.match_sequence(others, message, end, &mut *rest)
+            }),
+        }
+    }
+}
+
+/// Matches the messages to a rule, see `RuleSet::matcher`
+enum Matcher {
+    Regex(regex::Regex),
+    Compiled(CompiledRules),
+}
+
+impl Matcher {
+    fn matches(&self, message: &str) -> bool {
+        match self {
+            Matcher::Regex(regex) => regex.is_match(message),
+            Matcher::Compiled(rules) => rules.matches(message),
+        }
+    }
+}
+
 #[derive(Debug)]
-enum RuleError<'a> {
+enum RuleError {
     RuleNotFound,
-    ParsingError(ParsingError<'a>),
+    ParsingError(VerboseError<String>),
+}
+
+/// Innermost context of a failed match and the path of rules leading to it, outermost first
+fn describe_failure(err: &VerboseError<String>) -> String {
+    let innermost = err.contexts().next().unwrap_or("no context");
+    let rule_path = err
+        .contexts()
+        .filter_map(|ctx| ctx.strip_prefix("Rule::Ref("))
+        .filter_map(|ctx| ctx.strip_suffix(')'))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .join(" > ");
+    let remaining = err.deepest().map_or("", |(input, _)| input.as_str());
+
+    format!(
+        "{} failed at {:?} through rules {}",
+        innermost, remaining, rule_path
+    )
+}
+
+/// Messages following the rules, one per line whatever the line endings, without blank lines
+fn messages(data: &str) -> Vec<&str> {
+    data.lines().filter(|line| !line.is_empty()).collect()
+}
+
+/// Replacements of the `RECURSIVE_RULES` in part 2
+fn recursive_rules() -> [(usize, Rule); 2] {
+    [
+        (
+            8,
+            Rule::Alternative(vec![
+                Rule::Ref(42),
+                Rule::Sequence(vec![Rule::Ref(42), Rule::Ref(8)]),
+            ]),
+        ),
+        (
+            11,
+            Rule::Alternative(vec![
+                Rule::Sequence(vec![Rule::Ref(42), Rule::Ref(31)]),
+                Rule::Sequence(vec![Rule::Ref(42), Rule::Ref(11), Rule::Ref(31)]),
+            ]),
+        ),
+    ]
 }
 
 pub struct AdventDay19 {
@@ -274,67 +737,318 @@ impl AdventState for AdventDay19 {
         "test2.txt",
         // "input.txt",
     ];
+    const TITLE: Option<&'static str> = Some("Monster Messages");
 
-    fn new(_: &'static str, input_content: String) -> Self {
-        Self {
+    fn new(_: &str, input_content: String) -> Result<Self, String> {
+        Ok(Self {
             data: input_content,
-        }
+        })
     }
 
-    fn run(self) {
-        let (data, mut rules) = RuleSet::parse(&self.data).expect("could not parse input");
+    fn part1(&self) -> PartResult {
+        let (data, rules) = RuleSet::parse(&self.data).map_err(|err| err.to_string())?;
+        rules.check_cycles(&[])?;
 
-        let data: Vec<_> = data.split('\n').collect();
+        let messages = messages(data);
 
-        let matching_step_1: Vec<_> = data
-            .iter()
-            .cloned()
-            .filter(|line| rules.parse_with_rule(0, line).is_ok())
+        // Debug builds check the compiled rules against the regex, reporting any disagreement
+        // without changing the answer
+        if cfg!(debug_assertions) {
+            if let Err(err) = cross_check(&rules, 0, &messages) {
+                log::error!(target: LOG_TARGET, "{}", err);
+            }
+        }
+
+        let matcher = rules.matcher(0)?;
+        let matching_step_1: Vec<_> = messages
+            .into_iter()
+            .filter(|line| matcher.matches(line))
             .collect();
 
-        println!("Matches for 1: {:#?}", matching_step_1);
-        println!("Step 1: {}", matching_step_1.len());
+        log::debug!(target: LOG_TARGET, "Matches for 1: {:#?}", matching_step_1);
 
-        rules.merge_rules([
-            (
-                8,
-                Rule::Alternative(vec![
-                    Rule::Ref(42),
-                    Rule::Sequence(vec![Rule::Ref(42), Rule::Ref(8)]),
-                ]),
-            ),
-            (
-                11,
-                Rule::Alternative(vec![
-                    Rule::Sequence(vec![Rule::Ref(42), Rule::Ref(31)]),
-                    Rule::Sequence(vec![Rule::Ref(42), Rule::Ref(11), Rule::Ref(31)]),
-                ]),
-            ),
-        ]);
+        Ok(matching_step_1.len().into())
+    }
 
-        rules.rules.iter().for_each(|entry| println!("{:?}", entry));
+    fn part2(&self) -> PartResult {
+        let (data, mut rules) = RuleSet::parse(&self.data).map_err(|err| err.to_string())?;
+        let original_bnf = rules.to_bnf();
 
-        rules.simplify();
-        rules.rules.iter().for_each(|entry| println!("{:?}", entry));
+        rules.merge_rules(recursive_rules());
+        rules.check_cycles(RECURSIVE_RULES)?;
 
-        let matching_step_2: Vec<_> = data
+        rules
+            .rules
             .iter()
-            .cloned()
-            .enumerate()
-            .filter(|(pos, line)| match rules.parse_with_rule(0, line) {
-                Ok(_) => true,
-                Err(RuleError::RuleNotFound) => {
-                    eprintln!("Rule not found!");
-                    false
+            .for_each(|entry| log::trace!(target: LOG_TARGET, "{:?}", entry));
+
+        rules.simplify();
+        rules
+            .rules
+            .iter()
+            .for_each(|entry| log::trace!(target: LOG_TARGET, "simplified {:?}", entry));
+
+        if std::env::var_os(GRAMMAR_VAR).is_some() {
+            let grammar = format!(
+                "(* rules as parsed *)\n{}\n(* rules of part 2, simplified *)\n{}",
+                original_bnf,
+                rules.to_bnf()
+            );
+            match std::fs::write(GRAMMAR_FILE, grammar) {
+                Ok(()) => log::info!(target: LOG_TARGET, "Grammar written to {}", GRAMMAR_FILE),
+                Err(err) => {
+                    log::warn!(target: LOG_TARGET, "could not write {}: {}", GRAMMAR_FILE, err)
                 }
-                Err(RuleError::ParsingError(err)) => {
-                    eprintln!("{}: ({}) -> {}", pos, line, err);
-                    false
+            }
+        }
+
+        let matcher = rules.matcher(0)?;
+        let matching_step_2: Vec<_> = messages(data)
+            .into_iter()
+            .enumerate()
+            .filter(|(pos, line)| {
+                let matched = matcher.matches(line);
+
+                // Only the nom parsers tell where the message stops matching
+                if !matched && log::log_enabled!(target: LOG_TARGET, log::Level::Trace) {
+                    if let Err(RuleError::ParsingError(err)) = rules.parse_with_rule(0, line) {
+                        log::trace!(
+                            target: LOG_TARGET,
+                            "{}: ({}) -> {}",
+                            pos,
+                            line,
+                            describe_failure(&err)
+                        );
+                    }
                 }
+
+                matched
             })
             .collect();
 
-        println!("Matches for 2: {:#?}", matching_step_2);
-        println!("Step 2: {}", matching_step_2.len());
+        log::debug!(target: LOG_TARGET, "Matches for 2: {:#?}", matching_step_2);
+
+        Ok(matching_step_2.len().into())
+    }
+}
+
+#[test]
+fn test_describe_failure() {
+    let (_, rules) = RuleSet::parse("0: 1 2\n1: \"a\"\n2: 1 3\n3: \"b\"\n\n").unwrap();
+
+    assert!(rules.parse_with_rule(0, "aab").is_ok());
+
+    let err = match rules.parse_with_rule(0, "aaa") {
+        Err(RuleError::ParsingError(err)) => err,
+        other => panic!("unexpected result {:?}", other),
+    };
+    assert_eq!(
+        describe_failure(&err),
+        "rule Lit(\"b\") failed at \"a\" through rules 0 > 2 > 3"
+    );
+}
+
+#[test]
+fn test_to_regex() {
+    let (_, mut rules) =
+        RuleSet::parse("0: 1 2\n1: \"a\"\n2: 1 3 | 3 1\n3: \"b\"\n4: 5\n\n").unwrap();
+
+    assert_eq!(rules.to_regex(0), Ok("^a(?:ab|ba)$".to_owned()));
+    assert_eq!(rules.to_regex(4), Ok("^\\z.$".to_owned()));
+    assert_eq!(
+        cross_check(&rules, 0, &["aab", "aba", "abb", "", "aabb"]),
+        Ok(())
+    );
+
+    // A missing rule never matches, in a sequence or as an alternative, with the regex features
+    // the crate is built with
+    let (_, missing) = RuleSet::parse("0: 1 2\n1: \"a\"\n2: 1 3 | 1\n\n").unwrap();
+    let regex = regex::Regex::new(&missing.to_regex(0).unwrap()).unwrap();
+    assert!(regex.is_match("aa"));
+    assert!(!regex.is_match("aaa"));
+    assert!(!regex.is_match(""));
+    assert_eq!(cross_check(&missing, 0, &["aa", "a", "aaa", ""]), Ok(()));
+
+    rules.merge_rules([
+        (
+            2,
+            Rule::Alternative(vec![
+                Rule::Ref(1),
+                Rule::Sequence(vec![Rule::Ref(1), Rule::Ref(2)]),
+            ]),
+        ),
+        (3, Rule::Ref(0)),
+    ]);
+    assert_eq!(rules.to_regex(0), Err(CycleError(vec![2, 2])));
+    assert_eq!(
+        CycleError(vec![0, 2, 3, 0]).to_string(),
+        "rule 0 refers to itself through 0 > 2 > 3 > 0"
+    );
+}
+
+#[test]
+fn test_regex_agrees_with_the_matcher() {
+    for input in [
+        include_str!("../../data/2020/19/test1.txt"),
+        include_str!("../../data/2020/19/test2.txt"),
+        include_str!("../../data/2020/19/input.txt"),
+    ] {
+        let (data, rules) = RuleSet::parse(input).unwrap();
+        let messages = messages(data);
+
+        assert_eq!(cross_check(&rules, 0, &messages), Ok(()));
+    }
+}
+
+#[test]
+fn test_to_bnf() {
+    let mut rules = RuleSet {
+        rules: BTreeMap::new(),
+    };
+    rules.merge_rules([
+        (
+            11,
+            Rule::Alternative(vec![
+                Rule::Sequence(vec![Rule::Ref(42), Rule::Ref(31)]),
+                Rule::Sequence(vec![Rule::Ref(42), Rule::Ref(11), Rule::Ref(31)]),
+            ]),
+        ),
+        (2, Rule::Lit("ab".to_owned())),
+        (
+            0,
+            Rule::Sequence(vec![
+                Rule::Ref(2),
+                Rule::Alternative(vec![Rule::Ref(11), Rule::Lit("\"".to_owned())]),
+            ]),
+        ),
+    ]);
+
+    assert_eq!(
+        rules.to_bnf(),
+        "r0 ::= r2 ( r11 | \"\\\"\" ) ;\n\
+         r2 ::= \"ab\" ;\n\
+         r11 ::= r42 r31 | r42 r11 r31 ;\n"
+    );
+}
+
+#[test]
+fn test_find_cycles() {
+    let (_, rules) = RuleSet::parse("0: 1 0\n1: \"a\"\n\n").unwrap();
+    assert_eq!(rules.find_cycles(), [[0, 0]]);
+
+    let (_, rules) = RuleSet::parse("0: 1 | 3\n1: 3 2\n2: 1 3 | 3\n3: \"a\"\n\n").unwrap();
+    assert_eq!(rules.find_cycles(), [[1, 2, 1]]);
+    assert_eq!(
+        rules.check_cycles(&[]),
+        Err("rule 1 refers to itself through 1 > 2 > 1".to_owned())
+    );
+    assert_eq!(rules.check_cycles(&[1, 2]), Ok(()));
+
+    let (_, mut rules) = RuleSet::parse(include_str!("../../data/2020/19/test2.txt")).unwrap();
+    assert!(rules.find_cycles().is_empty());
+    assert!(matches!(rules.matcher(0), Ok(Matcher::Regex(_))));
+
+    rules.merge_rules(recursive_rules());
+    assert_eq!(rules.find_cycles(), [[8, 8], [11, 11]]);
+    assert_eq!(rules.check_cycles(RECURSIVE_RULES), Ok(()));
+    assert!(matches!(rules.matcher(0), Ok(Matcher::Compiled(_))));
+
+    // Fails instead of never returning
+    let day = AdventDay19 {
+        data: "0: 0\n\na".to_owned(),
+    };
+    assert_eq!(
+        day.part1(),
+        Err("rule 0 refers to itself through 0 > 0".to_owned())
+    );
+}
+
+#[test]
+fn test_compiled_rules() {
+    let (_, rules) =
+        RuleSet::parse("0: 8 1\n1: \"a\"\n2: \"a\"\n3: 1 4 | 2\n8: 1 | 1 8\n\n").unwrap();
+    let compiled = CompiledRules::new(&rules, 0).unwrap();
+
+    assert_eq!(compiled.literals, ["a"]);
+    assert!(matches!(compiled.rules[4], CompiledRule::Never));
+    assert!(compiled.matches("aa"));
+    assert!(!compiled.matches("a"));
+    assert!(!compiled.matches("aab"));
+
+    // The parsers stop after the first alternative of the looping rule, the compiled rules don't
+    assert!(compiled.matches("aaaa"));
+    assert!(rules.parse_with_rule(0, "aaaa").is_err());
+
+    // The missing rule never matches
+    let compiled = CompiledRules::new(&rules, 3).unwrap();
+    assert!(compiled.matches("a"));
+    assert!(!compiled.matches("aa"));
+
+    assert_eq!(
+        CompiledRules::new(&rules, 5).unwrap_err(),
+        "rule 5 not found"
+    );
+}
+
+#[test]
+fn test_matcher_fallback() {
+    // A rule missing from both parts
+    let day = AdventDay19 {
+        data: "0: 1 2\n1: \"a\"\n2: 1 3 | 1\n\naa\naaa\nab\n".to_owned(),
+    };
+    assert_eq!(day.part1(), Ok(1u8.into()));
+    assert_eq!(day.part2(), Ok(1u8.into()));
+
+    // Alternatives nested deeper than the regex parser allows
+    let mut grammar: String = (0..300)
+        .map(|idx| format!("{}: {} | 300\n", idx, idx + 1))
+        .collect();
+    grammar.push_str("300: \"a\"\n\n");
+    let (_, rules) = RuleSet::parse(&grammar).unwrap();
+
+    let matcher = rules.matcher(0).unwrap();
+    assert!(matches!(matcher, Matcher::Compiled(_)));
+    assert!(matcher.matches("a"));
+    assert!(!matcher.matches("aa"));
+}
+
+#[test]
+fn test_simplify() {
+    let (_, mut rules) =
+        RuleSet::parse("0: 1 4\n1: 2 3 | 3\n2: 1 3\n3: \"a\"\n4: 3 5\n5: 3 3 | 6\n6: \"aa\"\n\n")
+            .unwrap();
+    let original = rules.rules.clone();
+
+    rules.simplify();
+
+    // The mutually recursive rules and the rule referring to them are kept as they are
+    for idx in [0, 1, 2] {
+        assert_eq!(rules.rules[&idx], original[&idx], "rule {}", idx);
+    }
+    assert_eq!(rules.rules[&5], Rule::Lit("aa".to_owned()));
+    assert_eq!(rules.rules[&4], Rule::Lit("aaa".to_owned()));
+    assert_eq!(
+        rules.rules_reaching_cycles(),
+        [0, 1, 2].iter().copied().collect()
+    );
+}
+
+#[test]
+fn test_line_endings() {
+    let sample = include_str!("../../data/2020/19/test2.txt").trim_end();
+    let answers = |input: String| {
+        let day = AdventDay19 { data: input };
+        (day.part1(), day.part2())
+    };
+
+    let expected = answers(sample.to_owned());
+    assert_eq!(expected.0, Ok(3usize.into()));
+    for input in [
+        format!("{}\n", sample),
+        format!("{}\n\n\n", sample),
+        sample.replace('\n', "\r\n"),
+        format!("{}\r\n", sample.replace('\n', "\r\n")),
+    ] {
+        assert_eq!(answers(input.clone()), expected, "{:?}", input);
     }
 }
@@ -0,0 +1,267 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::advent_adapters::AdventState;
+use crate::advents::{Answer, PartResult};
+use crate::helper::grid::Grid;
+use crate::helper::parse::blocks;
+
+const SEA_MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+];
+
+struct Tile {
+    id: u64,
+    image: Grid<bool>,
+}
+
+impl Tile {
+    fn parse(block: &str) -> Result<Self, String> {
+        let (header, image) = block.split_once('\n').unwrap_or((block, ""));
+
+        let id = header
+            .trim()
+            .strip_prefix("Tile ")
+            .and_then(|rest| rest.strip_suffix(':'))
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| format!("invalid tile header '{}'", header))?;
+
+        let image = Grid::parse(image, |cell| match cell {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .map_err(|err| format!("tile {}: {}", id, err))?;
+
+        if image.width() != image.height() || image.width() < 3 {
+            return Err(format!("tile {} is not a square with an inner image", id));
+        }
+
+        Ok(Self { id, image })
+    }
+}
+
+/// Borders of a tile, clockwise from the top one, each read left to right or top to bottom
+fn borders(image: &Grid<bool>) -> [Vec<bool>; 4] {
+    [
+        image.row(0).to_vec(),
+        image.column(image.width() - 1).copied().collect(),
+        image.row(image.height() - 1).to_vec(),
+        image.column(0).copied().collect(),
+    ]
+}
+
+/// Border in a form that doesn't depend on which way the tile is flipped
+fn canonical(border: &[bool]) -> Vec<bool> {
+    let reversed: Vec<_> = border.iter().rev().copied().collect();
+    reversed.max(border.to_vec())
+}
+
+pub struct AdventDay20 {
+    tiles: Vec<Tile>,
+    /// Indices of the tiles having each border, in canonical form
+    tiles_by_border: HashMap<Vec<bool>, Vec<usize>>,
+}
+
+impl AdventDay20 {
+    fn parse(input: &str) -> Result<Self, String> {
+        let tiles: Vec<_> = blocks(input).map(Tile::parse).collect::<Result<_, _>>()?;
+
+        if tiles.is_empty() {
+            return Err("no tiles in the input".to_owned());
+        }
+
+        let mut tiles_by_border: HashMap<_, Vec<_>> = HashMap::new();
+        for (index, tile) in tiles.iter().enumerate() {
+            for border in &borders(&tile.image) {
+                tiles_by_border
+                    .entry(canonical(border))
+                    .or_default()
+                    .push(index);
+            }
+        }
+
+        if let Some(shared) = tiles_by_border.values().find(|tiles| tiles.len() > 2) {
+            return Err(format!(
+                "a border is shared by {} tiles, matches are ambiguous",
+                shared.len()
+            ));
+        }
+
+        Ok(Self {
+            tiles,
+            tiles_by_border,
+        })
+    }
+
+    /// Whether a border is at the edge of the image, not matching any other tile
+    fn is_outer(&self, border: &[bool]) -> bool {
+        self.tiles_by_border[&canonical(border)].len() == 1
+    }
+
+    /// Indices of the tiles with two outer borders
+    fn corners(&self) -> Result<Vec<usize>, String> {
+        let corners: Vec<_> = (0..self.tiles.len())
+            .filter(|&index| {
+                borders(&self.tiles[index].image)
+                    .iter()
+                    .filter(|border| self.is_outer(border))
+                    .count()
+                    == 2
+            })
+            .collect();
+
+        if corners.len() == 4 {
+            Ok(corners)
+        } else {
+            Err(format!("expected 4 corner tiles, found {}", corners.len()))
+        }
+    }
+
+    /// Product of the IDs of the corner tiles
+    fn step1(&self) -> Result<u64, String> {
+        Ok(self
+            .corners()?
+            .into_iter()
+            .map(|index| self.tiles[index].id)
+            .product())
+    }
+
+    /// Number of `#` cells in the assembled image that are not part of a sea monster
+    fn step2(&self) -> Result<usize, String> {
+        let monster: Vec<_> = SEA_MONSTER
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.char_indices()
+                    .filter(|&(_, cell)| cell == '#')
+                    .map(move |(column, _)| (row, column))
+            })
+            .collect();
+        let monster_height = SEA_MONSTER.len();
+        let monster_width = SEA_MONSTER[0].len();
+
+        for image in self.assemble()?.orientations() {
+            if image.height() < monster_height || image.width() < monster_width {
+                break;
+            }
+
+            let mut monster_cells = HashSet::new();
+            for row in 0..=image.height() - monster_height {
+                for column in 0..=image.width() - monster_width {
+                    let cells = monster.iter().map(|&(r, c)| (row + r, column + c));
+
+                    if cells.clone().all(|(r, c)| image.get(r, c) == Some(&true)) {
+                        monster_cells.extend(cells);
+                    }
+                }
+            }
+
+            if !monster_cells.is_empty() {
+                let rough = image.iter().filter(|&&cell| cell).count();
+                return Ok(rough - monster_cells.len());
+            }
+        }
+
+        Err("no sea monsters in any orientation of the image".to_owned())
+    }
+
+    /// Lay the tiles out so all their borders match, then join them without their borders
+    fn assemble(&self) -> Result<Grid<bool>, String> {
+        let side = (1..=self.tiles.len())
+            .find(|side| side * side >= self.tiles.len())
+            .filter(|side| side * side == self.tiles.len())
+            .ok_or_else(|| format!("{} tiles can't make a square", self.tiles.len()))?;
+
+        // Start from a corner turned so its outer borders are at the top and on the left
+        let corner = self.corners()?[0];
+        let first = self.tiles[corner]
+            .image
+            .orientations()
+            .find(|image| {
+                let [top, _, _, left] = borders(image);
+                self.is_outer(&top) && self.is_outer(&left)
+            })
+            .expect("a corner can always be turned to the top-left");
+
+        let mut used = vec![false; self.tiles.len()];
+        used[corner] = true;
+        let mut layout: Vec<Grid<bool>> = vec![first];
+
+        for position in 1..side * side {
+            let (row, column) = (position / side, position % side);
+
+            // Borders the next tile must have on the left and at the top, if any
+            let left = (column > 0).then(|| borders(&layout[position - 1])[1].clone());
+            let top = (row > 0).then(|| borders(&layout[position - side])[2].clone());
+
+            let next_to = left.as_ref().or(top.as_ref()).expect("not the first tile");
+            let (index, image) = self.tiles_by_border[&canonical(next_to)]
+                .iter()
+                .filter(|&&index| !used[index])
+                .flat_map(|&index| {
+                    self.tiles[index]
+                        .image
+                        .orientations()
+                        .map(move |image| (index, image))
+                })
+                .find(|(_, image)| {
+                    let [tile_top, _, _, tile_left] = borders(image);
+                    left.as_ref().is_none_or(|left| *left == tile_left)
+                        && top.as_ref().is_none_or(|top| *top == tile_top)
+                })
+                .ok_or_else(|| format!("no tile fits at row {}, column {}", row + 1, column + 1))?;
+
+            used[index] = true;
+            layout.push(image);
+        }
+
+        let inner = layout[0].width() - 2;
+        Ok(Grid::from_fn(side * inner, side * inner, |row, column| {
+            let tile = &layout[(row / inner) * side + column / inner];
+            tile.get(1 + row % inner, 1 + column % inner) == Some(&true)
+        }))
+    }
+}
+
+impl AdventState for AdventDay20 {
+    const INPUT_FILES: &'static [&'static str] = &["test.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Jurassic Jigsaw");
+
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        Self::parse(&input_content)
+    }
+
+    fn part1(&self) -> PartResult {
+        self.step1().map(Answer::from)
+    }
+
+    fn part2(&self) -> PartResult {
+        self.step2().map(Answer::from)
+    }
+}
+
+#[test]
+fn test_sample() {
+    let day = AdventDay20::parse(include_str!("../../data/2020/20/test.txt")).unwrap();
+
+    assert_eq!(day.step1(), Ok(20899048083289));
+    assert_eq!(day.step2(), Ok(273));
+}
+
+#[test]
+fn test_invalid_tiles() {
+    assert_eq!(
+        AdventDay20::parse("Tile x:\n#.\n.#").err(),
+        Some("invalid tile header 'Tile x:'".to_owned())
+    );
+    assert_eq!(
+        AdventDay20::parse("Tile 1:\n#..\n.#").err(),
+        Some("tile 1: row 2 has 2 cells, expected 3".to_owned())
+    );
+    assert_eq!(
+        AdventDay20::parse("Tile 1:\n#..\n.#.").err(),
+        Some("tile 1 is not a square with an inner image".to_owned())
+    );
+}
@@ -0,0 +1,182 @@
+use crate::advent_adapters::AdventState;
+use crate::advents::PartResult;
+
+pub struct AdventDay23 {
+    /// Cup labels clockwise from the current cup
+    cups: Vec<u32>,
+}
+
+/// Circle of cups labeled from 1 to the number of cups.
+///
+/// The circle is stored as a linked list inside an array, `next[c]` being the label of the cup
+/// clockwise from cup `c`. That makes a move constant time, while rotating a `Vec` would be
+/// linear in the number of cups, which is not an option for a million cups. The index 0 is
+/// unused so labels can be used as indices directly.
+struct Circle {
+    next: Vec<u32>,
+    current: u32,
+}
+
+impl Circle {
+    /// Circle with the given cups followed by all the labels after them up to `total`
+    fn new(cups: &[u32], total: u32) -> Self {
+        let labels = cups.iter().copied().chain(cups.len() as u32 + 1..=total);
+
+        let mut next = vec![0; total as usize + 1];
+        let mut last = None;
+        for label in labels {
+            if let Some(last) = last {
+                next[last as usize] = label;
+            }
+            last = Some(label);
+        }
+
+        let current = cups[0];
+        next[last.expect("there is at least one cup") as usize] = current;
+
+        Self { next, current }
+    }
+
+    fn play(&mut self, moves: usize) {
+        let highest = (self.next.len() - 1) as u32;
+
+        for _ in 0..moves {
+            let first = self.next[self.current as usize];
+            let second = self.next[first as usize];
+            let third = self.next[second as usize];
+
+            let mut destination = self.current;
+            loop {
+                destination = if destination == 1 {
+                    highest
+                } else {
+                    destination - 1
+                };
+
+                if destination != first && destination != second && destination != third {
+                    break;
+                }
+            }
+
+            // Splice the three picked up cups after the destination
+            self.next[self.current as usize] = self.next[third as usize];
+            self.next[third as usize] = self.next[destination as usize];
+            self.next[destination as usize] = first;
+
+            self.current = self.next[self.current as usize];
+        }
+    }
+
+    /// Labels clockwise after the given cup, stopping before coming back to it
+    fn after(&self, cup: u32) -> impl Iterator<Item = u32> + '_ {
+        std::iter::successors(Some(self.next[cup as usize]), move |&label| {
+            Some(self.next[label as usize])
+        })
+        .take_while(move |&label| label != cup)
+    }
+}
+
+impl AdventDay23 {
+    fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+
+        let cups: Vec<_> = input
+            .chars()
+            .map(|label| {
+                label
+                    .to_digit(10)
+                    .ok_or_else(|| format!("invalid cup label '{}'", label))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut sorted = cups.clone();
+        sorted.sort_unstable();
+        if sorted.is_empty() || !sorted.iter().copied().eq(1..=sorted.len() as u32) {
+            return Err(format!(
+                "cups must be labeled from 1 to their count, got '{}'",
+                input
+            ));
+        }
+
+        Ok(Self { cups })
+    }
+
+    /// Labels after cup 1 once the crab made 100 moves
+    fn step1(&self) -> String {
+        let mut circle = Circle::new(&self.cups, self.cups.len() as u32);
+        circle.play(100);
+
+        circle.after(1).map(|label| label.to_string()).collect()
+    }
+
+    /// Product of the two cups after cup 1 once the crab made ten million moves with a million
+    /// cups
+    fn step2(&self) -> u64 {
+        let mut circle = Circle::new(&self.cups, 1_000_000);
+        circle.play(10_000_000);
+
+        circle.after(1).take(2).map(u64::from).product()
+    }
+}
+
+impl AdventState for AdventDay23 {
+    const INPUT_FILES: &'static [&'static str] = &["test.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Crab Cups");
+    // The second part makes 10 million moves with a million cups
+    const SLOW: bool = true;
+
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        Self::parse(&input_content)
+    }
+
+    fn part1(&self) -> PartResult {
+        Ok(self.step1().into())
+    }
+
+    fn part2(&self) -> PartResult {
+        Ok(self.step2().into())
+    }
+}
+
+#[test]
+fn test_moves() {
+    let day = AdventDay23::parse(include_str!("../../data/2020/23/test.txt")).unwrap();
+
+    let mut circle = Circle::new(&day.cups, 9);
+    circle.play(10);
+    assert_eq!(
+        circle
+            .after(1)
+            .map(|label| label.to_string())
+            .collect::<String>(),
+        "92658374"
+    );
+
+    assert_eq!(day.step1(), "67384529");
+}
+
+#[test]
+fn test_million_cups() {
+    let day = AdventDay23::parse(include_str!("../../data/2020/23/test.txt")).unwrap();
+
+    let start = std::time::Instant::now();
+    assert_eq!(day.step2(), 149245887792);
+
+    // A few hundred milliseconds in debug builds, this only catches a quadratic implementation
+    assert!(
+        start.elapsed() < std::time::Duration::from_secs(30),
+        "ten million moves took {:?}",
+        start.elapsed()
+    );
+}
+
+#[test]
+fn test_invalid_cups() {
+    assert!(AdventDay23::parse("").is_err());
+    assert!(AdventDay23::parse("1224").is_err());
+    assert!(AdventDay23::parse("1345").is_err());
+    assert_eq!(
+        AdventDay23::parse("12a").err(),
+        Some("invalid cup label 'a'".to_owned())
+    );
+}
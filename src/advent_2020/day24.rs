@@ -0,0 +1,128 @@
+use std::collections::HashSet;
+
+use crate::advent_adapters::AdventState;
+use crate::advents::PartResult;
+use crate::helper::automaton::{self, Rules};
+use crate::helper::hex::{Direction, Hex};
+
+const DAYS: usize = 100;
+
+/// Black tiles stay black with 1 or 2 black neighbors, white tiles turn black with exactly 2
+const RULES: Rules = Rules {
+    birth: &[2],
+    survival: &[1, 2],
+};
+
+pub struct AdventDay24 {
+    /// Tiles that are flipped to black side up
+    black_tiles: HashSet<Hex>,
+}
+
+/// Split a line into the directions it lists, with no delimiter between them
+fn parse_path(line: &str) -> Result<Vec<Direction>, String> {
+    let mut directions = Vec::new();
+    let mut rest = line;
+
+    while !rest.is_empty() {
+        let (direction, length) = match rest.as_bytes() {
+            [b'e', ..] => (Direction::East, 1),
+            [b'w', ..] => (Direction::West, 1),
+            [b's', b'e', ..] => (Direction::SouthEast, 2),
+            [b's', b'w', ..] => (Direction::SouthWest, 2),
+            [b'n', b'e', ..] => (Direction::NorthEast, 2),
+            [b'n', b'w', ..] => (Direction::NorthWest, 2),
+            _ => return Err(format!("invalid direction at '{}'", rest)),
+        };
+
+        directions.push(direction);
+        rest = &rest[length..];
+    }
+
+    Ok(directions)
+}
+
+impl AdventDay24 {
+    fn parse(input: &str) -> Result<Self, String> {
+        let mut black_tiles = HashSet::new();
+
+        for (line, text) in input.lines().enumerate() {
+            let tile = parse_path(text.trim())
+                .map_err(|err| format!("line {}: {}", line + 1, err))?
+                .into_iter()
+                .fold(Hex::default(), Hex::neighbor);
+
+            // Flipping a tile a second time turns it back to white
+            if !black_tiles.insert(tile) {
+                black_tiles.remove(&tile);
+            }
+        }
+
+        Ok(Self { black_tiles })
+    }
+
+    /// Number of black tiles once all the tiles in the list are flipped
+    fn step1(&self) -> usize {
+        self.black_tiles.len()
+    }
+
+    /// Number of black tiles after the exhibit changed every day for 100 days
+    fn step2(&self) -> usize {
+        automaton::evolve(&self.black_tiles, DAYS, |&tile| tile.neighbors(), RULES).len()
+    }
+}
+
+impl AdventState for AdventDay24 {
+    const INPUT_FILES: &'static [&'static str] = &["test.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Lobby Layout");
+
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        Self::parse(&input_content)
+    }
+
+    fn part1(&self) -> PartResult {
+        Ok(self.step1().into())
+    }
+
+    fn part2(&self) -> PartResult {
+        Ok(self.step2().into())
+    }
+}
+
+#[test]
+fn test_paths() {
+    assert_eq!(
+        parse_path("esenee"),
+        Ok(vec![
+            Direction::East,
+            Direction::SouthEast,
+            Direction::NorthEast,
+            Direction::East,
+        ])
+    );
+    assert_eq!(
+        parse_path("nwwswee")
+            .unwrap()
+            .into_iter()
+            .fold(Hex::default(), Hex::neighbor),
+        Hex::default()
+    );
+    assert_eq!(parse_path("en"), Err("invalid direction at 'n'".to_owned()));
+    assert_eq!(
+        parse_path("sx"),
+        Err("invalid direction at 'sx'".to_owned())
+    );
+}
+
+#[test]
+fn test_sample() {
+    let day = AdventDay24::parse(include_str!("../../data/2020/24/test.txt")).unwrap();
+
+    assert_eq!(day.step1(), 10);
+
+    for &(days, expected) in &[(1, 15), (2, 12), (10, 37), (50, 566)] {
+        let tiles = automaton::evolve(&day.black_tiles, days, |&tile| tile.neighbors(), RULES);
+        assert_eq!(tiles.len(), expected, "after {} days", days);
+    }
+
+    assert_eq!(day.step2(), 2208);
+}
@@ -0,0 +1,86 @@
+use crate::advent_adapters::AdventState;
+use crate::advents::{Answer, PartResult};
+use crate::helper::math::{discrete_log, mod_pow};
+
+const MODULUS: u64 = 20201227;
+const SUBJECT_NUMBER: u64 = 7;
+
+pub struct AdventDay25 {
+    card_public_key: u64,
+    door_public_key: u64,
+}
+
+impl AdventDay25 {
+    fn parse(input: &str) -> Result<Self, String> {
+        let keys: Vec<u64> = input
+            .lines()
+            .enumerate()
+            .map(|(line, text)| {
+                text.trim()
+                    .parse()
+                    .map_err(|_| format!("line {}: invalid public key '{}'", line + 1, text))
+            })
+            .collect::<Result<_, _>>()?;
+
+        match keys[..] {
+            [card_public_key, door_public_key] => Ok(Self {
+                card_public_key,
+                door_public_key,
+            }),
+            _ => Err(format!("expected 2 public keys, found {}", keys.len())),
+        }
+    }
+
+    /// Encryption key the card and the door agree on.
+    ///
+    /// Public keys are the subject number raised to the loop size, so the loop size of the card
+    /// is the discrete logarithm of its public key, and the door's public key raised to it is the
+    /// encryption key.
+    fn step1(&self) -> Result<u64, String> {
+        let card_loop_size = discrete_log(SUBJECT_NUMBER, self.card_public_key, MODULUS)
+            .ok_or("the card public key can't be produced from the subject number")?;
+
+        Ok(mod_pow(self.door_public_key, card_loop_size, MODULUS))
+    }
+}
+
+impl AdventState for AdventDay25 {
+    const INPUT_FILES: &'static [&'static str] = &["test.txt", "input.txt"];
+    const TITLE: Option<&'static str> = Some("Combo Breaker");
+    /// The second star comes from having all the other ones
+    const PARTS: u8 = 1;
+
+    fn new(_input_file: &str, input_content: String) -> Result<Self, String> {
+        Self::parse(&input_content)
+    }
+
+    fn part1(&self) -> PartResult {
+        self.step1().map(Answer::from)
+    }
+}
+
+#[test]
+fn test_sample() {
+    let day = AdventDay25::parse(include_str!("../../data/2020/25/test.txt")).unwrap();
+
+    assert_eq!(day.step1(), Ok(14897079));
+
+    // Both sides must agree on the key
+    let swapped = AdventDay25 {
+        card_public_key: day.door_public_key,
+        door_public_key: day.card_public_key,
+    };
+    assert_eq!(swapped.step1(), Ok(14897079));
+}
+
+#[test]
+fn test_invalid_keys() {
+    assert_eq!(
+        AdventDay25::parse("5764801").err(),
+        Some("expected 2 public keys, found 1".to_owned())
+    );
+    assert_eq!(
+        AdventDay25::parse("5764801\nkey").err(),
+        Some("line 2: invalid public key 'key'".to_owned())
+    );
+}
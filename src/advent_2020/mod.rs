@@ -1,35 +1,66 @@
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19_with_nom;
+pub mod day03;
+pub mod day04;
+pub mod day07;
+pub mod day08;
+pub mod day10;
+pub mod day11;
+pub mod day13;
+#[cfg(feature = "heavy-days")]
+pub mod day15;
+pub mod day16;
+#[cfg(feature = "heavy-days")]
+pub mod day17;
+pub mod day18;
+pub mod day19_with_nom;
+#[cfg(feature = "heavy-days")]
+pub mod day20;
+#[cfg(feature = "heavy-days")]
+pub mod day23;
+pub mod day24;
+pub mod day25;
 
-use crate::advent_adapters::StatefulAdvent;
-use crate::advents::{AdventYear, SkippedAdvent};
+use crate::advents::AdventYear;
 
 pub fn get_advent_year() -> AdventYear {
-    AdventYear::new(
-        2020,
-        vec![
-            Box::new(SkippedAdvent::new(1)),
-            Box::new(SkippedAdvent::new(2)),
-            Box::new(SkippedAdvent::new(3)),
-            Box::new(SkippedAdvent::new(4)),
-            Box::new(SkippedAdvent::new(5)),
-            Box::new(SkippedAdvent::new(6)),
-            Box::new(SkippedAdvent::new(7)),
-            Box::new(SkippedAdvent::new(8)),
-            Box::new(SkippedAdvent::new(9)),
-            Box::new(SkippedAdvent::new(10)),
-            Box::new(SkippedAdvent::new(11)),
-            Box::new(SkippedAdvent::new(12)),
-            Box::new(SkippedAdvent::new(13)),
-            Box::new(SkippedAdvent::new(14)),
-            Box::new(day15::AdventDay15),
-            Box::new(day16::AdventDay16),
-            Box::new(StatefulAdvent::<day17::AdventDay17>::new(17)),
-            Box::new(StatefulAdvent::<day18::AdventDay18>::new(18)),
-            Box::new(StatefulAdvent::<day19_with_nom::AdventDay19>::new(19)),
-        ],
-    )
+    advent_year!(2020 {
+        3 => stateful day03::AdventDay03,
+        4 => stateful day04::AdventDay04,
+        7 => stateful day07::AdventDay07,
+        8 => stateful day08::AdventDay08,
+        10 => stateful day10::AdventDay10,
+        11 => stateful day11::AdventDay11,
+        13 => stateful day13::AdventDay13,
+        #[cfg(feature = "heavy-days")]
+        15 => stateful day15::AdventDay15,
+        16 => day16::AdventDay16,
+        #[cfg(feature = "heavy-days")]
+        17 => stateful day17::AdventDay17,
+        18 => stateful day18::AdventDay18,
+        19 => stateful day19_with_nom::AdventDay19,
+        #[cfg(feature = "heavy-days")]
+        20 => stateful day20::AdventDay20,
+        #[cfg(feature = "heavy-days")]
+        23 => stateful day23::AdventDay23,
+        24 => stateful day24::AdventDay24,
+        25 => stateful day25::AdventDay25,
+    })
+}
+
+#[test]
+fn test_heavy_days() {
+    let implemented: Vec<_> = get_advent_year()
+        .iter()
+        .filter(|advent| !advent.skip())
+        .map(|advent| advent.get_index())
+        .collect();
+
+    for day in [15, 17, 20, 23] {
+        assert_eq!(
+            implemented.contains(&day),
+            cfg!(feature = "heavy-days"),
+            "day {}",
+            day
+        );
+    }
+    assert!(implemented.contains(&18));
 }
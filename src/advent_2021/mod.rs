@@ -0,0 +1,5 @@
+use crate::advents::AdventYear;
+
+pub fn get_advent_year() -> AdventYear {
+    advent_year!(2021 {})
+}
@@ -1,16 +1,61 @@
-use crate::advents::Advent;
+use std::panic::resume_unwind;
+use std::time::{Duration, Instant};
 
-pub trait AdventState {
+use crate::advents::{Advent, Answer, DayResult, PartResult, Parts, Phase, Repl, Timings};
+use crate::helper::output;
+
+/// State of a day parsed from one input, solving each part from it. The parts only borrow the
+/// state, so they can run on other threads at the same time, see `INDEPENDENT_PARTS`
+pub trait AdventState: Sized + Sync {
     const INPUT_FILES: &'static [&'static str];
 
-    fn new(input_file: &'static str, input_content: String) -> Self;
+    /// Title of the puzzle, see `Advent::title`
+    const TITLE: Option<&'static str> = None;
+
+    /// Number of parts of the puzzle, see `Advent::part_count`
+    const PARTS: u8 = 2;
+
+    /// Whether the day is slow, see `Advent::slow`
+    const SLOW: bool = false;
+
+    /// Whether the parts can be solved at the same time, see `Advent::solve_concurrently`. Days
+    /// whose parts share state through interior mutability, like a cache filled by the first
+    /// part, solve them one after the other instead
+    const INDEPENDENT_PARTS: bool = true;
+
+    /// Parse the content of one of the input files. An error skips that file with the returned
+    /// message, while the remaining files are still processed.
+    fn new(input_file: &str, input_content: String) -> Result<Self, String>;
+
+    /// Parse an input the day only borrows, like a memory-mapped file, see
+    /// `Advent::solve_borrowed`. Days that don't keep the text of their input implement it and
+    /// have `new` call it, the others parse a copy
+    fn new_borrowed(input_file: &str, input_content: &str) -> Result<Self, String> {
+        Self::new(input_file, input_content.to_owned())
+    }
+
+    fn part1(&self) -> PartResult;
 
-    fn run(self);
+    /// Answer to the second part, if the puzzle has one
+    fn part2(&self) -> PartResult {
+        Err("the puzzle has no part 2".to_owned())
+    }
+
+    /// Interactive loop of the day, see `Advent::repl`
+    fn repl() -> Option<Box<dyn Repl>> {
+        None
+    }
+}
+
+/// Answer of a part from whatever the solution returns, used by `stateful_day!`
+pub fn part_result<T: Into<Answer>, E: ToString>(result: Result<T, E>) -> PartResult {
+    result.map(Into::into).map_err(|err| err.to_string())
 }
 
 pub struct StatefulAdvent<T: AdventState> {
     index: u8,
-    _t: std::marker::PhantomData<*const T>,
+    /// The state is only built while solving, so the adapter is `Send` and `Sync` whatever it is
+    _t: std::marker::PhantomData<fn() -> T>,
 }
 
 impl<T: AdventState> StatefulAdvent<T> {
@@ -27,16 +72,243 @@ impl<T: AdventState> Advent for StatefulAdvent<T> {
         self.index
     }
 
+    fn part_count(&self) -> u8 {
+        T::PARTS
+    }
+
+    fn title(&self) -> Option<&str> {
+        T::TITLE
+    }
+
+    fn slow(&self) -> bool {
+        T::SLOW
+    }
+
     fn get_input_names(&self) -> Vec<String> {
         T::INPUT_FILES.iter().copied().map(String::from).collect()
     }
 
-    fn process_input(&self, data: Vec<String>) {
-        data.into_iter()
-            .zip(T::INPUT_FILES.iter().copied())
-            .for_each(|(input, file_name)| {
-                println!("\nProcessing file {}", file_name);
-                T::new(file_name, input).run();
+    fn repl(&self) -> Option<Box<dyn Repl>> {
+        T::repl()
+    }
+
+    fn solve(&self, input_file: &str, input: String, parts: Parts) -> DayResult {
+        self.solve_timed(input_file, input, parts).0
+    }
+
+    fn solve_timed(&self, input_file: &str, input: String, parts: Parts) -> (DayResult, Timings) {
+        self.solve_parts(|| T::new(input_file, input), parts, false)
+    }
+
+    fn solve_concurrently(
+        &self,
+        input_file: &str,
+        input: String,
+        parts: Parts,
+    ) -> (DayResult, Timings) {
+        self.solve_parts(|| T::new(input_file, input), parts, T::INDEPENDENT_PARTS)
+    }
+
+    fn solve_borrowed(
+        &self,
+        input_file: &str,
+        input: &str,
+        parts: Parts,
+        concurrently: bool,
+    ) -> (DayResult, Timings) {
+        self.solve_parts(
+            || T::new_borrowed(input_file, input),
+            parts,
+            concurrently && T::INDEPENDENT_PARTS,
+        )
+    }
+}
+
+impl<T: AdventState> StatefulAdvent<T> {
+    /// Parse the input with `parse` and solve the selected parts, each on a thread of its own if
+    /// `concurrently`
+    fn solve_parts(
+        &self,
+        parse: impl FnOnce() -> Result<T, String>,
+        parts: Parts,
+        concurrently: bool,
+    ) -> (DayResult, Timings) {
+        let start = Instant::now();
+        let state = parse();
+        let mut timings = vec![(Phase::Parse, start.elapsed())];
+
+        let state = match state {
+            Ok(state) => state,
+            Err(err) => return (Err(err), timings),
+        };
+
+        let selected: Vec<u8> = (1..=T::PARTS)
+            .filter(|&part| parts.includes(part))
+            .collect();
+        let solve_part = |part: u8| -> (PartResult, Duration) {
+            let start = Instant::now();
+            let result = match part {
+                1 => state.part1(),
+                _ => state.part2(),
+            };
+            (result, start.elapsed())
+        };
+
+        let solved: Vec<_> = if concurrently && selected.len() > 1 {
+            // What each part prints is kept apart and printed in the order of the parts
+            let solved: Vec<_> = std::thread::scope(|scope| {
+                let workers: Vec<_> = selected
+                    .iter()
+                    .map(|&part| scope.spawn(move || output::capture(|| solve_part(part))))
+                    .collect();
+                workers
+                    .into_iter()
+                    .map(|worker| worker.join().unwrap_or_else(|panic| resume_unwind(panic)))
+                    .collect()
+            });
+            solved
+                .into_iter()
+                .map(|(solved, printed)| {
+                    for line in printed.lines() {
+                        output::line(format_args!("{}", line));
+                    }
+                    solved
+                })
+                .collect()
+        } else {
+            selected.iter().map(|&part| solve_part(part)).collect()
+        };
+
+        let results = selected
+            .into_iter()
+            .zip(solved)
+            .map(|(part, (result, elapsed))| {
+                timings.push((Phase::Part(part), elapsed));
+                (part, result)
             })
+            .collect();
+
+        (Ok(results), timings)
+    }
+}
+
+#[cfg(test)]
+static SOLVED_ON: std::sync::Mutex<Vec<(u8, std::thread::ThreadId)>> =
+    std::sync::Mutex::new(Vec::new());
+
+#[cfg(test)]
+struct Recorded<const INDEPENDENT: bool>;
+
+#[cfg(test)]
+impl<const INDEPENDENT: bool> Recorded<INDEPENDENT> {
+    fn record(part: u8) -> PartResult {
+        SOLVED_ON
+            .lock()
+            .unwrap()
+            .push((part, std::thread::current().id()));
+        crate::print_info!("part {}", part);
+        Ok(part.into())
+    }
+}
+
+#[cfg(test)]
+impl<const INDEPENDENT: bool> AdventState for Recorded<INDEPENDENT> {
+    const INPUT_FILES: &'static [&'static str] = &["input.txt"];
+    const INDEPENDENT_PARTS: bool = INDEPENDENT;
+
+    fn new(_input_file: &str, _input_content: String) -> Result<Self, String> {
+        Ok(Self)
+    }
+
+    fn part1(&self) -> PartResult {
+        Self::record(1)
+    }
+
+    fn part2(&self) -> PartResult {
+        Self::record(2)
+    }
+}
+
+#[test]
+fn test_solve_concurrently() {
+    let solve = |advent: &dyn Advent, parts| {
+        SOLVED_ON.lock().unwrap().clear();
+        let ((results, timings), printed) =
+            output::capture(|| advent.solve_concurrently("input.txt", String::new(), parts));
+        let solved_on = std::mem::take(&mut *SOLVED_ON.lock().unwrap());
+        (results.unwrap(), timings.len(), printed, solved_on)
+    };
+    let here = std::thread::current().id();
+
+    let (results, timings, printed, solved_on) =
+        solve(&StatefulAdvent::<Recorded<true>>::new(1), Parts::All);
+    assert_eq!(results, [(1, Ok(1u8.into())), (2, Ok(2u8.into()))]);
+    assert_eq!(timings, 3);
+    // Printed in the order of the parts, whichever finished first
+    assert_eq!(printed, "part 1\npart 2\n");
+    assert_eq!(solved_on.len(), 2);
+    assert!(solved_on.iter().all(|&(_, thread)| thread != here));
+    assert_ne!(solved_on[0].1, solved_on[1].1);
+
+    // A single part has nothing to run alongside
+    let (results, _, _, solved_on) =
+        solve(&StatefulAdvent::<Recorded<true>>::new(1), Parts::Only(2));
+    assert_eq!(results, [(2, Ok(2u8.into()))]);
+    assert_eq!(solved_on, [(2, here)]);
+
+    let (results, _, printed, solved_on) =
+        solve(&StatefulAdvent::<Recorded<false>>::new(1), Parts::All);
+    assert_eq!(results.len(), 2);
+    assert_eq!(printed, "part 1\npart 2\n");
+    assert_eq!(solved_on, [(1, here), (2, here)]);
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_concurrent_answers() {
+    use crate::inputs::{FsProvider, InputProvider};
+
+    let fixtures =
+        FsProvider::new(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"));
+    let advent_years = crate::advent_years();
+    let days = advent_years
+        .iter()
+        .filter(|advent_year| advent_year.get_year() == 2020)
+        .flat_map(|advent_year| advent_year.iter())
+        .filter(|advent| !advent.skip() && !advent.slow());
+
+    // The parts give the same answers at the same time as one after the other, and from a
+    // borrowed input as from an owned one
+    let mut compared = 0;
+    for advent in days {
+        for name in advent.get_input_names() {
+            let input = match fixtures.fetch(2020, advent.get_index(), &name) {
+                Ok(input) => input,
+                Err(_) => continue,
+            };
+            let expected = advent.solve_timed(&name, input.clone(), Parts::All).0;
+            assert_eq!(
+                advent
+                    .solve_concurrently(&name, input.clone(), Parts::All)
+                    .0,
+                expected,
+                "{} of day {}",
+                name,
+                advent.get_index()
+            );
+            for concurrently in [false, true] {
+                assert_eq!(
+                    advent
+                        .solve_borrowed(&name, &input, Parts::All, concurrently)
+                        .0,
+                    expected,
+                    "{} of day {} borrowed",
+                    name,
+                    advent.get_index()
+                );
+            }
+            compared += 1;
+        }
     }
+    assert!(compared >= 10, "only {} fixtures found", compared);
 }
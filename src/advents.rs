@@ -1,3 +1,7 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::{Duration, Instant};
+
 pub struct AdventYear {
     year: u16,
     advents: Vec<Box<dyn Advent>>,
@@ -21,20 +25,218 @@ impl AdventYear {
     }
 }
 
-pub trait Advent {
+/// Parts of a puzzle to run
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Parts {
+    All,
+    Only(u8),
+}
+
+impl Parts {
+    pub fn includes(self, part: u8) -> bool {
+        match self {
+            Parts::All => true,
+            Parts::Only(only) => only == part,
+        }
+    }
+}
+
+/// Answer to a part of a puzzle, kept in the type the day computed it with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Unsigned(u64),
+    Signed(i64),
+    /// Integers too large for 64 bits
+    Big(u128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Unsigned(answer) => answer.fmt(f),
+            Answer::Signed(answer) => answer.fmt(f),
+            Answer::Big(answer) => answer.fmt(f),
+            Answer::Text(answer) => answer.fmt(f),
+        }
+    }
+}
+
+macro_rules! answer_from {
+    ($variant:ident($inner:ty): $($source:ty),+) => {
+        $(
+            impl From<$source> for Answer {
+                fn from(answer: $source) -> Self {
+                    Answer::$variant(answer as $inner)
+                }
+            }
+        )+
+    };
+}
+
+answer_from!(Unsigned(u64): u8, u16, u32, u64, usize);
+answer_from!(Signed(i64): i8, i16, i32, i64, isize);
+answer_from!(Big(u128): u128);
+
+/// Wide signed results take the narrowest variant holding them, the sign of those beyond 64 bits
+/// is only kept as text
+impl From<i128> for Answer {
+    fn from(answer: i128) -> Self {
+        if let Ok(answer) = i64::try_from(answer) {
+            Answer::Signed(answer)
+        } else if let Ok(answer) = u128::try_from(answer) {
+            Answer::Big(answer)
+        } else {
+            Answer::Text(answer.to_string())
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(answer: String) -> Self {
+        Answer::Text(answer)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(answer: &str) -> Self {
+        Answer::Text(answer.to_owned())
+    }
+}
+
+/// Answers equal the strings they are displayed as, whatever their variant, so they can be
+/// compared with expected or recorded answers
+impl PartialEq<str> for Answer {
+    fn eq(&self, other: &str) -> bool {
+        match self {
+            Answer::Text(answer) => answer == other,
+            _ => {
+                let displayed = self.to_string();
+                displayed == other
+            }
+        }
+    }
+}
+
+impl PartialEq<&str> for Answer {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+/// Answer to one part of a puzzle, or why it couldn't be computed
+pub type PartResult = Result<Answer, String>;
+
+/// Results of the selected parts of a day for one input, or why the input couldn't be parsed
+pub type DayResult = Result<Vec<(u8, PartResult)>, String>;
+
+/// Step of solving an input that is timed on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Reading the input file, measured by the runner
+    Read,
+    Parse,
+    Part(u8),
+    /// Parsing and the parts together, for days that don't separate them
+    Solve,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Read => f.write_str("read"),
+            Phase::Parse => f.write_str("parse"),
+            Phase::Part(part) => write!(f, "part {}", part),
+            Phase::Solve => f.write_str("solve"),
+        }
+    }
+}
+
+/// How long each phase of solving an input took, in the order they ran
+pub type Timings = Vec<(Phase, Duration)>;
+
+/// Implementation of a day. Days can be shared with and sent to other threads, so the registry
+/// can be solved in parallel
+pub trait Advent: Send + Sync {
     fn get_index(&self) -> u8;
 
+    /// Number of parts of the puzzle, the last day of each year only has one
+    fn part_count(&self) -> u8 {
+        2
+    }
+
+    /// Title of the puzzle, shown next to the day in the prompts
+    fn title(&self) -> Option<&str> {
+        None
+    }
+
     fn skip(&self) -> bool {
         false
     }
 
+    /// Whether the day takes long enough to be left out when running every day of a year,
+    /// unless it is selected or `RunSettings::include_slow` is set
+    fn slow(&self) -> bool {
+        false
+    }
+
     fn get_input_names(&self) -> Vec<String> {
         vec!["input.txt".to_owned()]
     }
 
-    /// Process the given data. The data is the content of the files provided by
-    /// `Advent::get_input_names`
-    fn process_input(&self, data: Vec<String>);
+    /// Solve the selected parts for the content of one input file
+    fn solve(&self, input_file: &str, input: String, parts: Parts) -> DayResult;
+
+    /// Read-eval-print loop over the engine of the day, for the days that double as a tool
+    fn repl(&self) -> Option<Box<dyn Repl>> {
+        None
+    }
+
+    /// Same as `solve`, also reporting how long each phase took. Days that only implement
+    /// `solve` report a single `Phase::Solve`
+    fn solve_timed(&self, input_file: &str, input: String, parts: Parts) -> (DayResult, Timings) {
+        let start = Instant::now();
+        let result = self.solve(input_file, input, parts);
+        (result, vec![(Phase::Solve, start.elapsed())])
+    }
+
+    /// Same as `solve_timed`, solving the selected parts at the same time on threads of their
+    /// own when they don't depend on each other. Days that don't separate their parts solve them
+    /// as `solve_timed` does
+    fn solve_concurrently(
+        &self,
+        input_file: &str,
+        input: String,
+        parts: Parts,
+    ) -> (DayResult, Timings) {
+        self.solve_timed(input_file, input, parts)
+    }
+
+    /// Same as `solve_timed`, or `solve_concurrently` when `concurrently` is set, for an input the
+    /// day only borrows, like a memory-mapped file. Days that can't parse a `&str` solve a copy
+    fn solve_borrowed(
+        &self,
+        input_file: &str,
+        input: &str,
+        parts: Parts,
+        concurrently: bool,
+    ) -> (DayResult, Timings) {
+        if concurrently {
+            self.solve_concurrently(input_file, input.to_owned(), parts)
+        } else {
+            self.solve_timed(input_file, input.to_owned(), parts)
+        }
+    }
+}
+
+/// Interactive loop of a day, see `Advent::repl`. The runner reads the lines and prints the
+/// replies until the end of its input
+pub trait Repl {
+    /// Prompt shown before each line, like `mode 2> `
+    fn prompt(&self) -> String;
+
+    /// Handle one line typed by the user, returning what to print in reply
+    fn eval(&mut self, line: &str) -> Option<String>;
 }
 
 pub struct SkippedAdvent(u8);
@@ -58,7 +260,64 @@ impl Advent for SkippedAdvent {
         Vec::new()
     }
 
-    fn process_input(&self, _data: Vec<String>) {
-        unimplemented!()
+    fn solve(&self, _input_file: &str, _input: String, _parts: Parts) -> DayResult {
+        Err(format!("day {} is not implemented", self.0))
     }
 }
+
+#[test]
+fn test_parts() {
+    assert!(Parts::All.includes(1));
+    assert!(Parts::All.includes(2));
+    assert!(Parts::Only(2).includes(2));
+    assert!(!Parts::Only(2).includes(1));
+}
+
+#[test]
+fn test_answers() {
+    assert_eq!(Answer::from(1068781u64), "1068781");
+    assert_eq!(Answer::from(1068781usize), Answer::Unsigned(1068781));
+    assert_eq!(Answer::from(-3i32), Answer::Signed(-3));
+    assert_eq!(Answer::from("67384529"), "67384529");
+    assert_ne!(Answer::from(7u8), "07");
+
+    assert_eq!(
+        Answer::from(283582817678281i128),
+        Answer::Signed(283582817678281)
+    );
+    assert_eq!(Answer::from(i128::from(u64::MAX) + 1), Answer::Big(1 << 64));
+    assert_eq!(Answer::from(i128::MIN), i128::MIN.to_string().as_str());
+
+    assert_eq!(Answer::Big(u128::MAX).to_string(), u128::MAX.to_string());
+}
+
+/// Only compiles while the days can be shared with other threads
+fn assert_send_sync<T: Send + Sync + ?Sized>() {}
+
+const _: fn() = assert_send_sync::<dyn Advent>;
+const _: fn() = assert_send_sync::<AdventYear>;
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_send_sync() {
+    let advent_years = crate::advent_years();
+
+    // Every day is solved on worker threads sharing the registry
+    let solved: Vec<_> = std::thread::scope(|scope| {
+        let workers: Vec<_> = advent_years
+            .iter()
+            .flat_map(|advent_year| advent_year.iter())
+            .filter(|advent| advent.get_index() == 18 && !advent.skip())
+            .map(|advent| {
+                scope.spawn(move || advent.solve("test", "1 + 2 * 3".to_owned(), Parts::All))
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    assert_eq!(solved.len(), 1);
+    assert!(solved[0].is_ok());
+}
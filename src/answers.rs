@@ -0,0 +1,195 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::advents::Answer;
+#[cfg(test)]
+use crate::helper::temp::TempDir;
+
+/// Answer computed in a previous run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedAnswer {
+    pub day: u8,
+    pub part: u8,
+    /// Name of the input file the answer was computed from
+    pub input: String,
+    pub answer: String,
+}
+
+/// How a new answer compares to the recorded one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Check {
+    /// There was no answer recorded, the new one is now
+    New,
+    Same,
+    /// The new answer differs from the recorded one
+    Regression {
+        recorded: String,
+    },
+}
+
+/// Answers of the previous runs of a year, stored as JSON so refactors can be checked against
+/// them
+#[derive(Debug, Default)]
+pub struct AnswerStore {
+    /// File the answers are saved to, nothing is saved when `None`
+    path: Option<PathBuf>,
+    answers: Vec<RecordedAnswer>,
+    changed: bool,
+}
+
+impl AnswerStore {
+    /// Store kept in memory only
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Load the answers saved in a file, starting empty if it doesn't exist yet
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+
+        let answers = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|err| format!("invalid answers in {}: {}", path.display(), err))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(format!("could not read {}: {}", path.display(), err)),
+        };
+
+        Ok(Self {
+            path: Some(path),
+            answers,
+            changed: false,
+        })
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Compare an answer with the recorded one, recording it if there was none. With `update`,
+    /// a different answer replaces the recorded one, but is still reported as a regression.
+    pub fn check(
+        &mut self,
+        day: u8,
+        part: u8,
+        input: &str,
+        answer: &Answer,
+        update: bool,
+    ) -> Check {
+        let recorded = self.answers.iter_mut().find(|recorded| {
+            recorded.day == day && recorded.part == part && recorded.input == input
+        });
+
+        match recorded {
+            None => {
+                self.answers.push(RecordedAnswer {
+                    day,
+                    part,
+                    input: input.to_owned(),
+                    answer: answer.to_string(),
+                });
+                self.changed = true;
+                Check::New
+            }
+            Some(recorded) if *answer == *recorded.answer => Check::Same,
+            Some(recorded) => {
+                let previous = if update {
+                    self.changed = true;
+                    std::mem::replace(&mut recorded.answer, answer.to_string())
+                } else {
+                    recorded.answer.clone()
+                };
+
+                Check::Regression { recorded: previous }
+            }
+        }
+    }
+
+    /// Save the answers if any changed, sorted by day, part and input file
+    pub fn save(&mut self) -> Result<(), String> {
+        let path = match &self.path {
+            Some(path) if self.changed => path,
+            _ => return Ok(()),
+        };
+
+        self.answers
+            .sort_by(|a, b| (a.day, a.part, &a.input).cmp(&(b.day, b.part, &b.input)));
+
+        let content = serde_json::to_string_pretty(&self.answers).expect("answers are valid JSON");
+        std::fs::write(path, content + "\n")
+            .map_err(|err| format!("could not write {}: {}", path.display(), err))?;
+
+        self.changed = false;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_check() {
+    let mut store = AnswerStore::in_memory();
+
+    assert_eq!(
+        store.check(1, 1, "input.txt", &Answer::Unsigned(42), false),
+        Check::New
+    );
+    assert_eq!(
+        store.check(1, 1, "input.txt", &Answer::Unsigned(42), false),
+        Check::Same
+    );
+    assert_eq!(
+        store.check(1, 1, "test.txt", &Answer::Unsigned(7), false),
+        Check::New
+    );
+    assert_eq!(
+        store.check(1, 2, "input.txt", &Answer::Unsigned(43), false),
+        Check::New
+    );
+
+    assert_eq!(
+        store.check(1, 1, "input.txt", &Answer::Unsigned(41), false),
+        Check::Regression {
+            recorded: "42".to_owned()
+        }
+    );
+    assert_eq!(
+        store.check(1, 1, "input.txt", &Answer::Unsigned(41), false),
+        Check::Regression {
+            recorded: "42".to_owned()
+        },
+        "regressions are kept without update"
+    );
+
+    assert_eq!(
+        store.check(1, 1, "input.txt", &Answer::Unsigned(41), true),
+        Check::Regression {
+            recorded: "42".to_owned()
+        }
+    );
+    assert_eq!(
+        store.check(1, 1, "input.txt", &Answer::Unsigned(41), false),
+        Check::Same
+    );
+}
+
+#[test]
+fn test_save_and_load() {
+    let dir = TempDir::new("answers");
+    let path = dir.join("answers.json");
+
+    let mut store = AnswerStore::load(&path).unwrap();
+    store.check(2, 1, "input.txt", &Answer::Unsigned(5), false);
+    store.check(1, 2, "input.txt", &Answer::Unsigned(4), false);
+    store.check(1, 1, "input.txt", &Answer::Unsigned(3), false);
+    store.save().unwrap();
+
+    let loaded = AnswerStore::load(&path).unwrap();
+    let keys: Vec<_> = loaded
+        .answers
+        .iter()
+        .map(|answer| (answer.day, answer.part, answer.answer.as_str()))
+        .collect();
+    assert_eq!(keys, [(1, 1, "3"), (1, 2, "4"), (2, 1, "5")]);
+
+    std::fs::write(&path, "not json").unwrap();
+    assert!(AnswerStore::load(&path).is_err());
+}
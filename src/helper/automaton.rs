@@ -0,0 +1,187 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Neighbor counts that make a cell active in the next generation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// Counts that turn an inactive cell active
+    pub birth: &'static [usize],
+    /// Counts that keep an active cell active, 0 keeping the cells without active neighbors
+    pub survival: &'static [usize],
+}
+
+impl Rules {
+    pub fn next_state(&self, active: bool, active_neighbors: usize) -> bool {
+        if active {
+            self.survival.contains(&active_neighbors)
+        } else {
+            self.birth.contains(&active_neighbors)
+        }
+    }
+}
+
+impl Default for Rules {
+    /// Conway's rules, B3/S23
+    fn default() -> Self {
+        Self {
+            birth: &[3],
+            survival: &[2, 3],
+        }
+    }
+}
+
+/// Compute the next generation of a cellular automaton on an unbounded space, storing only the
+/// active cells
+pub fn cycle<C, N, I>(cells: &HashSet<C>, neighbors: N, rules: Rules) -> HashSet<C>
+where
+    C: Hash + Eq + Clone + Send + Sync,
+    N: Fn(&C) -> I + Sync,
+    I: Iterator<Item = C>,
+{
+    let mut next_generation = HashSet::new();
+    cycle_into(
+        cells,
+        &neighbors,
+        rules,
+        &mut HashMap::new(),
+        &mut next_generation,
+    );
+    next_generation
+}
+
+/// Same as [`cycle`], writing into `next_generation` and counting in `counts` so that both can
+/// be reused from one cycle to the next. Each active cell adds one to the count of each of its
+/// neighbors, listing them once, and only the cells with a count or already active can be
+/// active in the next generation
+fn cycle_into<C, N, I>(
+    cells: &HashSet<C>,
+    neighbors: &N,
+    rules: Rules,
+    counts: &mut HashMap<C, u8>,
+    next_generation: &mut HashSet<C>,
+) where
+    C: Hash + Eq + Clone + Send + Sync,
+    N: Fn(&C) -> I + Sync,
+    I: Iterator<Item = C>,
+{
+    counts.clear();
+    for cell in cells {
+        for neighbor in neighbors(cell) {
+            let count = counts.entry(neighbor).or_insert(0);
+            // No rule goes that high, the counts only need to stay above the ones they have
+            *count = count.saturating_add(1);
+        }
+    }
+
+    let is_active =
+        |&(cell, &count): &(&C, &u8)| rules.next_state(cells.contains(cell), usize::from(count));
+
+    next_generation.clear();
+
+    #[cfg(feature = "parallel")]
+    next_generation.par_extend(
+        counts
+            .par_iter()
+            .filter(is_active)
+            .map(|(cell, _)| cell.clone()),
+    );
+
+    #[cfg(not(feature = "parallel"))]
+    next_generation.extend(
+        counts
+            .iter()
+            .filter(is_active)
+            .map(|(cell, _)| cell.clone()),
+    );
+
+    // Active cells without any active neighbor have no count, they are checked on their own
+    if rules.next_state(true, 0) {
+        next_generation.extend(
+            cells
+                .iter()
+                .filter(|cell| !counts.contains_key(cell))
+                .cloned(),
+        );
+    }
+}
+
+/// Run `cycles` generations of a cellular automaton, see [`cycle`]
+pub fn evolve<C, N, I>(cells: &HashSet<C>, cycles: usize, neighbors: N, rules: Rules) -> HashSet<C>
+where
+    C: Hash + Eq + Clone + Send + Sync,
+    N: Fn(&C) -> I + Sync,
+    I: Iterator<Item = C>,
+{
+    let mut cells = cells.clone();
+    let mut counts = HashMap::new();
+    let mut next_generation = HashSet::new();
+
+    for _ in 0..cycles {
+        cycle_into(&cells, &neighbors, rules, &mut counts, &mut next_generation);
+        std::mem::swap(&mut cells, &mut next_generation);
+    }
+
+    cells
+}
+
+#[test]
+fn test_blinker() {
+    let neighbors = |&(x, y): &(i32, i32)| {
+        (-1..=1)
+            .flat_map(move |dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+            .filter(move |&cell| cell != (x, y))
+    };
+
+    let horizontal: HashSet<_> = [(-1, 0), (0, 0), (1, 0)].iter().copied().collect();
+    let vertical: HashSet<_> = [(0, -1), (0, 0), (0, 1)].iter().copied().collect();
+
+    assert_eq!(cycle(&horizontal, neighbors, Rules::default()), vertical);
+    assert_eq!(
+        evolve(&horizontal, 2, neighbors, Rules::default()),
+        horizontal
+    );
+    assert_eq!(
+        evolve(&horizontal, 0, neighbors, Rules::default()),
+        horizontal
+    );
+}
+
+#[test]
+fn test_lonely_survivors() {
+    let neighbors = |&x: &i32| std::iter::once(x - 1).chain(std::iter::once(x + 1));
+    let rules = Rules {
+        birth: &[2],
+        survival: &[0],
+    };
+
+    // The cells without neighbors survive, the ones next to each other die, and the cell between
+    // two others is born
+    let cells: HashSet<_> = [0, 1, 5, 7].iter().copied().collect();
+    let expected: HashSet<_> = [5, 6, 7].iter().copied().collect();
+    assert_eq!(cycle(&cells, neighbors, rules), expected);
+}
+
+#[test]
+fn test_isolated_survivors() {
+    let neighbors = |&(x, y): &(i32, i32)| {
+        (-1..=1)
+            .flat_map(move |dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+            .filter(move |&cell| cell != (x, y))
+    };
+    let rules = Rules {
+        birth: &[],
+        survival: &[0],
+    };
+
+    // Far apart cells never have an active neighbor, they stay as long as survival contains 0,
+    // and the pair, each the neighbor of the other, dies
+    let isolated: HashSet<_> = [(0, 0), (10, 10), (-10, 5)].iter().copied().collect();
+    let mut cells = isolated.clone();
+    cells.extend([(20, 0), (21, 0)].iter().copied());
+    assert_eq!(cycle(&cells, neighbors, rules), isolated);
+    assert_eq!(evolve(&cells, 5, neighbors, rules), isolated);
+    assert!(evolve(&cells, 5, neighbors, Rules::default()).is_empty());
+}
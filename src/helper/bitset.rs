@@ -0,0 +1,303 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+
+/// Set of small integers below `64 * WORDS`, stored as bits
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitSet<const WORDS: usize> {
+    words: [u64; WORDS],
+}
+
+/// Set of integers below 64
+pub type BitSet64 = BitSet<1>;
+
+impl<const WORDS: usize> BitSet<WORDS> {
+    /// Number of values the set can hold, values go from 0 to `CAPACITY - 1`
+    pub const CAPACITY: usize = 64 * WORDS;
+
+    #[inline]
+    pub fn new() -> Self {
+        Self { words: [0; WORDS] }
+    }
+
+    /// Set of every value below `len`
+    #[inline]
+    pub fn full(len: usize) -> Self {
+        assert!(
+            len <= Self::CAPACITY,
+            "{} values do not fit in the set",
+            len
+        );
+
+        let mut set = Self::new();
+        for (idx, word) in set.words.iter_mut().enumerate() {
+            let bits = len.saturating_sub(idx * 64).min(64);
+            *word = if bits == 64 { !0 } else { (1 << bits) - 1 };
+        }
+        set
+    }
+
+    #[inline]
+    fn position(value: usize) -> (usize, u64) {
+        assert!(
+            value < Self::CAPACITY,
+            "{} does not fit in a set of capacity {}",
+            value,
+            Self::CAPACITY
+        );
+
+        (value / 64, 1 << (value % 64))
+    }
+
+    /// Add a value, returning whether it was missing
+    #[inline]
+    pub fn insert(&mut self, value: usize) -> bool {
+        let (word, bit) = Self::position(value);
+        let missing = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        missing
+    }
+
+    /// Remove a value, returning whether it was present
+    #[inline]
+    pub fn remove(&mut self, value: usize) -> bool {
+        let (word, bit) = Self::position(value);
+        let present = self.words[word] & bit != 0;
+        self.words[word] &= !bit;
+        present
+    }
+
+    #[inline]
+    pub fn contains(&self, value: usize) -> bool {
+        value < Self::CAPACITY && {
+            let (word, bit) = Self::position(value);
+            self.words[word] & bit != 0
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Whether the set holds exactly one value
+    #[inline]
+    pub fn is_single(&self) -> bool {
+        self.len() == 1
+    }
+
+    /// Values of the set in increasing order
+    #[inline]
+    pub fn iter(&self) -> Iter<WORDS> {
+        Iter {
+            words: self.words,
+            word: 0,
+        }
+    }
+}
+
+impl<const WORDS: usize> Default for BitSet<WORDS> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WORDS: usize> fmt::Debug for BitSet<WORDS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the values of a [`BitSet`]
+pub struct Iter<const WORDS: usize> {
+    words: [u64; WORDS],
+    word: usize,
+}
+
+impl<const WORDS: usize> Iterator for Iter<WORDS> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        while self.word < WORDS {
+            let bits = &mut self.words[self.word];
+            if *bits != 0 {
+                let bit = bits.trailing_zeros() as usize;
+                // Clear the lowest bit
+                *bits &= *bits - 1;
+                return Some(self.word * 64 + bit);
+            }
+            self.word += 1;
+        }
+
+        None
+    }
+}
+
+impl<const WORDS: usize> IntoIterator for BitSet<WORDS> {
+    type Item = usize;
+    type IntoIter = Iter<WORDS>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<const WORDS: usize> IntoIterator for &BitSet<WORDS> {
+    type Item = usize;
+    type IntoIter = Iter<WORDS>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<const WORDS: usize> FromIterator<usize> for BitSet<WORDS> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<const WORDS: usize> Extend<usize> for BitSet<WORDS> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = usize>>(&mut self, iter: T) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+macro_rules! bitset_op {
+    ($op:ident, $method:ident, $assign_op:ident, $assign_method:ident,
+        |$a:ident, $b:ident| $combine:expr) => {
+        impl<const WORDS: usize> $assign_op for BitSet<WORDS> {
+            #[inline]
+            fn $assign_method(&mut self, other: Self) {
+                for ($a, &$b) in self.words.iter_mut().zip(&other.words) {
+                    *$a = $combine;
+                }
+            }
+        }
+
+        impl<const WORDS: usize> $op for BitSet<WORDS> {
+            type Output = Self;
+
+            #[inline]
+            fn $method(mut self, other: Self) -> Self {
+                self.$assign_method(other);
+                self
+            }
+        }
+    };
+}
+
+bitset_op!(BitAnd, bitand, BitAndAssign, bitand_assign, |a, b| *a & b);
+bitset_op!(BitOr, bitor, BitOrAssign, bitor_assign, |a, b| *a | b);
+bitset_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, |a, b| *a ^ b);
+bitset_op!(Sub, sub, SubAssign, sub_assign, |a, b| *a & !b);
+
+#[test]
+fn test_bitset() {
+    let mut set = BitSet64::new();
+    assert!(set.is_empty());
+    assert!(set.insert(3));
+    assert!(!set.insert(3));
+    assert!(set.is_single());
+    assert!(set.insert(63));
+    assert_eq!(set.iter().collect::<Vec<_>>(), [3, 63]);
+    assert!(!set.contains(64));
+    assert!(set.remove(3));
+    assert!(!set.remove(3));
+    assert_eq!(format!("{:?}", set), "{63}");
+
+    assert_eq!(BitSet64::full(0), BitSet64::new());
+    assert_eq!(BitSet64::full(64).len(), 64);
+    assert_eq!(
+        BitSet::<2>::full(70).iter().collect::<Vec<_>>(),
+        (0..70).collect::<Vec<_>>()
+    );
+
+    let a: BitSet<2> = [1, 2, 100].iter().copied().collect();
+    let b: BitSet<2> = [2, 3, 100].iter().copied().collect();
+    assert_eq!((a & b).iter().collect::<Vec<_>>(), [2, 100]);
+    assert_eq!((a | b).iter().collect::<Vec<_>>(), [1, 2, 3, 100]);
+    assert_eq!((a ^ b).iter().collect::<Vec<_>>(), [1, 3]);
+    assert_eq!((a - b).iter().collect::<Vec<_>>(), [1]);
+}
+
+#[test]
+fn test_bitset_against_hash_set() {
+    use std::collections::{BTreeSet, HashSet};
+
+    // xorshift, to get the same operations on every run without a dependency
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut random = move |bound: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound) as usize
+    };
+
+    let mut sets = [BitSet::<3>::new(), BitSet::new()];
+    let mut expected = [HashSet::new(), HashSet::new()];
+
+    for _ in 0..10_000 {
+        let idx = random(2);
+        let value = random(BitSet::<3>::CAPACITY as u64);
+
+        match random(6) {
+            0 | 1 => assert_eq!(sets[idx].insert(value), expected[idx].insert(value)),
+            2 => assert_eq!(sets[idx].remove(value), expected[idx].remove(&value)),
+            3 => {
+                let other = sets[1 - idx];
+                let (a, b) = (&expected[idx], &expected[1 - idx]);
+                let result: HashSet<_> = match random(4) {
+                    0 => {
+                        sets[idx] &= other;
+                        a & b
+                    }
+                    1 => {
+                        sets[idx] |= other;
+                        a | b
+                    }
+                    2 => {
+                        sets[idx] ^= other;
+                        a ^ b
+                    }
+                    _ => {
+                        sets[idx] -= other;
+                        a - b
+                    }
+                };
+                expected[idx] = result;
+            }
+            _ => assert_eq!(sets[idx].contains(value), expected[idx].contains(&value)),
+        }
+
+        assert_eq!(sets[idx].len(), expected[idx].len());
+        assert_eq!(sets[idx].is_single(), expected[idx].len() == 1);
+        assert_eq!(
+            sets[idx].iter().collect::<Vec<_>>(),
+            expected[idx]
+                .iter()
+                .copied()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Where an answer differs from the one expected, to point at it when they don't match
+
+use std::ffi::OsString;
+
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Above this many cells in the table of the longest common subsequence, the middles of the two
+/// answers are reported as changed as a whole
+const MAX_TABLE_CELLS: usize = 1 << 20;
+
+/// Part of the diff between the expected answer and the one computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change<'a> {
+    /// In both answers
+    Same(&'a str),
+    /// Only in the expected answer
+    Removed(&'a str),
+    /// Only in the computed answer
+    Added(&'a str),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Same,
+    Removed,
+    Added,
+}
+
+/// Whether colors can be written to stderr, following https://no-color.org
+pub fn use_colors(no_color: Option<OsString>, terminal: bool) -> bool {
+    terminal && no_color.is_none_or(|value| value.is_empty())
+}
+
+/// Length in bytes of the longest common prefix of two strings, which ends at a character
+/// boundary of both
+pub fn common_prefix(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|&((_, x), y)| x != y)
+        .map_or_else(|| a.len().min(b.len()), |((index, _), _)| index)
+}
+
+/// Length in bytes of the longest common suffix of two strings
+fn common_suffix(a: &str, b: &str) -> usize {
+    a.chars()
+        .rev()
+        .zip(b.chars().rev())
+        .take_while(|(x, y)| x == y)
+        .map(|(x, _)| x.len_utf8())
+        .sum()
+}
+
+/// Character-level diff turning `expected` into `got`, from their longest common subsequence
+pub fn diff_chars<'a>(expected: &'a str, got: &'a str) -> Vec<Change<'a>> {
+    let prefix = common_prefix(expected, got);
+    let suffix = common_suffix(&expected[prefix..], &got[prefix..]);
+    let e: Vec<_> = expected[prefix..expected.len() - suffix]
+        .char_indices()
+        .map(|(index, c)| (prefix + index, c))
+        .collect();
+    let g: Vec<_> = got[prefix..got.len() - suffix]
+        .char_indices()
+        .map(|(index, c)| (prefix + index, c))
+        .collect();
+
+    // Byte ranges of the changes, in `expected` except for the added ones
+    let mut ranges: Vec<(Kind, usize, usize)> = Vec::new();
+    let mut push = |kind: Kind, start: usize, end: usize| match ranges.last_mut() {
+        Some((last, _, last_end)) if *last == kind && *last_end == start => *last_end = end,
+        _ => ranges.push((kind, start, end)),
+    };
+    let char_end = |&(index, c): &(usize, char)| index + c.len_utf8();
+
+    push(Kind::Same, 0, prefix);
+    if (e.len() + 1) * (g.len() + 1) > MAX_TABLE_CELLS {
+        push(Kind::Removed, prefix, expected.len() - suffix);
+        push(Kind::Added, prefix, got.len() - suffix);
+    } else {
+        // Length of the longest common subsequence of e[i..] and g[j..]
+        let width = g.len() + 1;
+        let mut lengths = vec![0u32; (e.len() + 1) * width];
+        for i in (0..e.len()).rev() {
+            for j in (0..g.len()).rev() {
+                lengths[i * width + j] = if e[i].1 == g[j].1 {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < e.len() || j < g.len() {
+            if i < e.len() && j < g.len() && e[i].1 == g[j].1 {
+                push(Kind::Same, e[i].0, char_end(&e[i]));
+                i += 1;
+                j += 1;
+            } else if j == g.len()
+                || (i < e.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+            {
+                push(Kind::Removed, e[i].0, char_end(&e[i]));
+                i += 1;
+            } else {
+                push(Kind::Added, g[j].0, char_end(&g[j]));
+                j += 1;
+            }
+        }
+    }
+    push(Kind::Same, expected.len() - suffix, expected.len());
+
+    ranges
+        .into_iter()
+        .filter(|&(_, start, end)| start < end)
+        .map(|(kind, start, end)| match kind {
+            Kind::Same => Change::Same(&expected[start..end]),
+            Kind::Removed => Change::Removed(&expected[start..end]),
+            Kind::Added => Change::Added(&got[start..end]),
+        })
+        .collect()
+}
+
+/// `expected 1068781, got 1068783`, with what differs in red when `color` is set: the digits
+/// after the longest common prefix for numbers, the characters that are not in both answers for
+/// other answers. Without colors this is the plain text the reports use
+pub fn mismatch(expected: &str, got: &str, color: bool) -> String {
+    if !color {
+        return format!("expected {}, got {}", expected, got);
+    }
+
+    let red = |text: &str| {
+        if text.is_empty() {
+            String::new()
+        } else {
+            format!("{}{}{}", RED, text, RESET)
+        }
+    };
+    let is_number = |answer: &str| answer.parse::<i128>().is_ok();
+
+    let (expected, got) = if is_number(expected) && is_number(got) {
+        let prefix = common_prefix(expected, got);
+        (
+            format!("{}{}", &expected[..prefix], red(&expected[prefix..])),
+            format!("{}{}", &got[..prefix], red(&got[prefix..])),
+        )
+    } else {
+        let mut colored = (String::new(), String::new());
+        for change in diff_chars(expected, got) {
+            match change {
+                Change::Same(text) => {
+                    colored.0.push_str(text);
+                    colored.1.push_str(text);
+                }
+                Change::Removed(text) => colored.0.push_str(&red(text)),
+                Change::Added(text) => colored.1.push_str(&red(text)),
+            }
+        }
+        colored
+    };
+
+    format!("expected {}, got {}", expected, got)
+}
+
+#[test]
+fn test_common_prefix() {
+    assert_eq!(common_prefix("1068781", "1068783"), 6);
+    assert_eq!(common_prefix("12", "123"), 2);
+    assert_eq!(common_prefix("abc", "xbc"), 0);
+    assert_eq!(common_prefix("été", "étaient"), 3);
+    assert_eq!(common_suffix("été", "ôté"), 3);
+}
+
+#[test]
+fn test_diff_chars() {
+    use Change::*;
+
+    assert_eq!(diff_chars("same", "same"), [Same("same")]);
+    assert_eq!(
+        diff_chars("kitten", "sitting"),
+        [
+            Removed("k"),
+            Added("s"),
+            Same("itt"),
+            Removed("e"),
+            Added("i"),
+            Same("n"),
+            Added("g"),
+        ]
+    );
+    assert_eq!(
+        diff_chars("mxmxvkd,sqjhc,fvjkl", "mxmxvkd,fvjkl"),
+        [Same("mxmxvkd,"), Removed("sqjhc,"), Same("fvjkl")]
+    );
+    assert_eq!(diff_chars("", "ab"), [Added("ab")]);
+    assert_eq!(
+        diff_chars("çà", "çé"),
+        [Same("ç"), Removed("à"), Added("é")]
+    );
+}
+
+#[test]
+fn test_mismatch() {
+    assert_eq!(
+        mismatch("1068781", "1068783", false),
+        "expected 1068781, got 1068783"
+    );
+    assert_eq!(
+        mismatch("1068781", "1068783", true),
+        "expected 106878\x1b[31m1\x1b[0m, got 106878\x1b[31m3\x1b[0m"
+    );
+    // Numbers only get the suffix highlighted, even when it has digits in common
+    assert_eq!(
+        mismatch("1201", "1301", true),
+        "expected 1\x1b[31m201\x1b[0m, got 1\x1b[31m301\x1b[0m"
+    );
+    assert_eq!(
+        mismatch("12", "123", true),
+        "expected 12, got 12\x1b[31m3\x1b[0m"
+    );
+    assert_eq!(
+        mismatch("abcd", "abxd", true),
+        "expected ab\x1b[31mc\x1b[0md, got ab\x1b[31mx\x1b[0md"
+    );
+}
+
+#[test]
+fn test_use_colors() {
+    assert!(use_colors(None, true));
+    assert!(use_colors(Some(OsString::new()), true));
+    assert!(!use_colors(Some("1".into()), true));
+    assert!(!use_colors(None, false));
+}
@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Render a sparse set of cells as the 2D slices of the puzzle texts, one panel per position
+/// along the dimensions named by `fixed_dims`, like `z=0, w=1`.
+///
+/// The first two coordinates of each cell are its row and column, and the following ones are the
+/// fixed dimensions, the first fixed dimension changing fastest between panels. Every panel
+/// covers the bounding box of all the cells, with `#` for the cells of the set and `.` elsewhere.
+pub fn render_slices<const N: usize>(cells: &HashSet<[isize; N]>, fixed_dims: &[&str]) -> String {
+    assert_eq!(
+        fixed_dims.len() + 2,
+        N,
+        "every dimension beyond the rows and columns needs a name"
+    );
+
+    let mut output = String::new();
+    if cells.is_empty() {
+        return output;
+    }
+
+    let mut min = [isize::MAX; N];
+    let mut max = [isize::MIN; N];
+    for cell in cells {
+        for dim in 0..N {
+            min[dim] = min[dim].min(cell[dim]);
+            max[dim] = max[dim].max(cell[dim]);
+        }
+    }
+
+    let mut slice = min;
+    loop {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+
+        if !fixed_dims.is_empty() {
+            let header = fixed_dims
+                .iter()
+                .zip(&slice[2..])
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(output, "{}", header).unwrap();
+        }
+
+        let mut cell = slice;
+        for row in min[0]..=max[0] {
+            cell[0] = row;
+            for column in min[1]..=max[1] {
+                cell[1] = column;
+                output.push(if cells.contains(&cell) { '#' } else { '.' });
+            }
+            output.push('\n');
+        }
+
+        // Next slice, counting along the fixed dimensions like an odometer
+        let next = (2..N).find(|&dim| slice[dim] < max[dim]);
+        match next {
+            Some(dim) => {
+                slice[dim] += 1;
+                slice[2..dim].copy_from_slice(&min[2..dim]);
+            }
+            None => break,
+        }
+    }
+
+    output
+}
+
+/// Alignment of the cells of a column, see [`render_table`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// Render rows under a header line, each column as wide as its widest cell and two spaces apart
+/// from the next one, like the summary of the years of a run. Lines have no trailing spaces
+pub fn render_table(columns: &[(&str, Align)], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<_> = columns
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .collect();
+    for row in rows {
+        assert_eq!(
+            row.len(),
+            columns.len(),
+            "every row needs a cell per column"
+        );
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut output = String::new();
+    let header = columns.iter().map(|(name, _)| name.to_string());
+    for line in std::iter::once(header.collect::<Vec<_>>()).chain(rows.iter().cloned()) {
+        let mut rendered = String::new();
+        for (index, cell) in line.iter().enumerate() {
+            if index > 0 {
+                rendered.push_str("  ");
+            }
+            let padding = widths[index] - cell.chars().count();
+            match columns[index].1 {
+                Align::Left => {
+                    rendered.push_str(cell);
+                    rendered.extend(std::iter::repeat_n(' ', padding));
+                }
+                Align::Right => {
+                    rendered.extend(std::iter::repeat_n(' ', padding));
+                    rendered.push_str(cell);
+                }
+            }
+        }
+        writeln!(output, "{}", rendered.trim_end()).unwrap();
+    }
+
+    output
+}
+
+#[test]
+fn test_render_slices() {
+    let cells: HashSet<_> = [[0, 1, 0], [1, 2, -1], [1, 0, 0]].iter().copied().collect();
+
+    assert_eq!(
+        render_slices(&cells, &["z"]),
+        "z=-1\n...\n..#\n\nz=0\n.#.\n#..\n"
+    );
+    assert_eq!(
+        render_slices(&[[0, 0]].iter().copied().collect(), &[]),
+        "#\n"
+    );
+    assert_eq!(render_slices(&HashSet::<[isize; 3]>::new(), &["z"]), "");
+}
+
+#[test]
+fn test_render_table() {
+    let rows = vec![
+        vec!["2020".to_owned(), "16".to_owned(), "Zoë".to_owned()],
+        vec!["2021".to_owned(), "0".to_owned(), String::new()],
+    ];
+
+    assert_eq!(
+        render_table(
+            &[
+                ("Year", Align::Left),
+                ("Days", Align::Right),
+                ("Who", Align::Left)
+            ],
+            &rows
+        ),
+        "Year  Days  Who\n\
+         2020    16  Zoë\n\
+         2021     0\n"
+    );
+    assert_eq!(render_table(&[("Rank", Align::Right)], &[]), "Rank\n");
+}
@@ -0,0 +1,363 @@
+use std::fmt;
+
+/// Offsets `(row, column)` to the eight cells around a cell, clockwise from the top-left
+pub const DIRECTIONS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+];
+
+/// Rectangular grid of cells, stored row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    Empty,
+    /// A row doesn't have the same length as the first one
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidCell {
+        row: usize,
+        column: usize,
+        cell: char,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::Empty => f.write_str("the grid is empty"),
+            GridError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {} has {} cells, expected {}",
+                row + 1,
+                found,
+                expected
+            ),
+            GridError::InvalidCell { row, column, cell } => write!(
+                f,
+                "invalid cell {:?} at row {}, column {}",
+                cell,
+                row + 1,
+                column + 1
+            ),
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Parse a grid with one row per line, converting each character with `parse_cell`
+    pub fn parse(
+        input: &str,
+        mut parse_cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, GridError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+
+        for (row, line) in input.lines().enumerate() {
+            let row_start = cells.len();
+
+            for (column, cell) in line.chars().enumerate() {
+                cells.push(parse_cell(cell).ok_or(GridError::InvalidCell { row, column, cell })?);
+            }
+
+            let found = cells.len() - row_start;
+            match width {
+                None => width = Some(found),
+                Some(expected) if expected != found => {
+                    return Err(GridError::RaggedRow {
+                        row,
+                        expected,
+                        found,
+                    })
+                }
+                Some(_) => {}
+            }
+
+            height += 1;
+        }
+
+        match width {
+            Some(width) if width > 0 => Ok(Self {
+                width,
+                height,
+                cells,
+            }),
+            _ => Err(GridError::Empty),
+        }
+    }
+
+    /// Build a grid by computing each cell from its `(row, column)` position
+    pub fn from_fn(height: usize, width: usize, mut cell: impl FnMut(usize, usize) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|row| (0..width).map(move |column| (row, column)))
+            .map(|(row, column)| cell(row, column))
+            .collect();
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Iterate over the cells row by row
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Cells of a row, from left to right
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    /// Cells of a column, from top to bottom
+    pub fn column(&self, column: usize) -> impl Iterator<Item = &T> {
+        assert!(column < self.width, "column out of bounds");
+        self.cells.iter().skip(column).step_by(self.width)
+    }
+
+    /// Position one step away from the given one, if it is inside the grid
+    pub fn offset(
+        &self,
+        (row, column): (usize, usize),
+        (delta_row, delta_column): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let row = row.checked_add_signed(delta_row)?;
+        let column = column.checked_add_signed(delta_column)?;
+
+        if row < self.height && column < self.width {
+            Some((row, column))
+        } else {
+            None
+        }
+    }
+
+    /// Positions of the up to eight cells adjacent to the given one, including diagonals
+    pub fn neighbors8(
+        &self,
+        row: usize,
+        column: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRECTIONS8
+            .iter()
+            .filter_map(move |&direction| self.offset((row, column), direction))
+    }
+
+    /// Positions going from the given one (excluded) in a direction until the edge of the grid
+    pub fn ray(
+        &self,
+        row: usize,
+        column: usize,
+        direction: (isize, isize),
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        std::iter::successors(self.offset((row, column), direction), move |&position| {
+            self.offset(position, direction)
+        })
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Option<&T> {
+        if row < self.height && column < self.width {
+            Some(&self.cells[row * self.width + column])
+        } else {
+            None
+        }
+    }
+
+    /// Same as `get`, but the grid repeats itself horizontally forever
+    pub fn get_wrapping(&self, row: usize, column: usize) -> Option<&T> {
+        self.get(row, column % self.width)
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Rotate the grid a quarter turn clockwise
+    pub fn rotate_cw(&self) -> Self {
+        Self::from_fn(self.width, self.height, |row, column| {
+            self.cells[(self.height - 1 - column) * self.width + row].clone()
+        })
+    }
+
+    /// Mirror the grid left to right
+    pub fn flip_h(&self) -> Self {
+        Self::from_fn(self.height, self.width, |row, column| {
+            self.cells[row * self.width + self.width - 1 - column].clone()
+        })
+    }
+
+    /// Mirror the grid top to bottom
+    pub fn flip_v(&self) -> Self {
+        Self::from_fn(self.height, self.width, |row, column| {
+            self.cells[(self.height - 1 - row) * self.width + column].clone()
+        })
+    }
+
+    /// The eight ways the grid can be laid out by rotating and flipping it, starting with the
+    /// grid itself
+    pub fn orientations(&self) -> impl Iterator<Item = Self> {
+        let quarter = self.rotate_cw();
+        let half = quarter.rotate_cw();
+        let three_quarters = half.rotate_cw();
+
+        vec![
+            self.clone(),
+            self.flip_h(),
+            self.flip_v(),
+            quarter.flip_h(),
+            quarter.flip_v(),
+            quarter,
+            half,
+            three_quarters,
+        ]
+        .into_iter()
+    }
+}
+
+#[test]
+fn test_parse() {
+    let grid = Grid::parse("#.\n..\n.#\n", |c| Some(c == '#')).unwrap();
+
+    assert_eq!(grid.height(), 3);
+    assert_eq!(grid.get(0, 0), Some(&true));
+    assert_eq!(grid.get(2, 1), Some(&true));
+    assert_eq!(grid.get(1, 1), Some(&false));
+    assert_eq!(grid.get(3, 0), None);
+    assert_eq!(grid.get(0, 2), None);
+
+    assert_eq!(
+        Grid::parse("#.\n#\n", |c| Some(c == '#')),
+        Err(GridError::RaggedRow {
+            row: 1,
+            expected: 2,
+            found: 1
+        })
+    );
+    assert_eq!(
+        Grid::parse("#x", |c| if c == 'x' { None } else { Some(c) }),
+        Err(GridError::InvalidCell {
+            row: 0,
+            column: 1,
+            cell: 'x'
+        })
+    );
+    assert_eq!(Grid::parse("", Some), Err(GridError::Empty));
+}
+
+#[test]
+fn test_wrapping_columns() {
+    let grid = Grid::parse("abc\ndef\n", Some).unwrap();
+
+    assert_eq!(grid.get_wrapping(0, 0), Some(&'a'));
+    assert_eq!(grid.get_wrapping(0, 3), Some(&'a'));
+    assert_eq!(grid.get_wrapping(1, 5), Some(&'f'));
+    assert_eq!(grid.get_wrapping(1, 3 * 1000 + 1), Some(&'e'));
+    // Rows don't wrap
+    assert_eq!(grid.get_wrapping(2, 0), None);
+}
+
+#[test]
+fn test_neighbors8() {
+    let grid = Grid::from_fn(3, 4, |row, column| row * 4 + column);
+
+    let mut corner: Vec<_> = grid.neighbors8(0, 0).collect();
+    corner.sort_unstable();
+    assert_eq!(corner, [(0, 1), (1, 0), (1, 1)]);
+
+    assert_eq!(grid.neighbors8(1, 1).count(), 8);
+    assert_eq!(grid.neighbors8(2, 3).count(), 3);
+    assert_eq!(grid.neighbors8(1, 3).count(), 5);
+}
+
+#[test]
+fn test_ray() {
+    let grid = Grid::from_fn(3, 4, |row, column| row * 4 + column);
+
+    assert_eq!(
+        grid.ray(0, 0, (0, 1)).collect::<Vec<_>>(),
+        [(0, 1), (0, 2), (0, 3)]
+    );
+    assert_eq!(grid.ray(0, 0, (1, 1)).collect::<Vec<_>>(), [(1, 1), (2, 2)]);
+    assert_eq!(
+        grid.ray(2, 3, (-1, -1)).collect::<Vec<_>>(),
+        [(1, 2), (0, 1)]
+    );
+    assert_eq!(grid.ray(0, 0, (-1, 0)).count(), 0);
+    assert_eq!(
+        grid.ray(1, 1, (1, 0))
+            .map(|(r, c)| grid.get(r, c))
+            .collect::<Vec<_>>(),
+        [Some(&9)]
+    );
+}
+
+#[test]
+fn test_rows_and_columns() {
+    let grid = Grid::from_fn(2, 3, |row, column| row * 3 + column);
+
+    assert_eq!(grid.row(1), [3, 4, 5]);
+    assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), [2, 5]);
+}
+
+#[test]
+fn test_transforms() {
+    // 0 1 2
+    // 3 4 5
+    let grid = Grid::from_fn(2, 3, |row, column| row * 3 + column);
+
+    let rotated = grid.rotate_cw();
+    assert_eq!((rotated.height(), rotated.width()), (3, 2));
+    assert_eq!(rotated.cells, [3, 0, 4, 1, 5, 2]);
+    assert_eq!(
+        rotated.rotate_cw().rotate_cw().rotate_cw(),
+        grid,
+        "four quarter turns are a full turn"
+    );
+
+    assert_eq!(grid.flip_h().cells, [2, 1, 0, 5, 4, 3]);
+    assert_eq!(grid.flip_v().cells, [3, 4, 5, 0, 1, 2]);
+    assert_eq!(grid.flip_h().flip_v(), rotated.rotate_cw());
+}
+
+#[test]
+fn test_orientations() {
+    let grid = Grid::from_fn(3, 3, |row, column| row * 3 + column);
+
+    let orientations: Vec<_> = grid.orientations().collect();
+    assert_eq!(orientations[0], grid);
+
+    for (i, first) in orientations.iter().enumerate() {
+        for second in &orientations[i + 1..] {
+            assert_ne!(first, second, "orientations must be distinct");
+        }
+        assert!(
+            orientations.contains(&first.rotate_cw()) && orientations.contains(&first.flip_h()),
+            "orientations must be closed under rotation and flipping"
+        );
+    }
+}
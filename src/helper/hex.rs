@@ -0,0 +1,103 @@
+/// Position on a grid of hexagons with pointy tops, in axial coordinates.
+///
+/// Going east increases `q`, going south-east increases `r`, and the third cube coordinate is
+/// implied as `-q - r`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    East,
+    SouthEast,
+    SouthWest,
+    West,
+    NorthWest,
+    NorthEast,
+}
+
+impl Direction {
+    /// Every direction, clockwise from east
+    pub const ALL: [Direction; 6] = [
+        Direction::East,
+        Direction::SouthEast,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+        Direction::NorthEast,
+    ];
+
+    /// Axial offset `(q, r)` to the adjacent hexagon in this direction
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Direction::East => (1, 0),
+            Direction::SouthEast => (0, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (0, -1),
+            Direction::NorthEast => (1, -1),
+        }
+    }
+}
+
+impl Hex {
+    pub fn new(q: i32, r: i32) -> Self {
+        Self { q, r }
+    }
+
+    pub fn neighbor(self, direction: Direction) -> Self {
+        let (dq, dr) = direction.offset();
+        Self::new(self.q + dq, self.r + dr)
+    }
+
+    pub fn neighbors(self) -> impl Iterator<Item = Self> {
+        Direction::ALL
+            .iter()
+            .map(move |&direction| self.neighbor(direction))
+    }
+}
+
+#[test]
+fn test_opposite_directions_cancel() {
+    let origin = Hex::default();
+
+    for (i, &direction) in Direction::ALL.iter().enumerate() {
+        let opposite = Direction::ALL[(i + 3) % 6];
+        assert_eq!(origin.neighbor(direction).neighbor(opposite), origin);
+    }
+}
+
+#[test]
+fn test_neighbors() {
+    let origin = Hex::default();
+
+    let mut neighbors: Vec<_> = origin.neighbors().collect();
+    neighbors.sort();
+    neighbors.dedup();
+    assert_eq!(neighbors.len(), 6);
+
+    // Walking every direction in order from the north-west neighbor goes around the origin
+    let start = origin.neighbor(Direction::NorthWest);
+    let end = Direction::ALL.iter().fold(start, |hex, &direction| {
+        assert!(hex.neighbors().any(|hex| hex == origin));
+        hex.neighbor(direction)
+    });
+    assert_eq!(end, start);
+}
+
+#[test]
+fn test_path() {
+    let hex = [Direction::East, Direction::East, Direction::SouthWest]
+        .iter()
+        .fold(Hex::default(), |hex, &direction| hex.neighbor(direction));
+
+    assert_eq!(hex, Hex::new(1, 1));
+    assert_eq!(
+        hex,
+        Hex::default()
+            .neighbor(Direction::SouthEast)
+            .neighbor(Direction::East)
+    );
+}
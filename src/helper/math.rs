@@ -0,0 +1,134 @@
+/// Extended Euclidean algorithm, returning `(g, x, y)` such that `a * x + b * y = g = gcd(a, b)`
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a.abs(), a.signum(), 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+/// Solve a system of congruences `x ≡ remainder (mod modulus)` with the Chinese Remainder
+/// Theorem, returning the smallest non-negative solution and the modulus of the combined
+/// congruence.
+///
+/// Moduli don't need to be coprime; `None` is returned when the congruences contradict each
+/// other, or when the combined modulus doesn't fit in an `i128`.
+pub fn crt(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    congruences.iter().try_fold(
+        (0, 1),
+        |(solution, modulus): (i128, i128), &(remainder, m)| {
+            assert!(m > 0, "moduli must be positive");
+
+            let (g, inverse, _) = extended_gcd(modulus, m);
+            let difference = remainder - solution;
+            if difference % g != 0 {
+                return None;
+            }
+
+            let step = m / g;
+            let lcm = modulus.checked_mul(step)?;
+            // Number of `modulus` increments that moves `solution` onto the new congruence
+            let factor = (difference / g % step)
+                .checked_mul(inverse % step)?
+                .rem_euclid(step);
+
+            Some(((solution + modulus * factor).rem_euclid(lcm), lcm))
+        },
+    )
+}
+
+/// Compute `base^exponent mod modulus` by repeated squaring
+pub fn mod_pow(base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let modulus = u128::from(modulus);
+    let mut base = u128::from(base) % modulus;
+    let mut result = 1 % modulus;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+
+    result as u64
+}
+
+/// Smallest exponent `x` such that `base^x ≡ target (mod modulus)`, if any.
+///
+/// Uses the baby-step giant-step algorithm, taking `O(√modulus)` time and memory. The modulus
+/// must be prime, so every power of `base` has an inverse.
+pub fn discrete_log(base: u64, target: u64, modulus: u64) -> Option<u64> {
+    let step = (modulus as f64).sqrt().ceil() as u64;
+    let target = target % modulus;
+
+    // Baby steps: base^j for every j below the step, keeping the smallest j for each power
+    let mut baby_steps = std::collections::HashMap::new();
+    let mut power = 1 % modulus;
+    for j in 0..step {
+        baby_steps.entry(power).or_insert(j);
+        power = mul_mod(power, base, modulus);
+    }
+
+    // Giant steps: target * base^(-step * i), looking for a match within the baby steps
+    let giant_step = mod_pow(base, modulus - 1 - step % (modulus - 1), modulus);
+    let mut value = target;
+    for i in 0..step {
+        if let Some(&j) = baby_steps.get(&value) {
+            return Some(i * step + j);
+        }
+        value = mul_mod(value, giant_step, modulus);
+    }
+
+    None
+}
+
+fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(modulus)) as u64
+}
+
+#[test]
+fn test_extended_gcd() {
+    for &(a, b) in &[(240, 46), (17, 5), (5, 17), (12, 18), (7, 0)] {
+        let (g, x, y) = extended_gcd(a, b);
+        assert_eq!(a * x + b * y, g, "bezout identity for {} and {}", a, b);
+        assert_eq!(a % g, 0);
+        assert_eq!(b % g, 0);
+    }
+}
+
+#[test]
+fn test_crt() {
+    assert_eq!(crt(&[]), Some((0, 1)));
+    assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+    assert_eq!(crt(&[(0, 17), (-2, 13), (-3, 19)]), Some((3417, 4199)));
+
+    // Non-coprime moduli
+    assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+    assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+}
+
+#[test]
+fn test_mod_pow() {
+    assert_eq!(mod_pow(7, 8, 20201227), 5764801);
+    assert_eq!(mod_pow(2, 10, 1000), 24);
+    assert_eq!(mod_pow(5, 0, 7), 1);
+    assert_eq!(mod_pow(5, 0, 1), 0);
+    assert_eq!(mod_pow(u64::MAX, 2, u64::MAX - 1), 1);
+}
+
+#[test]
+fn test_discrete_log() {
+    assert_eq!(discrete_log(7, 5764801, 20201227), Some(8));
+    assert_eq!(discrete_log(7, 17807724, 20201227), Some(11));
+    assert_eq!(discrete_log(3, 1, 7), Some(0));
+    assert_eq!(discrete_log(2, 3, 7), None);
+
+    for exponent in 0..100 {
+        let target = mod_pow(5, exponent, 101);
+        let found = discrete_log(5, target, 101).unwrap();
+        assert_eq!(mod_pow(5, found, 101), target);
+        assert!(found <= exponent);
+    }
+}
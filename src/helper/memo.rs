@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Cache for recursive functions, where computing a value may require the values of other keys.
+///
+/// ```ignore
+/// fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+///     memo.get_or_insert_with(n, |memo| if n < 2 { n } else { fib(memo, n - 1) + fib(memo, n - 2) })
+/// }
+/// ```
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+}
+
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Return the cached value for `key`, computing it with `compute` on the first request.
+    /// `compute` receives the memo itself, so it can recursively request other keys.
+    pub fn get_or_insert_with(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            return value.clone();
+        }
+
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+
+        value
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_memo() {
+    fn fib(memo: &mut Memo<u64, u64>, calls: &mut usize, n: u64) -> u64 {
+        memo.get_or_insert_with(n, |memo| {
+            *calls += 1;
+            if n < 2 {
+                n
+            } else {
+                fib(memo, calls, n - 1) + fib(memo, calls, n - 2)
+            }
+        })
+    }
+
+    let mut memo = Memo::new();
+    let mut calls = 0;
+
+    assert_eq!(fib(&mut memo, &mut calls, 90), 2880067194370816120);
+    // Every value is computed exactly once
+    assert_eq!(calls, 91);
+
+    assert_eq!(fib(&mut memo, &mut calls, 50), 12586269025);
+    assert_eq!(calls, 91);
+}
@@ -1 +1,201 @@
+pub mod automaton;
+pub mod bitset;
+#[cfg(test)]
+pub mod counting_alloc;
+pub mod diff;
+pub mod display;
+pub mod grid;
+pub mod hex;
+pub mod math;
+pub mod memo;
 pub mod nom;
+#[macro_use]
+pub mod output;
+pub mod parse;
+pub mod search;
+pub mod temp;
+pub mod vm;
+
+/// Registers the days of a year, filling the missing ones with skipped days. Days solved with an
+/// [`AdventState`](crate::advent_adapters::AdventState) are marked `stateful`, other entries are
+/// values implementing [`Advent`](crate::advents::Advent). Entries can have attributes, a
+/// `#[cfg(...)]` leaving the day skipped when it doesn't hold
+///
+/// ```
+/// # #[cfg(feature = "y2020")] {
+/// use advent_of_rust::advent_2020::{day03, day16};
+/// use advent_of_rust::advent_year;
+///
+/// let advent_year = advent_year!(2020 {
+///     3 => stateful day03::AdventDay03,
+///     16 => day16::AdventDay16,
+/// });
+///
+/// // Same as
+/// // AdventYear::new(2020, vec![
+/// //     Box::new(SkippedAdvent::new(1)),
+/// //     Box::new(SkippedAdvent::new(2)),
+/// //     Box::new(StatefulAdvent::<day03::AdventDay03>::new(3)),
+/// //     ...
+/// //     Box::new(day16::AdventDay16),
+/// //     ...
+/// //     Box::new(SkippedAdvent::new(25)),
+/// // ])
+/// let days: Vec<_> = advent_year.iter().map(|advent| advent.get_index()).collect();
+/// assert_eq!(days, (1..=25).collect::<Vec<_>>());
+///
+/// let solved: Vec<_> = advent_year
+///     .iter()
+///     .filter(|advent| !advent.skip())
+///     .map(|advent| advent.get_index())
+///     .collect();
+/// assert_eq!(solved, [3, 16]);
+/// # }
+/// ```
+///
+/// Registering a day twice doesn't compile
+///
+/// ```compile_fail
+/// use advent_of_rust::advent_2020::day03;
+/// use advent_of_rust::advent_year;
+///
+/// advent_year!(2020 {
+///     3 => stateful day03::AdventDay03,
+///     3 => stateful day03::AdventDay03,
+/// });
+/// ```
+#[macro_export]
+macro_rules! advent_year {
+    ($year:literal { $($entries:tt)* }) => {{
+        // Not mutated when the year has no entries
+        #[allow(unused_mut)]
+        let mut advents: ::std::vec::Vec<
+            ::std::option::Option<::std::boxed::Box<dyn $crate::advents::Advent>>,
+        > = (0..25).map(|_| None).collect();
+
+        $crate::advent_year!(@entries advents [] $($entries)*);
+
+        $crate::advents::AdventYear::new(
+            $year,
+            advents
+                .into_iter()
+                .zip(1..)
+                .map(|(advent, day)| {
+                    advent.unwrap_or_else(|| {
+                        ::std::boxed::Box::new($crate::advents::SkippedAdvent::new(day))
+                    })
+                })
+                .collect(),
+        )
+    }};
+    (@entries $advents:ident [$($days:literal)*]
+        $(#[$attr:meta])* $day:literal => stateful $advent:path $(, $($rest:tt)*)?) => {
+        $(#[$attr])*
+        {
+            $advents[$day - 1] = Some(::std::boxed::Box::new(
+                $crate::advent_adapters::StatefulAdvent::<$advent>::new($day),
+            ));
+        }
+        $crate::advent_year!(@entries $advents [$($days)* $day] $($($rest)*)?);
+    };
+    (@entries $advents:ident [$($days:literal)*]
+        $(#[$attr:meta])* $day:literal => $advent:path $(, $($rest:tt)*)?) => {
+        $(#[$attr])*
+        {
+            $advents[$day - 1] = Some(::std::boxed::Box::new($advent));
+        }
+        $crate::advent_year!(@entries $advents [$($days)* $day] $($($rest)*)?);
+    };
+    (@entries $advents:ident [$($days:literal)*]) => {
+        const _: () = {
+            let days: &[u8] = &[$($days),*];
+            let mut i = 0;
+            while i < days.len() {
+                assert!(days[i] >= 1 && days[i] <= 25, "days go from 1 to 25");
+                let mut j = i + 1;
+                while j < days.len() {
+                    assert!(days[i] != days[j], "day registered twice");
+                    j += 1;
+                }
+                i += 1;
+            }
+        };
+    };
+}
+
+/// Implements [`AdventState`](crate::advent_adapters::AdventState) for a day whose parsing and
+/// parts are single expressions. The parse expression returns `Result<Self, String>`, and each
+/// part returns a `Result` of anything convertible to an answer. The optional `repl` gives the
+/// [`Repl`](crate::advents::Repl) of the day
+///
+/// ```
+/// use advent_of_rust::advent_adapters::StatefulAdvent;
+/// use advent_of_rust::advents::{Advent, Answer, Parts};
+/// use advent_of_rust::stateful_day;
+///
+/// pub struct Sums {
+///     numbers: Vec<u32>,
+/// }
+///
+/// stateful_day! {
+///     Sums {
+///         title: "Sums",
+///         input_files: ["test.txt"],
+///         parse(input) => input
+///             .lines()
+///             .map(|line| line.parse().map_err(|_| format!("{:?} is not a number", line)))
+///             .collect::<Result<_, _>>()
+///             .map(|numbers| Sums { numbers }),
+///         part1(day) => Ok::<_, String>(day.numbers.iter().sum::<u32>()),
+///         part2(day) => day.numbers.iter().copied().max().ok_or("no numbers"),
+///     }
+/// }
+///
+/// let advent = StatefulAdvent::<Sums>::new(1);
+/// assert_eq!(advent.title(), Some("Sums"));
+/// assert_eq!(
+///     advent.solve("test.txt", "1\n5\n3".to_owned(), Parts::All),
+///     Ok(vec![(1, Ok(Answer::Unsigned(9))), (2, Ok(Answer::Unsigned(5)))])
+/// );
+/// ```
+#[macro_export]
+macro_rules! stateful_day {
+    ($day:ident {
+        $(title: $title:literal,)?
+        $(repl: $repl:expr,)?
+        input_files: [$($file:literal),* $(,)?],
+        parse($input:ident) => $parse:expr,
+        part1($part1:ident) => $answer1:expr,
+        part2($part2:ident) => $answer2:expr $(,)?
+    }) => {
+        impl $crate::advent_adapters::AdventState for $day {
+            const INPUT_FILES: &'static [&'static str] = &[$($file),*];
+            $(const TITLE: ::std::option::Option<&'static str> = Some($title);)?
+
+            $(
+                fn repl() -> ::std::option::Option<
+                    ::std::boxed::Box<dyn $crate::advents::Repl>,
+                > {
+                    ::std::option::Option::Some(::std::boxed::Box::new($repl))
+                }
+            )?
+
+            fn new(
+                _input_file: &str,
+                $input: ::std::string::String,
+            ) -> ::std::result::Result<Self, ::std::string::String> {
+                $parse
+            }
+
+            fn part1(&self) -> $crate::advents::PartResult {
+                let $part1 = self;
+                $crate::advent_adapters::part_result($answer1)
+            }
+
+            fn part2(&self) -> $crate::advents::PartResult {
+                let $part2 = self;
+                $crate::advent_adapters::part_result($answer2)
+            }
+        }
+    };
+}
@@ -1,13 +1,30 @@
+use std::convert::TryFrom;
 use std::fmt;
 use std::iter::FromIterator;
+use std::str::FromStr;
 
 use nom::branch::Alt;
 use nom::error::{ContextError, ErrorKind, FromExternalError, ParseError};
 use nom::sequence::Tuple;
-use nom::{Err, IResult, Parser};
+use nom::{Err, Finish, IResult, Parser};
 
+/// List of parsers built at runtime, usable with `alt` and `tuple`. Parsers of different types
+/// can be mixed by boxing them, see [`BoxedParsers`] and [`dyn_alt!`]
 pub struct DynamicAlt<P>(Vec<P>);
 
+/// Parsers of any type with the same input, output and error types
+pub type BoxedParsers<'a, I, O, E> = DynamicAlt<Box<dyn Parser<I, O, E> + 'a>>;
+
+/// Build a [`BoxedParsers`] from parsers of different types, boxing each of them
+#[macro_export]
+macro_rules! dyn_alt {
+    ($($parser:expr),+ $(,)?) => {
+        $crate::helper::nom::DynamicAlt::from(vec![
+            $(Box::new($parser) as Box<dyn ::nom::Parser<_, _, _> + '_>),+
+        ])
+    };
+}
+
 impl<P> From<Vec<P>> for DynamicAlt<P> {
     fn from(v: Vec<P>) -> Self {
         Self(v)
@@ -25,18 +42,18 @@ where
     P: Parser<I, O, E>,
 {
     fn choice(&mut self, input: I) -> IResult<I, O, E> {
-        let length = self.0.len();
+        let (last, others) = self
+            .0
+            .split_last_mut()
+            .expect("DynamicAlt must include at least one alternative");
 
-        for alt in &mut self.0[..length - 1] {
+        for alt in others {
             if let Ok(o) = alt.parse(input.clone()) {
                 return Ok(o);
             };
         }
 
-        self.0
-            .last_mut()
-            .expect("DynamicAlt must include at least one alternative")
-            .parse(input)
+        last.parse(input)
     }
 }
 
@@ -58,6 +75,62 @@ where
     }
 }
 
+/// Apply every parser exactly once, in any order, returning their results in the order of the
+/// parsers. At each position the unmatched parsers are tried in order and the first one
+/// matching is used, even if another one could match there too. When none matches, the error
+/// lists the parsers still unmatched, by index
+pub fn dynamic_permutation<I: Clone, O, P>(
+    parsers: DynamicAlt<P>,
+) -> impl FnMut(I) -> IResult<I, Vec<O>, VerboseError<I>>
+where
+    P: Parser<I, O, VerboseError<I>>,
+{
+    let mut parsers = parsers.0;
+
+    move |mut input: I| {
+        let mut results: Vec<Option<O>> = parsers.iter().map(|_| None).collect();
+
+        for _ in 0..parsers.len() {
+            let mut matched = None;
+
+            for (index, parser) in parsers.iter_mut().enumerate() {
+                if results[index].is_some() {
+                    continue;
+                }
+
+                match parser.parse(input.clone()) {
+                    Ok(next) => {
+                        matched = Some((index, next));
+                        break;
+                    }
+                    Err(Err::Error(_)) => continue,
+                    Err(err) => return Err(err),
+                }
+            }
+
+            match matched {
+                Some((index, (next, result))) => {
+                    results[index] = Some(result);
+                    input = next;
+                }
+                None => {
+                    let unmatched: Vec<_> = (0..results.len())
+                        .filter(|&index| results[index].is_none())
+                        .collect();
+
+                    return Err(Err::Error(VerboseError::add_owned_context(
+                        input.clone(),
+                        format!("permutation, unmatched parsers {:?}", unmatched),
+                        VerboseError::from_error_kind(input, ErrorKind::Permutation),
+                    )));
+                }
+            }
+        }
+
+        Ok((input, results.into_iter().map(Option::unwrap).collect()))
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct VerboseError<I> {
     errors: Vec<(I, VerboseErrorKind)>,
@@ -119,6 +192,8 @@ impl<I: fmt::Display> fmt::Display for VerboseError<I> {
     }
 }
 
+impl<I: fmt::Debug + fmt::Display> std::error::Error for VerboseError<I> {}
+
 impl<I> ContextError<I> for VerboseError<I> {
     fn add_context(input: I, ctx: &'static str, mut other: Self) -> Self {
         other.errors.push((input, VerboseErrorKind::Context(ctx)));
@@ -127,6 +202,44 @@ impl<I> ContextError<I> for VerboseError<I> {
 }
 
 impl<I> VerboseError<I> {
+    /// Frames of the error, from the innermost failure out to the outermost context
+    pub fn frames(&self) -> impl Iterator<Item = (&I, &VerboseErrorKind)> {
+        self.errors.iter().map(|(input, kind)| (input, kind))
+    }
+
+    /// Frame of the innermost failure, where parsing actually stopped
+    pub fn deepest(&self) -> Option<&(I, VerboseErrorKind)> {
+        self.errors.first()
+    }
+
+    /// Static and owned contexts of the error, innermost first
+    pub fn contexts(&self) -> impl Iterator<Item = &str> {
+        self.errors.iter().filter_map(|(_, kind)| match kind {
+            VerboseErrorKind::Context(ctx) => Some(*ctx),
+            VerboseErrorKind::OwnedContext(ctx) => Some(ctx.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Same error with every input position converted by `f`
+    pub fn map_input<J>(self, mut f: impl FnMut(I) -> J) -> VerboseError<J> {
+        VerboseError {
+            errors: self
+                .errors
+                .into_iter()
+                .map(|(input, kind)| (f(input), kind))
+                .collect(),
+        }
+    }
+
+    /// Same error owning copies of the input positions, so it can outlive the input
+    pub fn into_owned(self) -> VerboseError<String>
+    where
+        I: ToString,
+    {
+        self.map_input(|input| input.to_string())
+    }
+
     pub fn add_owned_context(input: I, ctx: String, mut other: Self) -> Self {
         other
             .errors
@@ -135,6 +248,190 @@ impl<I> VerboseError<I> {
     }
 }
 
+/// Run `parser` on the whole input, failing when something is left after it
+pub fn parse_all<'a, O, E, F>(parser: F, input: &'a str) -> Result<O, E>
+where
+    E: ParseError<&'a str>,
+    F: Parser<&'a str, O, E>,
+{
+    nom::combinator::all_consuming(parser)(input)
+        .finish()
+        .map(|(_, output)| output)
+}
+
+/// Same as [`parse_all`], rendering the error with the lines and positions of its frames
+pub fn parse_all_pretty<'a, O, F>(parser: F, input: &'a str) -> Result<O, String>
+where
+    F: Parser<&'a str, O, nom::error::VerboseError<&'a str>>,
+{
+    parse_all(parser, input).map_err(|err| nom::error::convert_error(input, err))
+}
+
+/// Numbers separated by `sep` on a single line, with spaces and tabs around them. A blank `sep`
+/// separates them by blanks instead. The list may be empty or end with a separator, and an
+/// element that isn't a valid number fails with a context naming its position and text
+pub fn number_list<'a, T: FromStr>(
+    sep: &'a str,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<T>, VerboseError<&'a str>> {
+    let blank_sep = sep.trim().is_empty();
+    let boundary = move |c: char| c.is_whitespace() || (!blank_sep && sep.starts_with(c));
+
+    move |input: &'a str| {
+        let (mut input, _) = nom::character::complete::space0(input)?;
+        let mut numbers = Vec::new();
+
+        loop {
+            let (rest, text) = match nom::bytes::complete::take_till1(boundary)(input) {
+                Ok(element) => element,
+                Err(Err::Error(_)) => break,
+                Err(err) => return Err(err),
+            };
+
+            let number = text.parse().map_err(|_| {
+                Err::Failure(VerboseError::add_owned_context(
+                    input,
+                    format!("element {} of the list, {:?}", numbers.len() + 1, text),
+                    VerboseError::from_error_kind(input, ErrorKind::MapRes),
+                ))
+            })?;
+            numbers.push(number);
+
+            let separator = if blank_sep {
+                nom::character::complete::space1(rest)
+            } else {
+                ws(nom::bytes::complete::tag(sep))(rest)
+            };
+            match separator {
+                Ok((after, _)) => input = after,
+                Err(Err::Error(_)) => {
+                    input = rest;
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        let (input, _) = nom::character::complete::space0(input)?;
+        Ok((input, numbers))
+    }
+}
+
+/// What [`unsigned`] accepts besides plain decimal digits, built from [`UnsignedFormat::DECIMAL`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsignedFormat {
+    separators: bool,
+    radix_prefixes: bool,
+    max_digits: Option<usize>,
+}
+
+impl UnsignedFormat {
+    /// Decimal digits only, as many as the type holds
+    pub const DECIMAL: Self = Self {
+        separators: false,
+        radix_prefixes: false,
+        max_digits: None,
+    };
+
+    /// Accept underscores between the digits, like `1_000`
+    pub fn separators(self, allowed: bool) -> Self {
+        Self {
+            separators: allowed,
+            ..self
+        }
+    }
+
+    /// Accept the `0x`, `0o` and `0b` prefixes of hexadecimal, octal and binary numbers
+    pub fn radix_prefixes(self, allowed: bool) -> Self {
+        Self {
+            radix_prefixes: allowed,
+            ..self
+        }
+    }
+
+    /// Fail on numbers with more digits than this, separators and prefix aside
+    pub fn max_digits(self, max: usize) -> Self {
+        Self {
+            max_digits: Some(max),
+            ..self
+        }
+    }
+}
+
+impl Default for UnsignedFormat {
+    fn default() -> Self {
+        Self::DECIMAL
+    }
+}
+
+/// Unsigned number written in the given format. Numbers that don't fit in `T` fail with
+/// `ErrorKind::MapOpt`, and numbers with too many digits with `ErrorKind::TooLarge`
+pub fn unsigned<'a, T, E>(format: UnsignedFormat) -> impl FnMut(&'a str) -> IResult<&'a str, T, E>
+where
+    T: TryFrom<u128>,
+    E: ParseError<&'a str>,
+{
+    move |input: &'a str| {
+        let prefixes = [("0x", 16), ("0o", 8), ("0b", 2)];
+        let (digits, radix) = prefixes
+            .iter()
+            .filter(|_| format.radix_prefixes)
+            .find_map(|&(prefix, radix)| input.strip_prefix(prefix).map(|rest| (rest, radix)))
+            .unwrap_or((input, 10));
+
+        // Separators only count when a digit follows them
+        let mut end = 0;
+        let mut count = 0;
+        for (pos, c) in digits.char_indices() {
+            if c.is_digit(radix) {
+                end = pos + 1;
+                count += 1;
+            } else if !(format.separators && c == '_' && count > 0) {
+                break;
+            }
+        }
+
+        if count == 0 {
+            return Err(Err::Error(E::from_error_kind(input, ErrorKind::Digit)));
+        }
+        if format.max_digits.is_some_and(|max| count > max) {
+            return Err(Err::Error(E::from_error_kind(input, ErrorKind::TooLarge)));
+        }
+
+        let (text, rest) = digits.split_at(end);
+        u128::from_str_radix(&text.replace('_', ""), radix)
+            .ok()
+            .and_then(|value| T::try_from(value).ok())
+            .map(|value| (rest, value))
+            .ok_or_else(|| Err::Error(E::from_error_kind(input, ErrorKind::MapOpt)))
+    }
+}
+
+/// Run `parser` between optional spaces and tabs. Line endings are left alone since they usually
+/// separate records
+pub fn ws<'a, O, E, F>(parser: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
+where
+    E: ParseError<&'a str>,
+    F: Parser<&'a str, O, E>,
+{
+    nom::sequence::delimited(
+        nom::character::complete::space0,
+        parser,
+        nom::character::complete::space0,
+    )
+}
+
+/// Same as [`ws`], also consuming the line ending following the trailing blanks if any
+pub fn ws_line<'a, O, E, F>(parser: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
+where
+    E: ParseError<&'a str>,
+    F: Parser<&'a str, O, E>,
+{
+    nom::sequence::terminated(
+        ws(parser),
+        nom::combinator::opt(nom::character::complete::line_ending),
+    )
+}
+
 pub fn owned_context<I: Clone, F, O>(
     context: String,
     mut f: F,
@@ -157,3 +454,278 @@ where
         ))),
     }
 }
+
+#[cfg(test)]
+type TestResult<'a, O> = IResult<&'a str, O, VerboseError<&'a str>>;
+
+#[cfg(test)]
+fn vowel(input: &str) -> TestResult<'_, &str> {
+    match input.chars().next() {
+        Some(c) if "aeiou".contains(c) => Ok((&input[1..], "vowel")),
+        _ => Err(Err::Error(VerboseError::from_error_kind(
+            input,
+            ErrorKind::OneOf,
+        ))),
+    }
+}
+
+#[test]
+fn test_dyn_alt() {
+    use nom::bytes::complete::tag;
+    use nom::character::complete::digit1;
+
+    let alternatives: BoxedParsers<&str, &str, VerboseError<&str>> =
+        dyn_alt![tag("x"), digit1, vowel];
+    let mut parser = nom::branch::alt(alternatives);
+
+    assert_eq!(parser("xyz"), Ok(("yz", "x")));
+    assert_eq!(parser("42!"), Ok(("!", "42")));
+    assert_eq!(parser("ab"), Ok(("b", "vowel")));
+    assert!(parser("b").is_err());
+}
+
+#[test]
+fn test_dyn_tuple() {
+    use nom::bytes::complete::tag;
+    use nom::character::complete::digit1;
+
+    let rest = |input: &'static str| -> TestResult<'static, &'static str> {
+        Ok((&input[input.len()..], input))
+    };
+    let sequence: BoxedParsers<&str, &str, VerboseError<&str>> = dyn_alt![digit1, tag("-"), rest];
+    let mut parser = nom::sequence::tuple(sequence);
+
+    assert_eq!(parser("12-rest"), Ok(("", vec!["12", "-", "rest"])));
+    assert!(parser("12+rest").is_err());
+}
+
+#[test]
+fn test_dynamic_permutation() {
+    use nom::bytes::complete::tag;
+
+    let mut parser = dynamic_permutation(DynamicAlt::from(vec![tag("a"), tag("b"), tag("c")]));
+
+    assert_eq!(parser("abc!"), Ok(("!", vec!["a", "b", "c"])));
+    assert_eq!(parser("cab"), Ok(("", vec!["a", "b", "c"])));
+    assert_eq!(parser("bca"), Ok(("", vec!["a", "b", "c"])));
+
+    let error = match parser("cbc") {
+        Err(Err::Error(error)) => error,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert!(error
+        .to_string()
+        .contains("in section 'permutation, unmatched parsers [0]', at: c"));
+
+    let error = match parser("b") {
+        Err(Err::Error(error)) => error,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert!(error
+        .to_string()
+        .contains("in section 'permutation, unmatched parsers [0, 2]', at: "));
+}
+
+#[test]
+fn test_dynamic_permutation_first_come() {
+    use nom::bytes::complete::tag;
+    use nom::character::complete::{alpha1, digit1};
+
+    let parsers: BoxedParsers<&str, &str, VerboseError<&str>> = dyn_alt![tag("x"), alpha1, digit1];
+    let mut parser = dynamic_permutation(parsers);
+
+    assert_eq!(parser("xab12"), Ok(("", vec!["x", "ab", "12"])));
+    assert_eq!(parser("ab12x"), Ok(("", vec!["x", "ab", "12"])));
+    assert_eq!(parser("12xab"), Ok(("", vec!["x", "ab", "12"])));
+
+    // `alpha1` takes the `x` as well, even though splitting it off would have matched
+    assert!(parser("abx12").is_err());
+}
+
+#[test]
+fn test_into_owned() {
+    let owned = {
+        let input = String::from("abc");
+        let error = VerboseError::add_context(
+            &input[1..],
+            "letters",
+            VerboseError::from_char(&input[2..], 'x'),
+        );
+        error.into_owned()
+    };
+
+    assert_eq!(
+        owned,
+        VerboseError {
+            errors: vec![
+                ("c".to_owned(), VerboseErrorKind::Char('x')),
+                ("bc".to_owned(), VerboseErrorKind::Context("letters")),
+            ]
+        }
+    );
+    assert_eq!(
+        owned.clone().map_input(|input| input.len()).errors[1],
+        (2, VerboseErrorKind::Context("letters"))
+    );
+
+    let boxed: Box<dyn std::error::Error> = Box::new(owned);
+    assert_eq!(
+        boxed.to_string(),
+        "Parse error:\nexpected 'x' at: c\nin section 'letters', at: bc\n"
+    );
+}
+
+#[test]
+fn test_frames() {
+    let mut parser = nom::error::context(
+        "outer",
+        owned_context(
+            "middle".to_owned(),
+            nom::sequence::preceded(nom::character::complete::char('a'), vowel),
+        ),
+    );
+
+    let error = match parser("ab") {
+        Err(nom::Err::Error(error)) => error,
+        other => panic!("unexpected result {:?}", other),
+    };
+
+    assert_eq!(
+        error.frames().collect::<Vec<_>>(),
+        vec![
+            (&"b", &VerboseErrorKind::Nom(ErrorKind::OneOf)),
+            (&"ab", &VerboseErrorKind::OwnedContext("middle".to_owned())),
+            (&"ab", &VerboseErrorKind::Context("outer")),
+        ]
+    );
+    assert_eq!(
+        error.deepest(),
+        Some(&("b", VerboseErrorKind::Nom(ErrorKind::OneOf)))
+    );
+    assert_eq!(
+        error.contexts().collect::<Vec<_>>(),
+        vec!["middle", "outer"]
+    );
+
+    assert_eq!(VerboseError::<&str>::default().deepest(), None);
+}
+
+#[test]
+fn test_ws() {
+    let mut word = ws(nom::character::complete::alpha1::<_, VerboseError<&str>>);
+
+    assert_eq!(word("abc"), Ok(("", "abc")));
+    assert_eq!(word(" \t abc\t  def"), Ok(("def", "abc")));
+    assert_eq!(word("abc \ndef"), Ok(("\ndef", "abc")));
+    assert!(word("\nabc").is_err());
+
+    let mut line = ws_line(nom::character::complete::alpha1::<_, VerboseError<&str>>);
+
+    assert_eq!(line("abc \ndef"), Ok(("def", "abc")));
+    assert_eq!(line("abc\t\r\ndef"), Ok(("def", "abc")));
+    assert_eq!(line("abc\n\ndef"), Ok(("\ndef", "abc")));
+    assert_eq!(line(" abc"), Ok(("", "abc")));
+}
+
+#[test]
+fn test_parse_all() {
+    assert_eq!(
+        parse_all(
+            nom::bytes::complete::tag::<_, _, VerboseError<&str>>("ab"),
+            "ab"
+        ),
+        Ok("ab")
+    );
+
+    let error = parse_all(
+        nom::bytes::complete::tag::<_, _, VerboseError<&str>>("ab"),
+        "abc",
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.deepest(),
+        Some(&("c", VerboseErrorKind::Nom(ErrorKind::Eof)))
+    );
+    assert_eq!(error.to_string(), "Parse error:\nEof at: c\n");
+
+    assert_eq!(
+        parse_all_pretty(
+            nom::error::context("letters", nom::character::complete::alpha1),
+            "ab1"
+        ),
+        Err("0: at line 1, in Eof:\nab1\n  ^\n\n".to_owned())
+    );
+}
+
+#[test]
+fn test_number_list() {
+    let mut commas = number_list::<u32>(",");
+
+    assert_eq!(commas("0,3,6"), Ok(("", vec![0, 3, 6])));
+    assert_eq!(commas(" 1 ,\t2 ,3 \nrest"), Ok(("\nrest", vec![1, 2, 3])));
+    assert_eq!(commas("7,1,"), Ok(("", vec![7, 1])));
+    assert_eq!(commas(""), Ok(("", vec![])));
+    assert_eq!(commas("\n1"), Ok(("\n1", vec![])));
+
+    let error = match commas("1,x,3") {
+        Err(Err::Failure(error)) => error,
+        other => panic!("unexpected result {:?}", other),
+    };
+    assert_eq!(
+        error.contexts().collect::<Vec<_>>(),
+        ["element 2 of the list, \"x\""]
+    );
+    assert_eq!(
+        error.deepest(),
+        Some(&("x,3", VerboseErrorKind::Nom(ErrorKind::MapRes)))
+    );
+
+    let mut blanks = number_list::<i64>(" ");
+    assert_eq!(blanks("  4 -5\t6  "), Ok(("", vec![4, -5, 6])));
+    assert_eq!(blanks("4 5\n6"), Ok(("\n6", vec![4, 5])));
+}
+
+#[test]
+fn test_unsigned() {
+    fn parse<T: TryFrom<u128>>(format: UnsignedFormat, input: &str) -> TestResult<'_, T> {
+        unsigned(format)(input)
+    }
+    let kind = |result: TestResult<'_, u32>| match result {
+        Err(Err::Error(error)) => error.deepest().map(|(_, kind)| kind.clone()),
+        other => panic!("unexpected result {:?}", other),
+    };
+    let decimal = UnsignedFormat::DECIMAL;
+
+    assert_eq!(parse::<u32>(decimal, "42 + 1"), Ok((" + 1", 42)));
+    assert_eq!(parse::<u32>(decimal, "1_000"), Ok(("_000", 1)));
+    assert_eq!(parse::<u32>(decimal, "0x1f"), Ok(("x1f", 0)));
+    assert_eq!(
+        kind(parse(decimal, "x")),
+        Some(VerboseErrorKind::Nom(ErrorKind::Digit))
+    );
+    assert_eq!(
+        kind(parse(decimal, "4294967296")),
+        Some(VerboseErrorKind::Nom(ErrorKind::MapOpt))
+    );
+
+    let separated = decimal.separators(true);
+    assert_eq!(parse::<u32>(separated, "1_000_000"), Ok(("", 1_000_000)));
+    assert_eq!(parse::<u32>(separated, "1__0_"), Ok(("_", 10)));
+    assert_eq!(
+        kind(parse(separated, "_1")),
+        Some(VerboseErrorKind::Nom(ErrorKind::Digit))
+    );
+
+    let prefixed = decimal.radix_prefixes(true);
+    assert_eq!(parse::<u32>(prefixed, "0x1f"), Ok(("", 31)));
+    assert_eq!(parse::<u32>(prefixed, "0o17"), Ok(("", 15)));
+    assert_eq!(parse::<u32>(prefixed, "0b102"), Ok(("2", 2)));
+    assert_eq!(parse::<u32>(prefixed, "017"), Ok(("", 17)));
+
+    let short = UnsignedFormat::default().separators(true).max_digits(3);
+    assert_eq!(parse::<u32>(short, "9_99"), Ok(("", 999)));
+    assert_eq!(
+        kind(parse(short, "1_000")),
+        Some(VerboseErrorKind::Nom(ErrorKind::TooLarge))
+    );
+}
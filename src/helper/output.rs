@@ -0,0 +1,87 @@
+use std::cell::RefCell;
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+
+/// Buffer lines are captured into, shareable with the thread running a day so what it printed
+/// can be read even if it never finishes
+pub type SharedBuffer = Arc<Mutex<String>>;
+
+thread_local! {
+    /// Buffer of the day running on this thread, if its output is captured
+    static BUFFER: RefCell<Option<SharedBuffer>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with everything it prints through [`print_info!`] and [`print_answer!`] kept in a
+/// buffer instead of going to stdout, and return that buffer along with the result. Captures can
+/// be nested, the inner one taking the lines printed while it runs
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let buffer = SharedBuffer::default();
+    let result = capture_into(&buffer, f);
+    let captured = std::mem::take(&mut *buffer.lock().unwrap());
+
+    (result, captured)
+}
+
+/// Same as [`capture`], appending the lines to a buffer that other threads can read meanwhile
+pub fn capture_into<T>(buffer: &SharedBuffer, f: impl FnOnce() -> T) -> T {
+    let outer = BUFFER.with(|current| current.replace(Some(Arc::clone(buffer))));
+    let result = f();
+    BUFFER.with(|current| current.replace(outer));
+
+    result
+}
+
+/// Print a line to the capture of the current thread, or to stdout outside of any capture
+pub fn line(args: fmt::Arguments<'_>) {
+    BUFFER.with(|buffer| match &*buffer.borrow() {
+        Some(buffer) => writeln!(buffer.lock().unwrap(), "{}", args).unwrap(),
+        None => println!("{}", args),
+    })
+}
+
+/// Print details about how a day is solved, see [`capture`]
+#[macro_export]
+macro_rules! print_info {
+    ($($arg:tt)*) => {
+        $crate::helper::output::line(format_args!($($arg)*))
+    };
+}
+
+/// Print an intermediate answer of a day as `label: value`, see [`capture`]
+#[macro_export]
+macro_rules! print_answer {
+    ($label:expr, $value:expr $(,)?) => {
+        $crate::helper::output::line(format_args!("{}: {}", $label, $value))
+    };
+}
+
+#[test]
+fn test_capture() {
+    let (result, captured) = capture(|| {
+        print_info!("solving {} lines", 3);
+        let ((), inner) = capture(|| print_answer!("turn 2020", 436));
+        assert_eq!(inner, "turn 2020: 436\n");
+        print_info!("done");
+        42
+    });
+
+    assert_eq!(result, 42);
+    assert_eq!(captured, "solving 3 lines\ndone\n");
+
+    // Each thread has its own capture
+    let ((), captured) = capture(|| {
+        std::thread::spawn(|| capture(|| print_info!("other thread")).1)
+            .join()
+            .map(|other| assert_eq!(other, "other thread\n"))
+            .unwrap()
+    });
+    assert_eq!(captured, "");
+
+    let shared = SharedBuffer::default();
+    let worker = {
+        let shared = Arc::clone(&shared);
+        std::thread::spawn(move || capture_into(&shared, || print_info!("from the worker")))
+    };
+    worker.join().unwrap();
+    assert_eq!(*shared.lock().unwrap(), "from the worker\n");
+}
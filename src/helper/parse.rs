@@ -0,0 +1,280 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Split the input into blocks of consecutive lines separated by blank lines.
+///
+/// Blank lines are the ones with only whitespace, and any number of them separate two blocks.
+/// The blocks don't include their trailing line ending.
+pub fn blocks(input: &str) -> Blocks<'_> {
+    Blocks { rest: input }
+}
+
+pub struct Blocks<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Blocks<'a> {
+    /// End of the line starting at `start`, including its line ending
+    fn line_end(&self, start: usize) -> usize {
+        self.rest[start..]
+            .find('\n')
+            .map_or(self.rest.len(), |pos| start + pos + 1)
+    }
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Skip the blank lines before the block
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+
+            let line_end = self.line_end(0);
+            if !self.rest[..line_end].trim().is_empty() {
+                break;
+            }
+            self.rest = &self.rest[line_end..];
+        }
+
+        let mut block_end = 0;
+        while block_end < self.rest.len() {
+            let line_end = self.line_end(block_end);
+            if self.rest[block_end..line_end].trim().is_empty() {
+                break;
+            }
+            block_end = line_end;
+        }
+
+        let block = self.rest[..block_end].trim_end_matches(['\n', '\r']);
+        self.rest = &self.rest[block_end..];
+
+        Some(block)
+    }
+}
+
+/// Why a `key<sep>value` line couldn't be split
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KvError {
+    MissingSeparator { fragment: String, separator: String },
+    EmptyKey { fragment: String },
+    DuplicateKey { key: String },
+}
+
+impl fmt::Display for KvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KvError::MissingSeparator {
+                fragment,
+                separator,
+            } => write!(f, "{:?} has no {:?} separator", fragment, separator),
+            KvError::EmptyKey { fragment } => write!(f, "{:?} has no key", fragment),
+            KvError::DuplicateKey { key } => write!(f, "{:?} is defined more than once", key),
+        }
+    }
+}
+
+impl std::error::Error for KvError {}
+
+/// What `key_value_map` does with a key that appears again
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Duplicates {
+    Error,
+    LastWins,
+}
+
+/// Split a line on the first `sep`, trimming the whitespace around the key and the value
+pub fn key_value<'a>(line: &'a str, sep: &str) -> Result<(&'a str, &'a str), KvError> {
+    let (key, value) = line
+        .split_once(sep)
+        .ok_or_else(|| KvError::MissingSeparator {
+            fragment: line.to_owned(),
+            separator: sep.to_owned(),
+        })?;
+
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(KvError::EmptyKey {
+            fragment: line.to_owned(),
+        });
+    }
+
+    Ok((key, value.trim()))
+}
+
+/// Pairs of every non-blank line of a block, split with [`key_value`]
+pub fn key_value_map<'a>(
+    block: &'a str,
+    sep: &str,
+    duplicates: Duplicates,
+) -> Result<HashMap<&'a str, &'a str>, KvError> {
+    let mut map = HashMap::new();
+
+    for line in block.lines().filter(|line| !line.trim().is_empty()) {
+        let (key, value) = key_value(line, sep)?;
+
+        match map.entry(key) {
+            Entry::Occupied(_) if duplicates == Duplicates::Error => {
+                return Err(KvError::DuplicateKey {
+                    key: key.to_owned(),
+                })
+            }
+            Entry::Occupied(mut entry) => {
+                entry.insert(value);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+        }
+    }
+
+    Ok(map)
+}
+
+/// First element of a list that isn't a valid number
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumbersError {
+    /// Position of the element in the list, from 0
+    pub index: usize,
+    pub text: String,
+}
+
+impl fmt::Display for NumbersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "element {} of the list, {:?}, is not a valid number",
+            self.index + 1,
+            self.text
+        )
+    }
+}
+
+impl std::error::Error for NumbersError {}
+
+/// Numbers separated by `sep`, with any whitespace around them. A blank `sep` splits on runs of
+/// whitespace instead. A blank input is an empty list, and a single trailing separator is allowed
+pub fn numbers<T: FromStr>(input: &str, sep: &str) -> Result<Vec<T>, NumbersError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let elements: Vec<_> = if sep.trim().is_empty() {
+        input.split_whitespace().collect()
+    } else {
+        let mut elements: Vec<_> = input.split(sep).map(str::trim).collect();
+        if elements.len() > 1 && elements.last() == Some(&"") {
+            elements.pop();
+        }
+        elements
+    };
+
+    elements
+        .into_iter()
+        .enumerate()
+        .map(|(index, text)| {
+            text.parse().map_err(|_| NumbersError {
+                index,
+                text: text.to_owned(),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_blocks() {
+    let input = "a b\nc\n\nd\n\n\n  \ne\nf\n";
+    assert_eq!(blocks(input).collect::<Vec<_>>(), ["a b\nc", "d", "e\nf"]);
+
+    let input = "\n\na\r\n\r\nb";
+    assert_eq!(blocks(input).collect::<Vec<_>>(), ["a", "b"]);
+
+    assert_eq!(blocks("").count(), 0);
+    assert_eq!(blocks("\n \n").count(), 0);
+}
+
+#[test]
+fn test_key_value() {
+    assert_eq!(key_value("mask = X10", "="), Ok(("mask", "X10")));
+    assert_eq!(
+        key_value("  class:  1-3 or 5-7 ", ":"),
+        Ok(("class", "1-3 or 5-7"))
+    );
+    assert_eq!(key_value("a: b: c", ": "), Ok(("a", "b: c")));
+    assert_eq!(key_value("empty: ", ":"), Ok(("empty", "")));
+
+    let missing = key_value("class 1-3", ": ").unwrap_err();
+    assert_eq!(
+        missing,
+        KvError::MissingSeparator {
+            fragment: "class 1-3".to_owned(),
+            separator: ": ".to_owned(),
+        }
+    );
+    assert_eq!(missing.to_string(), "\"class 1-3\" has no \": \" separator");
+
+    assert_eq!(
+        key_value(" = 3", "=").unwrap_err().to_string(),
+        "\" = 3\" has no key"
+    );
+}
+
+#[test]
+fn test_key_value_map() {
+    let block = "a=1\n b = 2\n\na=3\n";
+
+    let map = key_value_map(block, "=", Duplicates::LastWins).unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["a"], "3");
+    assert_eq!(map["b"], "2");
+
+    assert_eq!(
+        key_value_map(block, "=", Duplicates::Error),
+        Err(KvError::DuplicateKey {
+            key: "a".to_owned()
+        })
+    );
+    assert_eq!(
+        key_value_map("a=1\nb", "=", Duplicates::LastWins)
+            .unwrap_err()
+            .to_string(),
+        "\"b\" has no \"=\" separator"
+    );
+    assert!(key_value_map("", "=", Duplicates::Error)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_numbers() {
+    assert_eq!(numbers::<u32>("0,3,6", ","), Ok(vec![0, 3, 6]));
+    assert_eq!(numbers::<i32>(" 1 , -2,3 \n", ","), Ok(vec![1, -2, 3]));
+    assert_eq!(numbers::<u32>("7,1,", ","), Ok(vec![7, 1]));
+    assert_eq!(numbers::<u32>("  4 5\t6  ", " "), Ok(vec![4, 5, 6]));
+    assert_eq!(numbers::<u32>("", ","), Ok(vec![]));
+    assert_eq!(numbers::<u32>(" \n", " "), Ok(vec![]));
+
+    assert_eq!(
+        numbers::<u32>("1,,2", ","),
+        Err(NumbersError {
+            index: 1,
+            text: "".to_owned()
+        })
+    );
+    assert_eq!(
+        numbers::<u8>("1,2,300", ",").unwrap_err().to_string(),
+        "element 3 of the list, \"300\", is not a valid number"
+    );
+    assert_eq!(
+        numbers::<u32>(",", ",").unwrap_err(),
+        NumbersError {
+            index: 0,
+            text: "".to_owned()
+        }
+    );
+}
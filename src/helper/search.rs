@@ -0,0 +1,256 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Path found by a search, from the start to the goal both included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N, C> {
+    pub nodes: Vec<N>,
+    /// Sum of the costs of the steps, the number of steps for unweighted searches
+    pub cost: C,
+}
+
+impl<N: Hash + Eq + Clone, C> Path<N, C> {
+    /// Walk the parents back from the goal to the start
+    fn rebuild(parents: &HashMap<N, N>, goal: N, cost: C) -> Self {
+        let mut nodes = vec![goal];
+        while let Some(parent) = parents.get(nodes.last().unwrap()) {
+            nodes.push(parent.clone());
+        }
+        nodes.reverse();
+
+        Self { nodes, cost }
+    }
+}
+
+/// Shortest path in steps from `start` to the first node satisfying `goal`
+pub fn bfs<N, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, usize>>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents = HashMap::new();
+    let mut visited: HashSet<_> = [start.clone()].iter().cloned().collect();
+    let mut queue: VecDeque<_> = [(start, 0)].iter().cloned().collect();
+
+    while let Some((node, steps)) = queue.pop_front() {
+        if goal(&node) {
+            return Some(Path::rebuild(&parents, node, steps));
+        }
+
+        for next in neighbors(&node) {
+            if visited.insert(next.clone()) {
+                parents.insert(next.clone(), node.clone());
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+
+    None
+}
+
+/// Node waiting in the queue of `dijkstra`, ordered by cost only
+struct Candidate<N, C> {
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for Candidate<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl<N, C: Ord> Eq for Candidate<N, C> {}
+
+impl<N, C: Ord> PartialOrd for Candidate<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for Candidate<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cost.cmp(&other.cost)
+    }
+}
+
+/// Cheapest path from `start` to the first node satisfying `goal`, where `neighbors` gives the
+/// cost of each step. Costs start at `C::default()` and must not be negative
+pub fn dijkstra<N, C, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Hash + Eq + Clone,
+    C: Ord + Copy + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut parents = HashMap::new();
+    let mut best: HashMap<_, _> = [(start.clone(), C::default())].iter().cloned().collect();
+    let mut queue = BinaryHeap::new();
+    queue.push(Reverse(Candidate {
+        cost: C::default(),
+        node: start,
+    }));
+
+    while let Some(Reverse(Candidate { cost, node })) = queue.pop() {
+        if best.get(&node).is_some_and(|&known| known < cost) {
+            // Reached again through a cheaper path after being queued
+            continue;
+        }
+
+        if goal(&node) {
+            return Some(Path::rebuild(&parents, node, cost));
+        }
+
+        for (next, step) in neighbors(&node) {
+            let next_cost = cost + step;
+
+            match best.entry(next.clone()) {
+                Entry::Occupied(entry) if *entry.get() <= next_cost => continue,
+                Entry::Occupied(mut entry) => {
+                    entry.insert(next_cost);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(next_cost);
+                }
+            }
+
+            parents.insert(next.clone(), node.clone());
+            queue.push(Reverse(Candidate {
+                cost: next_cost,
+                node: next,
+            }));
+        }
+    }
+
+    None
+}
+
+/// Nodes reachable from `start` in one step or more, so `start` is only included when it is on a
+/// cycle
+pub fn reachable<N, I>(start: N, mut neighbors: impl FnMut(&N) -> I) -> HashSet<N>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut visited = HashSet::new();
+    let mut queue: VecDeque<_> = [start].iter().cloned().collect();
+
+    while let Some(node) = queue.pop_front() {
+        for next in neighbors(&node) {
+            if visited.insert(next.clone()) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    visited
+}
+
+#[cfg(test)]
+fn graph() -> HashMap<char, Vec<(char, u32)>> {
+    // a -1-> b -1-> c -1-> d
+    //  \----------5-------/
+    // e is isolated, and f loops onto itself
+    [
+        ('a', vec![('b', 1), ('d', 5)]),
+        ('b', vec![('c', 1)]),
+        ('c', vec![('d', 1)]),
+        ('d', vec![]),
+        ('e', vec![]),
+        ('f', vec![('f', 1)]),
+    ]
+    .iter()
+    .cloned()
+    .collect()
+}
+
+#[test]
+fn test_bfs() {
+    let graph = graph();
+    let neighbors = |node: &char| {
+        graph[node]
+            .iter()
+            .map(|&(next, _)| next)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        bfs('a', neighbors, |&node| node == 'd'),
+        Some(Path {
+            nodes: vec!['a', 'd'],
+            cost: 1
+        })
+    );
+    assert_eq!(
+        bfs('a', neighbors, |&node| node == 'c'),
+        Some(Path {
+            nodes: vec!['a', 'b', 'c'],
+            cost: 2
+        })
+    );
+    assert_eq!(
+        bfs('a', neighbors, |&node| node == 'a'),
+        Some(Path {
+            nodes: vec!['a'],
+            cost: 0
+        })
+    );
+    assert_eq!(bfs('a', neighbors, |&node| node == 'e'), None);
+}
+
+#[test]
+fn test_dijkstra() {
+    let graph = graph();
+    let neighbors = |node: &char| graph[node].clone();
+
+    assert_eq!(
+        dijkstra('a', neighbors, |&node| node == 'd'),
+        Some(Path {
+            nodes: vec!['a', 'b', 'c', 'd'],
+            cost: 3
+        })
+    );
+    assert_eq!(dijkstra('b', neighbors, |&node| node == 'a'), None);
+
+    // Numbers reached by adding one for one or doubling for three
+    let path = dijkstra(
+        1u32,
+        |&n| {
+            vec![(n + 1, 1u32), (n * 2, 3)]
+                .into_iter()
+                .filter(|&(n, _)| n <= 20)
+        },
+        |&n| n == 20,
+    )
+    .unwrap();
+    assert_eq!(path.nodes, [1, 2, 3, 4, 5, 10, 20]);
+    assert_eq!(path.cost, 10);
+}
+
+#[test]
+fn test_reachable() {
+    let graph = graph();
+    let neighbors = |node: &char| {
+        graph[node]
+            .iter()
+            .map(|&(next, _)| next)
+            .collect::<Vec<_>>()
+    };
+
+    let mut from_a: Vec<_> = reachable('a', neighbors).into_iter().collect();
+    from_a.sort_unstable();
+    assert_eq!(from_a, ['b', 'c', 'd']);
+
+    assert!(reachable('e', neighbors).is_empty());
+    assert_eq!(reachable('f', neighbors), ['f'].iter().copied().collect());
+}
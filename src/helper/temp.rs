@@ -0,0 +1,65 @@
+//! Folders of the tests in the temporary directory, removed once they are done with
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Folders created so far by the process, numbering them apart when a name is used twice
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Empty folder named after a test, the current process and a count of the folders it created,
+/// so that concurrent tests and runs don't share it. It is removed with its content when dropped, even when the test panics
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Create the folder, replacing the one a previous run may have left behind
+    pub fn new(name: &str) -> Self {
+        let count = CREATED.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("{}-{}-{}", name, std::process::id(), count));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path)
+            .unwrap_or_else(|err| panic!("could not create {}: {}", path.display(), err));
+
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+#[test]
+fn test_temp_dir() {
+    let path = {
+        let dir = TempDir::new("temp-dir");
+        std::fs::write(dir.join("file.txt"), "content").unwrap();
+        assert!(dir.path().join("file.txt").is_file());
+        assert_ne!(dir.path(), TempDir::new("temp-dir").path());
+        dir.path().to_owned()
+    };
+
+    assert!(!path.exists());
+}
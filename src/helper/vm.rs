@@ -0,0 +1,190 @@
+//! The handheld game console from 2020 day 8: a tiny machine with an accumulator and a program
+//! counter, running one instruction per line.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    /// Add the argument to the accumulator
+    Acc(i64),
+    /// Jump relative to the current instruction
+    Jmp(i64),
+    /// Do nothing
+    Nop(i64),
+}
+
+impl FromStr for Instruction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (operation, argument) = s
+            .split_once(' ')
+            .ok_or_else(|| format!("missing argument in '{}'", s))?;
+
+        let argument = argument
+            .parse()
+            .map_err(|_| format!("invalid argument in '{}'", s))?;
+
+        match operation {
+            "acc" => Ok(Instruction::Acc(argument)),
+            "jmp" => Ok(Instruction::Jmp(argument)),
+            "nop" => Ok(Instruction::Nop(argument)),
+            _ => Err(format!("unknown operation in '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Acc(arg) => write!(f, "acc {:+}", arg),
+            Instruction::Jmp(arg) => write!(f, "jmp {:+}", arg),
+            Instruction::Nop(arg) => write!(f, "nop {:+}", arg),
+        }
+    }
+}
+
+/// Parse a program with one instruction per line
+pub fn parse_program(input: &str) -> Result<Vec<Instruction>, String> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(line, text)| {
+            text.parse()
+                .map_err(|err| format!("line {}: {}", line + 1, err))
+        })
+        .collect()
+}
+
+/// Why a machine stopped running
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Exit {
+    /// The program counter is right after the last instruction
+    Halted,
+    /// The next instruction was already executed, so the program would run forever
+    Loop,
+    /// A jump moved the program counter outside of the program
+    OutOfBounds(i64),
+}
+
+pub struct Machine<'a> {
+    program: &'a [Instruction],
+    pc: i64,
+    accumulator: i64,
+    /// Instructions executed so far
+    visited: Vec<bool>,
+}
+
+impl<'a> Machine<'a> {
+    pub fn new(program: &'a [Instruction]) -> Self {
+        Self {
+            program,
+            pc: 0,
+            accumulator: 0,
+            visited: vec![false; program.len()],
+        }
+    }
+
+    pub fn accumulator(&self) -> i64 {
+        self.accumulator
+    }
+
+    pub fn pc(&self) -> i64 {
+        self.pc
+    }
+
+    /// Execute the next instruction, or return why it can't be executed
+    pub fn step(&mut self) -> Result<(), Exit> {
+        let len = self.program.len() as i64;
+        if self.pc == len {
+            return Err(Exit::Halted);
+        }
+        if self.pc < 0 || self.pc > len {
+            return Err(Exit::OutOfBounds(self.pc));
+        }
+
+        let index = self.pc as usize;
+        if self.visited[index] {
+            return Err(Exit::Loop);
+        }
+        self.visited[index] = true;
+
+        match self.program[index] {
+            Instruction::Acc(arg) => {
+                self.accumulator += arg;
+                self.pc += 1;
+            }
+            Instruction::Jmp(arg) => self.pc += arg,
+            Instruction::Nop(_) => self.pc += 1,
+        }
+
+        Ok(())
+    }
+
+    /// Run until the program halts or an instruction is about to run a second time
+    pub fn run_until_loop_or_halt(&mut self) -> Exit {
+        loop {
+            if let Err(exit) = self.step() {
+                return exit;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse_program() {
+    assert_eq!(
+        parse_program("nop +0\nacc -12\njmp +4\n"),
+        Ok(vec![
+            Instruction::Nop(0),
+            Instruction::Acc(-12),
+            Instruction::Jmp(4)
+        ])
+    );
+    assert_eq!(
+        parse_program("nop +0\nmul +2"),
+        Err("line 2: unknown operation in 'mul +2'".to_owned())
+    );
+    assert_eq!(
+        parse_program("acc"),
+        Err("line 1: missing argument in 'acc'".to_owned())
+    );
+    assert_eq!(Instruction::Acc(-3).to_string(), "acc -3");
+    assert_eq!(Instruction::Jmp(3).to_string(), "jmp +3");
+}
+
+#[test]
+fn test_machine_step() {
+    let program = parse_program("acc +2\njmp +2\nacc +100\nacc -1").unwrap();
+    let mut machine = Machine::new(&program);
+
+    assert_eq!(machine.step(), Ok(()));
+    assert_eq!((machine.pc(), machine.accumulator()), (1, 2));
+    assert_eq!(machine.step(), Ok(()));
+    assert_eq!((machine.pc(), machine.accumulator()), (3, 2));
+    assert_eq!(machine.step(), Ok(()));
+    assert_eq!((machine.pc(), machine.accumulator()), (4, 1));
+    assert_eq!(machine.step(), Err(Exit::Halted));
+    // A stopped machine stays stopped
+    assert_eq!(machine.step(), Err(Exit::Halted));
+}
+
+#[test]
+fn test_machine_exits() {
+    let program = parse_program("acc +1\njmp -1").unwrap();
+    let mut machine = Machine::new(&program);
+    assert_eq!(machine.run_until_loop_or_halt(), Exit::Loop);
+    assert_eq!(machine.accumulator(), 1);
+
+    let program = parse_program("nop +0\njmp -2").unwrap();
+    let mut machine = Machine::new(&program);
+    assert_eq!(machine.run_until_loop_or_halt(), Exit::OutOfBounds(-1));
+
+    let program = parse_program("jmp +3\nacc +1").unwrap();
+    let mut machine = Machine::new(&program);
+    assert_eq!(machine.run_until_loop_or_halt(), Exit::OutOfBounds(3));
+
+    let mut machine = Machine::new(&[]);
+    assert_eq!(machine.run_until_loop_or_halt(), Exit::Halted);
+}
@@ -0,0 +1,808 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::ErrorKind;
+use std::io::{Read, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "mmap")]
+use std::sync::Arc;
+
+#[cfg(test)]
+use crate::helper::temp::TempDir;
+use crate::time;
+
+/// Source of the input files of the days
+pub trait InputProvider {
+    /// Content of the input `name` of a day
+    fn fetch(&self, year: u16, day: u8, name: &str) -> Result<String, InputError>;
+
+    /// Same as `fetch`, for the runner, which can solve inputs it doesn't own. Providers reading
+    /// files map the large ones with the `mmap` feature instead of reading them
+    fn fetch_content(&self, year: u16, day: u8, name: &str) -> Result<InputContent, InputError> {
+        self.fetch(year, day, name).map(InputContent::Read)
+    }
+}
+
+impl<P: InputProvider + ?Sized> InputProvider for &P {
+    fn fetch(&self, year: u16, day: u8, name: &str) -> Result<String, InputError> {
+        (**self).fetch(year, day, name)
+    }
+
+    fn fetch_content(&self, year: u16, day: u8, name: &str) -> Result<InputContent, InputError> {
+        (**self).fetch_content(year, day, name)
+    }
+}
+
+/// Text of an input, read into a `String` or, with the `mmap` feature, memory-mapped. Either way
+/// it derefs to the `&str` the days can borrow, see `Advent::solve_borrowed`
+#[derive(Debug, Clone)]
+pub enum InputContent {
+    Read(String),
+    /// Checked to be UTF-8 once when mapped, see [`map_input`]. Shared so that `--repeat` solves
+    /// the same mapping again
+    #[cfg(feature = "mmap")]
+    Mapped(Arc<memmap2::Mmap>),
+}
+
+impl InputContent {
+    /// The text as a `String`, only copied when it is mapped
+    pub fn into_string(self) -> String {
+        match self {
+            InputContent::Read(content) => content,
+            #[cfg(feature = "mmap")]
+            InputContent::Mapped(_) => String::from(&*self),
+        }
+    }
+}
+
+impl Deref for InputContent {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            InputContent::Read(content) => content,
+            // Safety: `map_input` only maps files whose content is valid UTF-8
+            #[cfg(feature = "mmap")]
+            InputContent::Mapped(map) => unsafe { std::str::from_utf8_unchecked(map) },
+        }
+    }
+}
+
+impl From<String> for InputContent {
+    fn from(content: String) -> Self {
+        InputContent::Read(content)
+    }
+}
+
+/// Why an input couldn't be provided
+#[derive(Debug)]
+pub enum InputError {
+    /// The source has no such input
+    NotFound { location: String },
+    /// The input exists but reading it failed
+    Io {
+        location: String,
+        source: std::io::Error,
+    },
+    /// Downloading the input failed
+    Http { url: String, message: String },
+    /// The puzzle of the day has not unlocked yet, see [`time::locked_message`]
+    Locked { message: String },
+    /// The content to save doesn't look like an input
+    Rejected { location: String, reason: String },
+    /// The input was missing and the user chose not to give it, see [`FsProvider::asking_missing`]
+    Cancelled { location: String },
+    /// A compressed input isn't valid gzip data
+    Corrupt {
+        location: String,
+        source: std::io::Error,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::NotFound { location } => write!(f, "{} does not exist", location),
+            InputError::Io { location, source } => match source.kind() {
+                ErrorKind::PermissionDenied => {
+                    write!(f, "not allowed to read {}, check its permissions", location)
+                }
+                ErrorKind::IsADirectory => {
+                    write!(f, "{} is a directory instead of an input file", location)
+                }
+                kind => write!(f, "could not read {} ({:?}): {}", location, kind, source),
+            },
+            InputError::Http { url, message } => {
+                write!(f, "could not download {}: {}", url, message)
+            }
+            InputError::Locked { message } => {
+                write!(f, "{}, its input can't be downloaded yet", message)
+            }
+            InputError::Corrupt { location, source } => {
+                write!(f, "{} is not valid gzip data: {}", location, source)
+            }
+            InputError::Rejected { location, reason } => {
+                write!(f, "refusing to save {}: {}", location, reason)
+            }
+            InputError::Cancelled { location } => {
+                write!(f, "{} is missing and was not pasted", location)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Asks for the content of a missing input at a path, `None` when it is not given
+pub type AskInput = Box<dyn Fn(&Path) -> Option<String>>;
+
+/// Inputs read from `<root>/<year>/<day>/<name>`, or decompressed from `<name>.gz` next to it
+/// when the plain file is missing
+pub struct FsProvider {
+    root: PathBuf,
+    ask_missing: Option<AskInput>,
+}
+
+impl FsProvider {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            ask_missing: None,
+        }
+    }
+
+    /// Ask for the missing inputs instead of failing, saving what `ask` returns with
+    /// [`write_input`]. When it returns `None` the input is [`InputError::Cancelled`]
+    pub fn asking_missing(mut self, ask: impl Fn(&Path) -> Option<String> + 'static) -> Self {
+        self.ask_missing = Some(Box::new(ask));
+        self
+    }
+
+    /// Folder holding the input files of a day
+    pub fn folder(&self, year: u16, day: u8) -> PathBuf {
+        self.root.join(year.to_string()).join(day.to_string())
+    }
+
+    pub fn path(&self, year: u16, day: u8, name: &str) -> PathBuf {
+        self.folder(year, day).join(name)
+    }
+}
+
+/// Path to show in errors, resolved since the data folder is often given relatively
+fn location(path: &Path) -> String {
+    std::path::absolute(path)
+        .unwrap_or_else(|_| path.to_owned())
+        .display()
+        .to_string()
+}
+
+/// Inputs at least this large are memory-mapped with the `mmap` feature, the smaller ones are
+/// faster to read
+pub const MMAP_THRESHOLD: u64 = 1 << 20;
+
+/// Map an input file of at least [`MMAP_THRESHOLD`] bytes, checking its content is UTF-8.
+/// `None` when the file is smaller, missing or not a regular file, it is then read as usual
+///
+/// The inputs must not be edited in place while they are solved, a mapped file changing
+/// underneath the days is undefined behavior. The runner only replaces them with a rename, see
+/// [`write_input`]
+#[cfg(feature = "mmap")]
+fn map_input(path: &Path) -> Result<Option<memmap2::Mmap>, InputError> {
+    let io_error = |source| InputError::Io {
+        location: location(path),
+        source,
+    };
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(io_error(err)),
+    };
+    let metadata = file.metadata().map_err(io_error)?;
+    if !metadata.is_file() || metadata.len() < MMAP_THRESHOLD {
+        return Ok(None);
+    }
+
+    // Safety: see above, the file is not expected to change while it is mapped
+    let map = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error)?;
+    std::str::from_utf8(&map)
+        .map_err(|err| io_error(std::io::Error::new(ErrorKind::InvalidData, err)))?;
+
+    Ok(Some(map))
+}
+
+/// Content of a gzip-compressed input, `None` when there is no such file
+fn read_compressed(path: &Path) -> Result<Option<String>, InputError> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(source) => {
+            return Err(InputError::Io {
+                location: location(path),
+                source,
+            })
+        }
+    };
+
+    let mut content = String::new();
+    flate2::read::GzDecoder::new(file)
+        .read_to_string(&mut content)
+        .map_err(|source| InputError::Corrupt {
+            location: location(path),
+            source,
+        })?;
+
+    Ok(Some(content))
+}
+
+impl InputProvider for FsProvider {
+    fn fetch(&self, year: u16, day: u8, name: &str) -> Result<String, InputError> {
+        let path = self.path(year, day, name);
+        let io_error = |source| InputError::Io {
+            location: location(&path),
+            source,
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Ok(content),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                if let Some(content) =
+                    read_compressed(&self.path(year, day, &format!("{}.gz", name)))?
+                {
+                    return Ok(content);
+                }
+                let ask = match &self.ask_missing {
+                    Some(ask) => ask,
+                    None => {
+                        return Err(InputError::NotFound {
+                            location: location(&path),
+                        })
+                    }
+                };
+
+                let content = ask(&path).ok_or_else(|| InputError::Cancelled {
+                    location: location(&path),
+                })?;
+                std::fs::create_dir_all(self.folder(year, day)).map_err(io_error)?;
+                write_input(&path, &content)?;
+                Ok(content)
+            }
+            Err(err) => Err(io_error(err)),
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    fn fetch_content(&self, year: u16, day: u8, name: &str) -> Result<InputContent, InputError> {
+        match map_input(&self.path(year, day, name))? {
+            Some(map) => Ok(InputContent::Mapped(Arc::new(map))),
+            None => self.fetch(year, day, name).map(InputContent::Read),
+        }
+    }
+}
+
+/// Start of the pages adventofcode.com serves instead of an input
+const ERROR_PAGES: &[&str] = &[
+    "Please don't repeatedly request this endpoint before it unlocks!",
+    "Puzzle inputs differ by user.",
+    "404 Not Found",
+    "<!DOCTYPE html>",
+    "<html",
+];
+
+/// Save an input so that it is never seen half-written: the content goes to a `.<name>.partial`
+/// file of the same folder, synced to the disk, then renamed over the input. Empty contents and
+/// error pages of the site are rejected without touching the input
+pub fn write_input(path: &Path, content: &str) -> Result<(), InputError> {
+    write_input_with(path, content, |from, to| std::fs::rename(from, to))
+}
+
+fn write_input_with(
+    path: &Path,
+    content: &str,
+    rename: impl FnOnce(&Path, &Path) -> std::io::Result<()>,
+) -> Result<(), InputError> {
+    let location = || path.display().to_string();
+    let rejected = |reason: &str| InputError::Rejected {
+        location: location(),
+        reason: reason.to_owned(),
+    };
+
+    if content.trim().is_empty() {
+        return Err(rejected("the content is empty"));
+    }
+    if let Some(page) = ERROR_PAGES
+        .iter()
+        .find(|page| content.trim_start().starts_with(*page))
+    {
+        return Err(rejected(&format!(
+            "the content is an error page starting with {:?}",
+            page
+        )));
+    }
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| rejected("the path has no file name"))?;
+    let partial = path.with_file_name(format!(".{}.partial", name.to_string_lossy()));
+
+    let result = std::fs::File::create(&partial)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| rename(&partial, path));
+
+    result.map_err(|source| {
+        // Leave nothing behind, the next attempt starts from scratch
+        let _ = std::fs::remove_file(&partial);
+        InputError::Io {
+            location: location(),
+            source,
+        }
+    })
+}
+
+/// Real inputs downloaded from adventofcode.com with a session cookie, and cached in a data
+/// folder laid out like the one of [`FsProvider`]. The sample inputs, which the site doesn't
+/// serve as files, are only read from the cache folder
+pub struct HttpProvider {
+    session: String,
+    cache: FsProvider,
+    /// Seconds since 1970-01-01, to check the puzzles are unlocked before downloading them
+    clock: fn() -> u64,
+}
+
+/// Name of the input the site serves for each day
+pub const REAL_INPUT: &str = "input.txt";
+
+/// How the requests to the site identify the runner
+pub const USER_AGENT: &str = "github.com/Fryuni/advent-of-rust";
+
+impl HttpProvider {
+    pub fn new(session: impl Into<String>, cache_root: impl Into<PathBuf>) -> Self {
+        Self {
+            session: session.into(),
+            cache: FsProvider::new(cache_root),
+            clock: time::now,
+        }
+    }
+
+    /// Check the puzzles are unlocked with this clock instead of the system one
+    pub fn with_clock(mut self, clock: fn() -> u64) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Provider using the session cookie from the `AOC_SESSION` environment variable
+    pub fn from_env(cache_root: impl Into<PathBuf>) -> Result<Self, String> {
+        std::env::var("AOC_SESSION")
+            .map(|session| Self::new(session.trim(), cache_root))
+            .map_err(|_| "AOC_SESSION must hold the session cookie of adventofcode.com".to_owned())
+    }
+
+    /// File the input is cached in
+    pub fn cache_path(&self, year: u16, day: u8, name: &str) -> PathBuf {
+        self.cache.path(year, day, name)
+    }
+
+    fn download(&self, year: u16, day: u8) -> Result<String, InputError> {
+        let url = format!("https://adventofcode.com/{}/day/{}/input", year, day);
+        let http_error = |message: String| InputError::Http {
+            url: url.clone(),
+            message,
+        };
+
+        ureq::get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .set("User-Agent", USER_AGENT)
+            .call()
+            .map_err(|err| http_error(err.to_string()))?
+            .into_string()
+            .map_err(|err| http_error(err.to_string()))
+    }
+}
+
+impl InputProvider for HttpProvider {
+    fn fetch(&self, year: u16, day: u8, name: &str) -> Result<String, InputError> {
+        match self.cache.fetch(year, day, name) {
+            Ok(content) if !content.is_empty() || name != REAL_INPUT => return Ok(content),
+            Err(err) if name != REAL_INPUT => return Err(err),
+            _ => {}
+        }
+
+        let now = (self.clock)();
+        if !time::is_unlocked(year, day, now) {
+            return Err(InputError::Locked {
+                message: time::locked_message(year, day, now),
+            });
+        }
+        let content = self.download(year, day)?;
+
+        let path = self.cache_path(year, day, name);
+        std::fs::create_dir_all(self.cache.folder(year, day)).map_err(|source| InputError::Io {
+            location: path.display().to_string(),
+            source,
+        })?;
+        write_input(&path, &content)?;
+
+        Ok(content)
+    }
+
+    #[cfg(feature = "mmap")]
+    fn fetch_content(&self, year: u16, day: u8, name: &str) -> Result<InputContent, InputError> {
+        match self.cache.fetch_content(year, day, name) {
+            Ok(content) if !content.is_empty() || name != REAL_INPUT => Ok(content),
+            Err(err) if name != REAL_INPUT => Err(err),
+            _ => self.fetch(year, day, name).map(InputContent::Read),
+        }
+    }
+}
+
+/// Inputs kept in memory, for tests
+#[derive(Default)]
+pub struct InMemoryProvider {
+    inputs: HashMap<(u16, u8, String), String>,
+}
+
+impl InMemoryProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, year: u16, day: u8, name: &str, content: impl Into<String>) -> Self {
+        self.inputs
+            .insert((year, day, name.to_owned()), content.into());
+        self
+    }
+}
+
+impl InputProvider for InMemoryProvider {
+    fn fetch(&self, year: u16, day: u8, name: &str) -> Result<String, InputError> {
+        self.inputs
+            .get(&(year, day, name.to_owned()))
+            .cloned()
+            .ok_or_else(|| InputError::NotFound {
+                location: format!("{} of day {} of {}", name, day, year),
+            })
+    }
+}
+
+#[test]
+fn test_input_folders() {
+    let data = FsProvider::new("data");
+    assert_eq!(data.folder(2020, 1), Path::new("data/2020/1"));
+    assert_eq!(data.folder(2021, 1), Path::new("data/2021/1"));
+    assert_eq!(data.folder(2020, 18), Path::new("data/2020/18"));
+    assert_eq!(
+        FsProvider::new("elsewhere").path(2020, 18, "input.txt"),
+        Path::new("elsewhere/2020/18/input.txt")
+    );
+}
+
+#[test]
+fn test_fs_provider() {
+    let root = TempDir::new("inputs");
+
+    let provider = FsProvider::new(root.path());
+    assert!(matches!(
+        provider.fetch(2020, 3, "test.txt"),
+        Err(InputError::NotFound { .. })
+    ));
+
+    // What is given for a missing input is saved, the next fetches read it
+    let path = provider.path(2020, 3, "test.txt");
+    let provider = provider.asking_missing(|path| {
+        Some(match path.file_name()?.to_str()? {
+            "test.txt" => "..#\n".to_owned(),
+            "empty.txt" => " \n".to_owned(),
+            _ => return None,
+        })
+    });
+    assert_eq!(provider.fetch(2020, 3, "test.txt").unwrap(), "..#\n");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "..#\n");
+    assert_eq!(provider.fetch(2020, 3, "test.txt").unwrap(), "..#\n");
+
+    assert!(matches!(
+        provider.fetch(2020, 3, "empty.txt"),
+        Err(InputError::Rejected { .. })
+    ));
+    assert!(!provider.path(2020, 3, "empty.txt").exists());
+
+    let error = provider.fetch(2020, 3, "input.txt").unwrap_err();
+    assert!(matches!(error, InputError::Cancelled { .. }));
+    assert!(error
+        .to_string()
+        .ends_with("input.txt is missing and was not pasted"));
+    assert!(!provider.path(2020, 3, "input.txt").exists());
+}
+
+#[test]
+fn test_unreadable_inputs() {
+    let root = TempDir::new("inputs-unreadable");
+
+    let provider = FsProvider::new(root.path());
+    std::fs::create_dir_all(provider.path(2020, 16, "test.txt")).unwrap();
+
+    let error = provider.fetch(2020, 16, "test.txt").unwrap_err();
+    assert!(
+        matches!(&error, InputError::Io { source, .. } if source.kind() == ErrorKind::IsADirectory)
+    );
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "{} is a directory instead of an input file",
+            std::path::absolute(provider.path(2020, 16, "test.txt"))
+                .unwrap()
+                .display()
+        )
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = provider.path(2020, 16, "input.txt");
+        std::fs::write(&path, "class: 1-3").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Permissions don't apply to root, which can still read the file
+        if std::fs::read(&path).is_err() {
+            assert!(provider
+                .fetch(2020, 16, "input.txt")
+                .unwrap_err()
+                .to_string()
+                .starts_with("not allowed to read /"));
+        }
+    }
+
+    let denied = InputError::Io {
+        location: "/data/2020/16/input.txt".to_owned(),
+        source: ErrorKind::PermissionDenied.into(),
+    };
+    assert_eq!(
+        denied.to_string(),
+        "not allowed to read /data/2020/16/input.txt, check its permissions"
+    );
+}
+
+#[test]
+fn test_write_input() {
+    let root = TempDir::new("inputs-write");
+    let path = root.join("input.txt");
+    let files = || {
+        let mut files: Vec<_> = std::fs::read_dir(&root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        files
+    };
+
+    write_input(&path, "0,3,6\n").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "0,3,6\n");
+    assert_eq!(files(), ["input.txt"]);
+
+    // A crash between the write and the rename keeps the previous input
+    let error = write_input_with(&path, "1,2,3\n", |_, _| {
+        Err(std::io::Error::other("simulated crash"))
+    })
+    .unwrap_err();
+    assert!(matches!(error, InputError::Io { .. }));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "0,3,6\n");
+    assert_eq!(files(), ["input.txt"]);
+
+    std::fs::remove_file(&path).unwrap();
+    write_input_with(&path, "1,2,3\n", |_, _| {
+        Err(std::io::Error::other("simulated crash"))
+    })
+    .unwrap_err();
+    assert!(files().is_empty());
+
+    for content in [
+        "",
+        " \n",
+        "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n",
+        "Please don't repeatedly request this endpoint before it unlocks! The calendar countdown is synchronized with the server time; the link will be enabled on the calendar the instant this puzzle becomes available.\n",
+        "<!DOCTYPE html>\n<html lang=\"en-us\">",
+    ] {
+        assert!(matches!(
+            write_input(&path, content),
+            Err(InputError::Rejected { .. })
+        ));
+    }
+    assert!(files().is_empty());
+}
+
+#[test]
+fn test_compressed_inputs() {
+    let root = TempDir::new("inputs-gzip");
+
+    let sample = include_str!("../tests/fixtures/2020/3/test.txt");
+    let provider = FsProvider::new(root.path());
+    std::fs::create_dir_all(provider.folder(2020, 3)).unwrap();
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(sample.as_bytes()).unwrap();
+    std::fs::write(
+        provider.path(2020, 3, "input.txt.gz"),
+        encoder.finish().unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(provider.fetch(2020, 3, "input.txt").unwrap(), sample);
+    // Found compressed, so not decompressed next to it
+    assert!(!provider.path(2020, 3, "input.txt").exists());
+
+    // The plain file wins over the compressed one
+    std::fs::write(provider.path(2020, 3, "input.txt"), "plain").unwrap();
+    assert_eq!(provider.fetch(2020, 3, "input.txt").unwrap(), "plain");
+
+    std::fs::write(provider.path(2020, 3, "test.txt.gz"), "not gzip").unwrap();
+    let error = provider.fetch(2020, 3, "test.txt").unwrap_err();
+    assert!(matches!(error, InputError::Corrupt { .. }));
+    assert!(error.to_string().starts_with(&format!(
+        "{} is not valid gzip data",
+        location(&provider.path(2020, 3, "test.txt.gz"))
+    )));
+}
+
+#[test]
+fn test_input_content() {
+    let provider = InMemoryProvider::new().with(2020, 3, "test.txt", "..#");
+    let content = provider.fetch_content(2020, 3, "test.txt").unwrap();
+    assert_eq!(&*content, "..#");
+    assert_eq!(content.into_string(), "..#");
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_mapped_inputs() {
+    let root = TempDir::new("inputs-mapped");
+    let provider = FsProvider::new(root.path());
+    std::fs::create_dir_all(provider.folder(2020, 1)).unwrap();
+
+    // Small inputs are read, large ones mapped
+    std::fs::write(provider.path(2020, 1, "test.txt"), "1721\n979\n").unwrap();
+    assert!(matches!(
+        provider.fetch_content(2020, 1, "test.txt").unwrap(),
+        InputContent::Read(content) if content == "1721\n979\n"
+    ));
+
+    let content = "1721\n979\n366\n".repeat(MMAP_THRESHOLD as usize / 10);
+    std::fs::write(provider.path(2020, 1, "input.txt"), &content).unwrap();
+    let mapped = provider.fetch_content(2020, 1, "input.txt").unwrap();
+    assert!(matches!(mapped, InputContent::Mapped(_)));
+    assert_eq!(&*mapped, content);
+    assert_eq!(mapped.into_string(), content);
+
+    std::fs::write(provider.path(2020, 1, "input.txt"), [0xff_u8; 1 << 20]).unwrap();
+    let error = provider.fetch_content(2020, 1, "input.txt").unwrap_err();
+    assert!(
+        matches!(&error, InputError::Io { source, .. } if source.kind() == ErrorKind::InvalidData)
+    );
+
+    // Missing inputs are still looked for compressed
+    assert!(matches!(
+        provider.fetch_content(2020, 1, "test2.txt"),
+        Err(InputError::NotFound { .. })
+    ));
+}
+
+/// Reading then parsing a 50 MB input, against mapping it and parsing the borrowed text, run with
+/// `cargo test --release --features mmap -- --ignored bench_mapped_inputs --nocapture`
+#[cfg(feature = "mmap")]
+#[ignore]
+#[test]
+fn bench_mapped_inputs() {
+    use crate::advent_adapters::{AdventState, StatefulAdvent};
+    use crate::advents::{Advent, PartResult, Parts};
+    use std::time::{Duration, Instant};
+
+    /// Counts the trees of a map, parsing the borrowed input without keeping it
+    struct Trees(usize);
+
+    impl AdventState for Trees {
+        const INPUT_FILES: &'static [&'static str] = &["input.txt"];
+
+        fn new(input_file: &str, input_content: String) -> Result<Self, String> {
+            Self::new_borrowed(input_file, &input_content)
+        }
+
+        fn new_borrowed(_input_file: &str, input_content: &str) -> Result<Self, String> {
+            Ok(Trees(input_content.bytes().filter(|&b| b == b'#').count()))
+        }
+
+        fn part1(&self) -> PartResult {
+            Ok((self.0 as u64).into())
+        }
+    }
+
+    let root = TempDir::new("inputs-bench");
+    let provider = FsProvider::new(root.path());
+    std::fs::create_dir_all(provider.folder(2020, 3)).unwrap();
+    let line = "#.##..#.#...#..##.#.#...#.##..#.#...#..##.#.#...#\n";
+    std::fs::write(
+        provider.path(2020, 3, "input.txt"),
+        line.repeat(50_000_000 / line.len()),
+    )
+    .unwrap();
+
+    let advent = StatefulAdvent::<Trees>::new(3);
+    let fastest = |solve: &dyn Fn()| {
+        (0..10)
+            .map(|_| {
+                let start = Instant::now();
+                solve();
+                start.elapsed()
+            })
+            .min()
+            .unwrap_or(Duration::MAX)
+    };
+    let read = fastest(&|| {
+        let input = provider.fetch(2020, 3, "input.txt").unwrap();
+        advent.solve("input.txt", input, Parts::All).unwrap();
+    });
+    let mapped = fastest(&|| {
+        let input = provider.fetch_content(2020, 3, "input.txt").unwrap();
+        assert!(matches!(input, InputContent::Mapped(_)));
+        advent
+            .solve_borrowed("input.txt", &input, Parts::All, false)
+            .0
+            .unwrap();
+    });
+    print_info!(
+        "read and parsed: {:?}, mapped and parsed: {:?}",
+        read,
+        mapped
+    );
+}
+
+#[test]
+fn test_in_memory_provider() {
+    let provider = InMemoryProvider::new().with(2020, 3, "test.txt", "..#");
+
+    assert_eq!(provider.fetch(2020, 3, "test.txt").unwrap(), "..#");
+    assert_eq!(
+        provider
+            .fetch(2020, 3, "input.txt")
+            .unwrap_err()
+            .to_string(),
+        "input.txt of day 3 of 2020 does not exist"
+    );
+}
+
+#[test]
+fn test_http_provider_cache() {
+    let root = TempDir::new("inputs-http");
+
+    let provider = HttpProvider::new("no session", root.path());
+    std::fs::create_dir_all(root.join("2020/3")).unwrap();
+    std::fs::write(provider.cache_path(2020, 3, REAL_INPUT), "cached").unwrap();
+
+    assert_eq!(provider.fetch(2020, 3, REAL_INPUT).unwrap(), "cached");
+    assert!(matches!(
+        provider.fetch(2020, 3, "test.txt"),
+        Err(InputError::NotFound { .. })
+    ));
+}
+
+#[test]
+fn test_http_provider_locked() {
+    let dir = TempDir::new("inputs-locked");
+    let root = dir.join("data");
+
+    // An hour and a quarter before the puzzle unlocks, nothing is requested from the site
+    let provider =
+        HttpProvider::new("no session", &root).with_clock(|| time::unlock_time(2021, 5) - 4500);
+    let error = provider.fetch(2021, 5, REAL_INPUT).unwrap_err();
+    assert!(matches!(error, InputError::Locked { .. }));
+    assert_eq!(
+        error.to_string(),
+        "day 5 of 2021 unlocks in 01:15:00, at 2021-12-05 05:00:00 UTC, its input can't be downloaded yet"
+    );
+    assert!(!root.exists());
+
+    // Cached inputs don't need the site
+    std::fs::create_dir_all(root.join("2021/5")).unwrap();
+    std::fs::write(provider.cache_path(2021, 5, REAL_INPUT), "cached").unwrap();
+    assert_eq!(provider.fetch(2021, 5, REAL_INPUT).unwrap(), "cached");
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::helper::display::{render_table, Align};
+#[cfg(test)]
+use crate::helper::temp::TempDir;
+use crate::inputs::USER_AGENT;
+
+/// The site asks not to fetch a private leaderboard more than once every 15 minutes
+pub const REFRESH_SECS: u64 = 15 * 60;
+
+/// Member of a private leaderboard, as the JSON API of the site describes them
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Member {
+    pub id: u64,
+    /// Missing for anonymous members
+    pub name: Option<String>,
+    pub local_score: u64,
+    pub stars: u32,
+    /// Seconds since the Unix epoch when the last star was earned, 0 without stars
+    #[serde(default)]
+    pub last_star_ts: u64,
+    /// Parts solved by day, both keyed by their number as a string
+    #[serde(default)]
+    pub completion_day_level: HashMap<String, HashMap<String, Star>>,
+}
+
+/// Part solved by a member
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Star {
+    /// Seconds since the Unix epoch when the part was solved
+    pub get_star_ts: u64,
+}
+
+impl Member {
+    /// Name shown for the member, anonymous members only have their id
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous #{})", self.id),
+        }
+    }
+
+    /// Stars earned on a day, 0, 1 or 2
+    pub fn stars_on(&self, day: u8) -> usize {
+        self.completion_day_level
+            .get(&day.to_string())
+            .map_or(0, |parts| parts.len())
+    }
+}
+
+/// Private leaderboard of a year
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Leaderboard {
+    pub event: String,
+    pub owner_id: u64,
+    pub members: HashMap<String, Member>,
+}
+
+impl Leaderboard {
+    pub fn parse(json: &str) -> Result<Self, String> {
+        if !json.trim_start().starts_with('{') {
+            return Err(
+                "the site did not answer with a leaderboard, check the session cookie in AOC_SESSION and that it can see this leaderboard".to_owned(),
+            );
+        }
+
+        serde_json::from_str(json).map_err(|err| format!("unexpected leaderboard JSON: {}", err))
+    }
+
+    /// Members by local score, then by stars, then by who got the last one first
+    pub fn ranking(&self) -> Vec<&Member> {
+        let mut members: Vec<_> = self.members.values().collect();
+        members.sort_by(|a, b| {
+            b.local_score
+                .cmp(&a.local_score)
+                .then(b.stars.cmp(&a.stars))
+                .then(a.last_star_ts.cmp(&b.last_star_ts))
+                .then(a.id.cmp(&b.id))
+        });
+        members
+    }
+
+    /// Table of the ranking, with the stars of each member up to the last day anyone solved:
+    /// `*` for both parts, `+` for the first one and `.` for none
+    pub fn render(&self) -> String {
+        let last_day = (1..=25)
+            .rev()
+            .find(|&day| self.members.values().any(|member| member.stars_on(day) > 0))
+            .unwrap_or(0);
+
+        let rows: Vec<_> = self
+            .ranking()
+            .into_iter()
+            .enumerate()
+            .map(|(rank, member)| {
+                let days: String = (1..=last_day)
+                    .map(|day| match member.stars_on(day) {
+                        0 => '.',
+                        1 => '+',
+                        _ => '*',
+                    })
+                    .collect();
+                vec![
+                    (rank + 1).to_string(),
+                    member.display_name(),
+                    member.local_score.to_string(),
+                    member.stars.to_string(),
+                    days,
+                ]
+            })
+            .collect();
+
+        render_table(
+            &[
+                ("Rank", Align::Right),
+                ("Member", Align::Left),
+                ("Score", Align::Right),
+                ("Stars", Align::Right),
+                ("Days", Align::Left),
+            ],
+            &rows,
+        )
+    }
+}
+
+/// Response of the site kept on disk, so the leaderboard is fetched at most every
+/// [`REFRESH_SECS`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedLeaderboard {
+    /// Seconds since the Unix epoch when the leaderboard was fetched
+    pub fetched: u64,
+    pub json: String,
+}
+
+/// File caching a private leaderboard in the data folder
+pub fn cache_path(data_dir: &Path, year: u16, id: u64) -> PathBuf {
+    data_dir
+        .join(year.to_string())
+        .join("leaderboards")
+        .join(format!("{}.json", id))
+}
+
+/// Leaderboard from the cache when it is recent enough at `now`, otherwise from the site, caching
+/// it. A stale cache is still used when the site can't be reached. Returns the leaderboard and
+/// when it was fetched
+pub fn load(
+    data_dir: &Path,
+    year: u16,
+    id: u64,
+    session: &str,
+    now: u64,
+) -> Result<(Leaderboard, u64), String> {
+    let path = cache_path(data_dir, year, id);
+    let cached: Option<CachedLeaderboard> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+
+    if let Some(cached) = &cached {
+        if now.saturating_sub(cached.fetched) < REFRESH_SECS {
+            return Ok((Leaderboard::parse(&cached.json)?, cached.fetched));
+        }
+    }
+
+    let json = match download(year, id, session) {
+        Ok(json) => json,
+        Err(err) => match cached {
+            Some(cached) => {
+                log::warn!("{}, showing the leaderboard fetched earlier", err);
+                return Ok((Leaderboard::parse(&cached.json)?, cached.fetched));
+            }
+            None => return Err(err),
+        },
+    };
+    let leaderboard = Leaderboard::parse(&json)?;
+
+    let cached = CachedLeaderboard { fetched: now, json };
+    let content = serde_json::to_string(&cached).expect("leaderboards are valid JSON");
+    let saved = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, content + "\n"));
+    if let Err(err) = saved {
+        log::warn!("could not write {}: {}", path.display(), err);
+    }
+
+    Ok((leaderboard, now))
+}
+
+fn download(year: u16, id: u64, session: &str) -> Result<String, String> {
+    let url = format!(
+        "https://adventofcode.com/{}/leaderboard/private/view/{}.json",
+        year, id
+    );
+
+    ureq::get(&url)
+        .set("Cookie", &format!("session={}", session))
+        .set("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|err| match err {
+            ureq::Error::Status(404, _) => {
+                format!("there is no private leaderboard {} for {}", id, year)
+            }
+            err => format!("could not download the leaderboard: {}", err),
+        })?
+        .into_string()
+        .map_err(|err| format!("could not read the leaderboard: {}", err))
+}
+
+#[test]
+fn test_render() {
+    let leaderboard =
+        Leaderboard::parse(include_str!("../tests/fixtures/pages/leaderboard.json")).unwrap();
+
+    assert_eq!(leaderboard.event, "2020");
+    assert_eq!(
+        leaderboard.render(),
+        "Rank  Member            Score  Stars  Days\n\
+         \x20  1  Ada                  37      5  **+\n\
+         \x20  2  (anonymous #202)     37      5  **+\n\
+         \x20  3  Grace Hopper         12      3  *.+\n\
+         \x20  4  Lurker                0      0  ...\n"
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(Leaderboard::parse("<!DOCTYPE html><html>")
+        .unwrap_err()
+        .contains("AOC_SESSION"));
+    assert!(Leaderboard::parse("{\"event\": \"2020\"}")
+        .unwrap_err()
+        .starts_with("unexpected leaderboard JSON: missing field"));
+}
+
+#[test]
+fn test_cache() {
+    let data_dir = TempDir::new("leaderboard");
+    let path = cache_path(&data_dir, 2020, 101);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+    let json = include_str!("../tests/fixtures/pages/leaderboard.json").to_owned();
+    let cached = CachedLeaderboard {
+        fetched: 1_000_000,
+        json,
+    };
+    std::fs::write(&path, serde_json::to_string(&cached).unwrap()).unwrap();
+
+    // Recent enough, the site is not asked
+    let (leaderboard, fetched) = load(&data_dir, 2020, 101, "unused", 1_000_000 + 899).unwrap();
+    assert_eq!((leaderboard.members.len(), fetched), (4, 1_000_000));
+}
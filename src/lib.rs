@@ -0,0 +1,85 @@
+#[macro_use]
+pub mod helper;
+
+#[cfg(feature = "y2020")]
+pub mod advent_2020;
+#[cfg(feature = "y2021")]
+pub mod advent_2021;
+pub mod advent_adapters;
+pub mod advents;
+pub mod answers;
+pub mod inputs;
+pub mod leaderboard;
+pub mod manifest;
+pub mod output;
+pub mod runner;
+pub mod selection;
+pub mod site;
+pub mod time;
+pub mod titles;
+
+pub use crate::advents::{Advent, AdventYear, Answer, DayResult, Parts};
+pub use crate::runner::{
+    run_day, run_year, run_years, RunReport, RunSettings, Runner, Selection, Summary,
+};
+
+/// Every registered year, in chronological order. Each year is only compiled with its feature,
+/// like `y2020`
+pub fn advent_years() -> Vec<AdventYear> {
+    // Not mutated when no year is compiled
+    #[allow(unused_mut)]
+    let mut advent_years = vec![
+        #[cfg(feature = "y2020")]
+        advent_2020::get_advent_year(),
+        #[cfg(feature = "y2021")]
+        advent_2021::get_advent_year(),
+    ];
+    advent_years.sort_by_key(AdventYear::get_year);
+
+    for pair in advent_years.windows(2) {
+        assert_ne!(
+            pair[0].get_year(),
+            pair[1].get_year(),
+            "year registered twice"
+        );
+    }
+
+    advent_years
+}
+
+/// Years in the registry, in chronological order
+pub fn registered_years() -> Vec<u16> {
+    advent_years().iter().map(AdventYear::get_year).collect()
+}
+
+/// Solve every part of a registered day for the given input, as the runner would for one of the
+/// day's input files
+pub fn run_day_for_test(year: u16, day: u8, input: &str) -> DayResult {
+    let advent_year = advent_years()
+        .into_iter()
+        .find(|advent_year| advent_year.get_year() == year)
+        .ok_or_else(|| format!("year {} is not registered", year))?;
+
+    let advent = advent_year
+        .iter()
+        .find(|advent| advent.get_index() == day)
+        .ok_or_else(|| format!("day {} of {} is not registered", day, year))?;
+
+    advent.solve("test", input.to_owned(), Parts::All)
+}
+
+#[test]
+fn test_registered_years() {
+    let years: Vec<_> = advent_years().iter().map(AdventYear::get_year).collect();
+
+    let compiled = [
+        (2020, cfg!(feature = "y2020")),
+        (2021, cfg!(feature = "y2021")),
+    ];
+    let expected: Vec<_> = compiled
+        .iter()
+        .filter(|(_, compiled)| *compiled)
+        .map(|(year, _)| *year)
+        .collect();
+    assert_eq!(years, expected);
+}
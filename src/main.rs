@@ -1,136 +1,2276 @@
-use std::io::{ErrorKind, Read};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use structopt::StructOpt;
+use clap::{ArgAction, ArgGroup, CommandFactory, Parser, Subcommand, ValueEnum};
+use itertools::Itertools;
+use log::LevelFilter;
 
-use crate::advents::AdventYear;
+use advent_of_rust::advent_years;
+#[cfg(all(test, feature = "y2020"))]
+use advent_of_rust::advents::{self, Answer};
+use advent_of_rust::advents::{Advent, AdventYear, Parts};
+#[cfg(all(test, feature = "y2020"))]
+use advent_of_rust::answers::{AnswerStore, Check};
+use advent_of_rust::helper;
+#[cfg(all(test, feature = "y2020"))]
+use advent_of_rust::helper::temp::TempDir;
+#[cfg(all(test, feature = "y2020"))]
+use advent_of_rust::inputs::InMemoryProvider;
+use advent_of_rust::inputs::{FsProvider, HttpProvider, InputProvider, REAL_INPUT};
+use advent_of_rust::leaderboard;
+use advent_of_rust::manifest::{Manifest, MANIFEST_FILE};
+use advent_of_rust::output::{utc_timestamp, Output};
+#[cfg(all(test, feature = "y2020"))]
+use advent_of_rust::runner::solve_inputs;
+use advent_of_rust::runner::{
+    find_advent, implemented_days, read_inputs, registered_years_hint, timed_out_days,
+    unreadable_days, validate_selection, RunReport, RunSettings, Runner, Selection,
+};
+use advent_of_rust::selection::{LastSelection, LAST_SELECTION_FILE};
+use advent_of_rust::site;
+use advent_of_rust::time;
+use advent_of_rust::titles::TitleCache;
+use advent_of_rust::{errln, outln};
 
-#[macro_use]
-mod helper;
+/// Solutions to the puzzles of Advent of Code, checked against the answers recorded before
+#[derive(Parser, Debug)]
+#[command(version, after_help = INTERACTIVE_HELP)]
+struct Cli {
+    #[command(flatten)]
+    common: CommonOptions,
 
-mod advent_2020;
-mod advent_adapters;
-mod advents;
+    /// What to do, running the puzzles when omitted
+    #[command(subcommand)]
+    command: Option<Command>,
+}
 
-#[derive(StructOpt, Debug)]
-struct Cli {
+/// How the missing days are asked for, at the end of the help of the command and of `run`
+const INTERACTIVE_HELP: &str = "\
+Without a year or a day, `run` asks for them with a prompt listing the implemented days, starting \
+on the last day selected, with `» all days` entries to run a whole year. The prompt is only shown in a terminal, and never with \
+--no-interactive, --output tap or a CI environment variable: the missing days are then an error.";
+
+/// Options shared by every subcommand
+#[derive(clap::Args, Debug)]
+struct CommonOptions {
+    /// Folder holding the inputs and recorded answers of each year
+    #[arg(long, default_value = "data", global = true)]
+    data_dir: PathBuf,
+
+    /// Show diagnostics of the days, twice to show everything
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Only show errors, not warnings
+    #[arg(short, long, conflicts_with = "verbose", global = true)]
+    quiet: bool,
+
+    /// Also write the output to this file, appending to it when it exists
+    #[arg(long, global = true)]
+    output_file: Option<PathBuf>,
+
+    /// How to print the results, `tap` reporting each part of each input as a test point
+    #[arg(long, value_enum, default_value_t = OutputMode::Human, global = true)]
+    output: OutputMode,
+
+    /// Write a JUnit report to this file, with a test case for each part of each input
+    #[arg(long, global = true)]
+    junit: Option<PathBuf>,
+
+    /// Don't write the summary of the run to `last-run.json` in the data folder
+    #[arg(long, global = true)]
+    no_manifest: bool,
+
+    /// Never prompt, omitting the year or the day is then an error. Implied by `--output tap` and
+    /// by a `CI` environment variable
+    #[arg(long, global = true)]
+    no_interactive: bool,
+}
+
+/// Format of the output, see `CommonOptions::output`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputMode {
+    Human,
+    Tap,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the puzzles of a year and day, asking for the missing ones
+    Run(RunOptions),
+    /// List the registered years and their implemented days
+    List,
+    /// Solve the inputs of a day several times and report how long it takes
+    Bench {
+        #[arg(value_parser = year_parser())]
+        year: u16,
+        #[arg(value_parser = day_parser())]
+        advent: u8,
+
+        /// How many times each input is solved
+        #[arg(long, default_value_t = 10)]
+        iterations: u32,
+    },
+    /// Create the module and the input folder of a new day
+    New {
+        #[arg(value_parser = year_parser())]
+        year: u16,
+        #[arg(value_parser = day_parser())]
+        advent: u8,
+    },
+    /// Download the input of a day, using the session cookie in `AOC_SESSION`
+    Download {
+        #[arg(value_parser = year_parser())]
+        year: u16,
+        #[arg(value_parser = day_parser())]
+        advent: u8,
+    },
+    /// Show when the next puzzle unlocks, at midnight US/Eastern
+    Next {
+        /// Count down until it unlocks, then download its input
+        #[arg(long)]
+        wait: bool,
+        /// Also create the module of the day once its input is downloaded, as `new` does
+        #[arg(long, requires = "wait")]
+        new: bool,
+    },
+    /// Download the titles of the implemented days, shown by `list` and the prompt
+    Titles {
+        #[arg(value_parser = year_parser())]
+        year: Option<u16>,
+    },
+    /// Show a private leaderboard, using the session cookie in `AOC_SESSION`. It is fetched at
+    /// most every 15 minutes, as the site asks
+    Leaderboard {
+        /// Number of the leaderboard, at the end of its URL
+        id: u64,
+        /// Year of the leaderboard, the last one that started by default
+        #[arg(value_parser = year_parser())]
+        year: Option<u16>,
+    },
+    /// Check the answers against the recorded ones without recording anything
+    Verify {
+        #[arg(value_parser = year_parser())]
+        year: Option<u16>,
+        #[arg(value_parser = day_parser())]
+        advent: Option<u8>,
+
+        /// Also compare the answers to the real inputs with the ones accepted by
+        /// adventofcode.com, using the session cookie in `AOC_SESSION` for the days whose
+        /// answers are not cached yet
+        #[arg(long)]
+        online: bool,
+
+        /// Also verify the slow days when verifying every day of a year
+        #[arg(long)]
+        include_slow: bool,
+    },
+}
+
+/// Names that select a subcommand when they are the first argument
+const SUBCOMMANDS: &[&str] = &[
+    "run",
+    "list",
+    "bench",
+    "new",
+    "download",
+    "next",
+    "titles",
+    "leaderboard",
+    "verify",
+    "help",
+];
+
+#[derive(Parser, Debug, Default)]
+#[command(after_help = INTERACTIVE_HELP)]
+#[command(group(ArgGroup::new("days").args(["advent", "all"])))]
+struct RunOptions {
+    #[arg(value_parser = year_parser())]
     year: Option<u16>,
+    #[arg(value_parser = day_parser())]
     advent: Option<u8>,
+
+    /// Run this year as well, can be given several times
+    #[arg(long = "year", value_name = "YEAR", value_parser = year_parser())]
+    years: Vec<u16>,
+
+    /// Print how long solving each input took
+    #[arg(long)]
+    time: bool,
+
+    /// Only run the given part of the puzzles
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// Replace the recorded answers that changed instead of failing
+    #[arg(long)]
+    update_answers: bool,
+
+    /// Run every day of the given year, or of all years, without asking
+    #[arg(long)]
+    all: bool,
+
+    /// Also run the slow days when running every day, they run anyway when selected
+    #[arg(long)]
+    include_slow: bool,
+
+    /// Only solve the real input of the days, leaving out the examples
+    #[arg(long)]
+    only_real: bool,
+
+    /// Only solve the input with this name, can be given several times
+    #[arg(long, value_name = "NAME", conflicts_with = "only_real")]
+    only_file: Vec<String>,
+
+    /// Rerun the day selected interactively the last time, without asking
+    #[arg(long, conflicts_with = "all")]
+    last: bool,
+
+    /// Solve the day this many times after a discarded warm-up, only printing the first run, and
+    /// report the minimum, median and maximum wall time
+    #[arg(long)]
+    repeat: Option<u32>,
+
+    /// Solve the two parts of each input at the same time, on threads of their own, for the days
+    /// whose parts don't depend on each other
+    #[arg(long)]
+    parallel_parts: bool,
+
+    /// Give up on a day that takes longer than this many seconds, moving on to the next one
+    #[arg(long, value_parser = parse_timeout)]
+    timeout: Option<Duration>,
+
+    /// Read lines and evaluate them with the engine of the day instead of solving it, for the
+    /// days that have a REPL, until Ctrl-D
+    #[arg(long, conflicts_with_all = ["all", "repeat", "update_answers"])]
+    repl: bool,
 }
 
 impl Cli {
+    /// Parse the arguments of the process, `run` being implied when no subcommand is given, and
+    /// exit with a usage error when they select days that are not implemented
     pub fn from_user(advent_years: &[AdventYear]) -> Self {
-        let mut options: Self = Self::from_args();
-
-        let dialoguer_theme = &dialoguer::theme::ColorfulTheme::default();
+        let usage_error = |message: String| -> ! {
+            Self::command()
+                .error(clap::error::ErrorKind::ValueValidation, message)
+                .exit()
+        };
 
-        if options.year.is_none() {
-            let years: Vec<_> = advent_years.iter().map(|y| y.get_year()).collect();
+        let arguments =
+            with_today(std::env::args_os().collect(), time::now()).unwrap_or_else(|message| {
+                usage_error(message);
+            });
+        let cli = Self::parse_from(with_implicit_run(arguments));
 
-            options.year = dialoguer::Select::with_theme(dialoguer_theme)
-                .items(&years)
-                .interact_opt()
-                .unwrap()
-                .map(|i| years[i]);
+        if let Err(message) = cli.validate(advent_years) {
+            usage_error(message);
         }
 
-        if let (Some(year), None) = (options.year, options.advent) {
-            if let Some(advent_year) = advent_years.iter().find(|y| y.get_year() == year) {
-                let advents: Vec<_> = advent_year
+        cli
+    }
+
+    /// Check the selected years and days are registered, the parsers only check their ranges
+    fn validate(&self, advent_years: &[AdventYear]) -> Result<(), String> {
+        let (years, day) = match &self.command {
+            Some(Command::Run(options)) => (options.selected_years(), options.advent),
+            Some(Command::Verify { year, advent, .. }) => (year.iter().copied().collect(), *advent),
+            Some(Command::Titles { year }) => (year.iter().copied().collect(), None),
+            Some(Command::Bench { year, advent, .. }) => (vec![*year], Some(*advent)),
+            _ => return Ok(()),
+        };
+
+        validate_selection(advent_years, &years, day)?;
+
+        match &self.command {
+            Some(Command::Run(options)) => {
+                let days: Vec<_> = advent_years
                     .iter()
-                    .filter_map(|a| if a.skip() { None } else { Some(a.get_index()) })
+                    .filter(|advent_year| {
+                        years.is_empty() || years.contains(&advent_year.get_year())
+                    })
+                    .flat_map(|advent_year| {
+                        let year = advent_year.get_year();
+                        advent_year.iter().map(move |advent| (year, &**advent))
+                    })
+                    .filter(|(_, advent)| {
+                        !advent.skip() && day.is_none_or(|day| day == advent.get_index())
+                    })
                     .collect();
+                options.check_inputs(&days)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Day of a year in the registry, without taking it out like `find_advent`
+fn registered_day(advent_years: &[AdventYear], year: u16, day: u8) -> Option<&dyn Advent> {
+    advent_years
+        .iter()
+        .filter(|advent_year| advent_year.get_year() == year)
+        .flat_map(AdventYear::iter)
+        .find(|advent| advent.get_index() == day)
+        .map(|advent| &**advent)
+}
 
-                options.advent = dialoguer::Select::with_theme(dialoguer_theme)
-                    .items(&advents)
-                    .interact_opt()
-                    .unwrap()
-                    .map(|i| advents[i]);
-            };
+/// First Advent of Code
+const FIRST_YEAR: u16 = 2015;
+
+/// Year of an advent that started, the range depending on the current year, a two-digit year
+/// standing for the one of this century (see `expand_year`). The error lists the registered
+/// years, they are checked against the registry by `Cli::validate`
+fn year_parser() -> clap::builder::ValueParser {
+    clap::builder::ValueParser::new(|value: &str| {
+        let year: u16 = value
+            .parse()
+            .map_err(|_| format!("the year must be a number, not {}", value))?;
+
+        match expand_year(year) {
+            year if (FIRST_YEAR..=current_year()).contains(&year) => Ok(year),
+            year => Err(format!(
+                "there was no Advent of Code in {}, {}",
+                year,
+                registered_years_hint(&advent_of_rust::registered_years(), year)
+            )),
         }
+    })
+}
 
-        options
+/// Full year of the shorthand `15` to `99`, the advents starting in 2015 there is no ambiguity.
+/// This only applies to the arguments that are years, `20 18` being day 18 of 2020
+fn expand_year(year: u16) -> u16 {
+    match year {
+        15..=99 => 2000 + year,
+        year => year,
     }
 }
 
-fn main() {
-    let advent_years = vec![advent_2020::get_advent_year()];
-    let options: Cli = Cli::from_user(&advent_years);
+fn day_parser() -> clap::builder::RangedI64ValueParser<u8> {
+    clap::value_parser!(u8).range(1..=25)
+}
+
+fn parse_timeout(value: &str) -> Result<Duration, String> {
+    match value.parse::<f64>() {
+        Ok(secs) if secs > 0.0 && secs.is_finite() => Ok(Duration::from_secs_f64(secs)),
+        _ => Err(format!(
+            "the timeout must be a positive number of seconds, not {}",
+            value
+        )),
+    }
+}
+
+/// Year of the current date in UTC
+fn current_year() -> u16 {
+    time::year_of_day((time::now() / 86400) as i64)
+}
+
+/// Options of a command and its subcommands followed by their value, which is never the `today`
+/// keyword
+fn value_options(command: &clap::Command) -> Vec<String> {
+    let mut options: Vec<String> = command
+        .get_arguments()
+        .filter(|argument| !argument.is_positional() && argument.get_action().takes_values())
+        .flat_map(|argument| {
+            let long = argument.get_long().map(|long| format!("--{}", long));
+            let short = argument.get_short().map(|short| format!("-{}", short));
+            long.into_iter().chain(short)
+        })
+        .collect();
+    for subcommand in command.get_subcommands() {
+        options.extend(value_options(subcommand));
+    }
+
+    options
+}
+
+/// Replace the `today` keyword by the year and the day of the advent at `now` on the site, so
+/// that `advent-of-rust today` runs the puzzle of the day and `download today` fetches its input.
+/// Outside of the advent, the keyword is an error telling when the next puzzle unlocks
+fn with_today(arguments: Vec<OsString>, now: u64) -> Result<Vec<OsString>, String> {
+    let mut expanded = Vec::with_capacity(arguments.len() + 1);
+    let value_options = value_options(&Cli::command());
+
+    for argument in arguments {
+        let is_value = expanded
+            .last()
+            .and_then(|previous: &OsString| previous.to_str())
+            .is_some_and(|previous| value_options.iter().any(|option| option == previous));
+        if argument != "today" || is_value {
+            expanded.push(argument);
+            continue;
+        }
+
+        let (year, day) = time::advent_day(now).ok_or_else(|| {
+            let (year, day, _) = time::next_unlock(now);
+            format!(
+                "there is no puzzle today, {}",
+                time::locked_message(year, day, now)
+            )
+        })?;
+        expanded.push(year.to_string().into());
+        expanded.push(day.to_string().into());
+    }
+
+    Ok(expanded)
+}
+
+/// Insert the `run` subcommand when the arguments don't start with one, so that
+/// `advent-of-rust 2020 18` keeps working. The former `--list` flag is mapped to `list`
+fn with_implicit_run(mut arguments: Vec<OsString>) -> Vec<OsString> {
+    let command = Cli::command();
+    let shared: Vec<_> = command
+        .get_arguments()
+        .filter(|argument| argument.is_global_set())
+        .collect();
+    let takes_value = |option: &clap::Arg| option.get_action().takes_values();
+    let mut position = 1;
+
+    // Skip the shared options with their values, they are accepted before the subcommand
+    while let Some(argument) = arguments.get(position).and_then(|a| a.to_str()) {
+        let skipped = if let Some(name) = argument.strip_prefix("--") {
+            let (name, inline_value) = name
+                .split_once('=')
+                .map_or((name, false), |(name, _)| (name, true));
+            shared
+                .iter()
+                .find(|option| option.get_long() == Some(name))
+                .map(|&option| 1 + usize::from(takes_value(option) && !inline_value))
+        } else if let Some(letters) = argument.strip_prefix('-').filter(|l| !l.is_empty()) {
+            // Flags can be grouped, like `-vv`
+            letters
+                .chars()
+                .all(|letter| {
+                    shared
+                        .iter()
+                        .any(|option| option.get_short() == Some(letter) && !takes_value(option))
+                })
+                .then_some(1)
+        } else {
+            None
+        };
+
+        match skipped {
+            Some(count) => position += count,
+            None => break,
+        }
+    }
+
+    match arguments.get(position).and_then(|a| a.to_str()) {
+        Some("--list") => arguments[position] = "list".into(),
+        Some(argument)
+            if SUBCOMMANDS.contains(&argument)
+                || ["-h", "--help", "-V", "--version"].contains(&argument) => {}
+        Some(_) => arguments.insert(position, "run".into()),
+        None => {}
+    }
+
+    arguments
+}
+
+impl CommonOptions {
+    /// Whether missing arguments can be asked for, given the value of the `CI` environment
+    /// variable. `--no-interactive` and `--output tap` always disable the prompts, `CI` does
+    /// unless it is empty, `false` or `0`
+    fn interactive(&self, ci: Option<OsString>) -> bool {
+        let on_ci = ci.is_some_and(|ci| !["", "false", "0"].contains(&&*ci.to_string_lossy()));
+
+        !self.no_interactive && self.output == OutputMode::Human && !on_ci
+    }
+
+    /// Log level selected by the flags, overriding the one from `RUST_LOG`
+    fn log_level(&self) -> Option<LevelFilter> {
+        match (self.quiet, self.verbose) {
+            (true, _) => Some(LevelFilter::Error),
+            (false, 0) => None,
+            (false, 1) => Some(LevelFilter::Debug),
+            (false, _) => Some(LevelFilter::Trace),
+        }
+    }
+
+    fn init_logger(&self) {
+        let mut builder = env_logger::Builder::new();
+        builder
+            .filter_level(LevelFilter::Warn)
+            .format_timestamp(None)
+            .parse_default_env();
+
+        if let Some(level) = self.log_level() {
+            builder.filter_level(level);
+        }
+
+        builder.init();
+    }
+}
+
+impl RunOptions {
+    /// Years given as the positional argument or with `--year`, every year when empty
+    fn selected_years(&self) -> Vec<u16> {
+        let mut years: Vec<u16> = self
+            .year
+            .into_iter()
+            .chain(self.years.iter().copied())
+            .collect();
+        years.sort_unstable();
+        years.dedup();
+        years
+    }
 
-    match options.year {
-        Some(year) => {
-            match advent_years
+    /// Fill the year and the day from the last selection, unless other years were given
+    fn use_last(&mut self, last: LastSelection) {
+        let years = self.selected_years();
+        if years.is_empty() || years == [last.year] {
+            self.year = Some(last.year);
+            self.years.clear();
+            self.advent = self.advent.or(Some(last.day));
+        }
+    }
+
+    /// Ask for the year and the day when they were not given, starting on the last selection.
+    /// Without a year, a single prompt lists the days of every year after an entry running all of
+    /// them. Returns what was selected, or an error when the prompt is cancelled without
+    /// confirming to run everything. When it can't be shown, exits with a usage error listing
+    /// the implemented days
+    fn ask_missing(
+        &mut self,
+        advent_years: &[AdventYear],
+        last: Option<LastSelection>,
+        data_dir: &Path,
+        interactive: bool,
+    ) -> Result<Option<LastSelection>, String> {
+        let years = self.selected_years();
+        if self.all || self.advent.is_some() || years.len() > 1 {
+            return Ok(None);
+        }
+
+        let days: Vec<_> = advent_years
+            .iter()
+            .filter(|advent_year| years.is_empty() || years.contains(&advent_year.get_year()))
+            .flat_map(|advent_year| {
+                advent_year
+                    .iter()
+                    .filter(|advent| !advent.skip())
+                    .map(move |advent| (advent_year.get_year(), &**advent))
+            })
+            .collect();
+
+        if days.is_empty() {
+            return Ok(None);
+        }
+
+        let reason = if !interactive {
+            Some("Prompts are disabled")
+        } else if !(std::io::stdin().is_terminal() && std::io::stderr().is_terminal()) {
+            Some("Not running in a terminal")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            let days = days
+                .iter()
+                .group_by(|(year, _)| *year)
                 .into_iter()
-                .find(|advent_year| advent_year.get_year() == year)
-            {
-                None => println!("No solution registered for given year {}", year),
-                Some(target_year) => run_advent_year(&options, target_year),
-            };
-        }
-        None => {
-            advent_years
+                .map(|(year, advents)| {
+                    format!(
+                        "{}: {}",
+                        year,
+                        advents.map(|(_, advent)| advent.get_index()).join(", ")
+                    )
+                })
+                .join("; ");
+            Cli::command()
+                .error(
+                    clap::error::ErrorKind::MissingRequiredArgument,
+                    format!(
+                        "{}, pass the year and the day, or --all. The implemented days are {}",
+                        reason, days
+                    ),
+                )
+                .exit();
+        }
+
+        let entries = prompt_entries(&days, years.is_empty());
+        let titles: HashMap<_, _> = advent_years
+            .iter()
+            .map(|advent_year| {
+                let year = advent_year.get_year();
+                (year, TitleCache::load(data_dir, year))
+            })
+            .collect();
+        let labels: Vec<_> = entries
+            .iter()
+            .map(|entry| entry.label(&titles, years.len() == 1))
+            .collect();
+
+        let default = last
+            .and_then(|last| {
+                entries.iter().position(|entry| match entry {
+                    PromptEntry::Day(year, advent) => {
+                        (*year, advent.get_index()) == (last.year, last.day)
+                    }
+                    _ => false,
+                })
+            })
+            .unwrap_or_else(|| {
+                entries
+                    .iter()
+                    .position(|entry| matches!(entry, PromptEntry::Day(..)))
+                    .unwrap_or(0)
+            });
+
+        let theme = &dialoguer::theme::ColorfulTheme::default();
+        let selected = dialoguer::FuzzySelect::with_theme(theme)
+            .items(&labels)
+            .default(default)
+            .interact_opt()
+            .map_err(|err| format!("could not ask for the day: {}", err))?
+            .ok_or("Nothing selected, pick a day or `» all days`, or pass --all")?;
+
+        match entries[selected] {
+            PromptEntry::Day(year, advent) => {
+                self.year = Some(year);
+                self.advent = Some(advent.get_index());
+
+                Ok(Some(LastSelection {
+                    year,
+                    day: advent.get_index(),
+                }))
+            }
+            PromptEntry::AllDays(year) => {
+                self.year = Some(year);
+                self.all = true;
+                Ok(None)
+            }
+            PromptEntry::AllYears => {
+                self.all = true;
+                Ok(None)
+            }
+        }
+    }
+
+    fn parts(&self) -> Parts {
+        self.part.map_or(Parts::All, Parts::Only)
+    }
+
+    /// Days given as the arguments, every day of every year when there are none
+    fn selection(&self) -> Selection {
+        let years = self.selected_years();
+        match (years.as_slice(), self.advent) {
+            ([], None) => Selection::All,
+            (&[year], None) => Selection::Year(year),
+            (&[year], Some(day)) => Selection::Day(year, day),
+            (_, None) => Selection::Years(years),
+            (_, Some(day)) => Selection::Days(years, day),
+        }
+    }
+
+    /// Check the inputs given with `--only-file` are inputs of at least one of the selected days
+    fn check_inputs(&self, days: &[(u16, &dyn Advent)]) -> Result<(), String> {
+        let names: Vec<_> = days
+            .iter()
+            .flat_map(|(_, advent)| advent.get_input_names())
+            .unique()
+            .collect();
+        let name = match self.only_file.iter().find(|name| !names.contains(name)) {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+
+        match days {
+            [(year, advent)] => Err(format!(
+                "day {} of {} has no input {}, its inputs are {}",
+                advent.get_index(),
+                year,
+                name,
+                names.join(", ")
+            )),
+            _ => Err(format!(
+                "none of the selected days has an input {}, their inputs are {}",
+                name,
+                names.join(", ")
+            )),
+        }
+    }
+
+    /// Ask which inputs to solve of a day selected interactively, all of them being checked,
+    /// unless it only has one or they were chosen with `--only-real` or `--only-file`
+    fn ask_inputs(&mut self, advent: &dyn Advent) -> Result<(), String> {
+        let names = advent.get_input_names();
+        if names.len() < 2 || self.only_real || !self.only_file.is_empty() {
+            return Ok(());
+        }
+
+        let selected =
+            dialoguer::MultiSelect::with_theme(&dialoguer::theme::ColorfulTheme::default())
+                .with_prompt("Inputs to solve")
+                .items(&names)
+                .defaults(&vec![true; names.len()])
+                .interact_opt()
+                .map_err(|err| format!("could not ask for the inputs: {}", err))?
+                .ok_or("Nothing selected, pass --only-real or --only-file to choose the inputs")?;
+
+        if selected.is_empty() {
+            return Err("No input selected, there is nothing to run".to_owned());
+        }
+        if selected.len() < names.len() {
+            self.only_file = selected
                 .into_iter()
-                .for_each(move |y| run_advent_year(&options, y));
+                .map(|index| names[index].clone())
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// How the runner should run the selected days
+    fn settings(&self) -> RunSettings {
+        RunSettings {
+            day: self.advent,
+            parts: self.parts(),
+            time: self.time,
+            update_answers: self.update_answers,
+            repeat: self.repeat,
+            timeout: self.timeout,
+            include_slow: self.include_slow,
+            inputs: if self.only_real {
+                vec![REAL_INPUT.to_owned()]
+            } else {
+                self.only_file.clone()
+            },
+            parallel_parts: self.parallel_parts,
         }
     }
 }
 
-fn run_advent_year(options: &Cli, y: advents::AdventYear) {
-    let year = y.get_year();
-    println!("Running year {}", year);
+/// Entry of the prompt asking for the day to run
+#[derive(Clone, Copy)]
+enum PromptEntry<'a> {
+    /// Every day of every year
+    AllYears,
+    /// Every day of a year
+    AllDays(u16),
+    Day(u16, &'a dyn Advent),
+}
 
-    let mut advents = y.into_advents();
+impl PromptEntry<'_> {
+    /// Line of the entry in the prompt, the year being left out of `» all days` when it is the
+    /// only one listed
+    fn label(&self, titles: &HashMap<u16, TitleCache>, single_year: bool) -> String {
+        match self {
+            PromptEntry::AllYears => "» all years".to_owned(),
+            PromptEntry::AllDays(_) if single_year => "» all days".to_owned(),
+            PromptEntry::AllDays(year) => format!("» all days of {}", year),
+            PromptEntry::Day(year, advent) => entry_label(*year, *advent, &titles[year]),
+        }
+    }
+}
 
-    if advents.len() == 0 {
-        return eprintln!("No adventures registered for year {}!", year);
+/// Entries of the prompt for `days`, sorted by year: each year starts with `» all days`, and
+/// `» all years` comes first with `all_years`
+fn prompt_entries<'a>(days: &[(u16, &'a dyn Advent)], all_years: bool) -> Vec<PromptEntry<'a>> {
+    let mut entries = Vec::with_capacity(days.len() + 1);
+    if all_years {
+        entries.push(PromptEntry::AllYears);
     }
+    for (year, advents) in &days.iter().group_by(|(year, _)| *year) {
+        entries.push(PromptEntry::AllDays(year));
+        entries.extend(advents.map(|&(year, advent)| PromptEntry::Day(year, advent)));
+    }
+    entries
+}
 
-    advents.sort_by_key(|advent| advent.get_index());
+/// Line of a day in the prompt, like `2020 · 18 · Operation Order`
+fn entry_label(year: u16, advent: &dyn Advent, titles: &TitleCache) -> String {
+    match titles.title_of(advent) {
+        Some(title) => format!("{} · {} · {}", year, advent.get_index(), title),
+        None => format!("{} · {}", year, advent.get_index()),
+    }
+}
 
-    if let Some(advent) = options.advent {
-        let index = advents
-            .binary_search_by_key(&advent, |advent| advent.get_index())
-            .expect("Advent index not found");
-        let target_advent = advents.swap_remove(index);
+fn main() {
+    let advent_years = advent_years();
+    let cli = Cli::from_user(&advent_years);
+    cli.common.init_logger();
+    let common = &cli.common;
 
-        run_advent(year, target_advent);
+    let output = match &common.output_file {
+        Some(path) => Output::Terminal.tee(path).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }),
+        None => Output::Terminal,
+    };
+    let output = match common.output {
+        OutputMode::Human => output,
+        OutputMode::Tap => output.tap(),
+    };
+    let output = &mut match &common.junit {
+        Some(path) => output.junit(path),
+        None => output.record(),
+    };
+    let inputs = FsProvider::new(&common.data_dir);
+    // Missing inputs are pasted in an editor, when there is someone to do it
+    let inputs = &if common.interactive(std::env::var_os("CI"))
+        && std::io::stdin().is_terminal()
+        && std::io::stderr().is_terminal()
+    {
+        inputs.asking_missing(paste_input)
     } else {
-        advents
-            .into_iter()
-            .for_each(|advent| run_advent(year, advent));
+        inputs
+    };
+
+    let runs_days = match &cli.command {
+        None | Some(Command::Verify { .. }) => true,
+        Some(Command::Run(options)) => !options.repl,
+        _ => false,
+    };
+    let result = match cli.command {
+        None => run(common, inputs, RunOptions::default(), advent_years, output),
+        Some(Command::Run(options)) => run(common, inputs, options, advent_years, output),
+        Some(Command::List) => {
+            list_advent_years(&advent_years, &common.data_dir, output);
+            Ok(())
+        }
+        Some(Command::Bench {
+            year,
+            advent,
+            iterations,
+        }) => find_advent(advent_years, year, advent)
+            .and_then(|target| bench_advent(inputs, year, &*target, iterations, output)),
+        Some(Command::New { year, advent }) => new_advent(common, year, advent, output),
+        Some(Command::Download { year, advent }) => download_input(common, year, advent, output),
+        Some(Command::Next { wait, new }) => next_puzzle(common, wait, new, output),
+        Some(Command::Titles { year }) => fetch_titles(common, &advent_years, year, output),
+        Some(Command::Leaderboard { id, year }) => show_leaderboard(common, id, year, output),
+        Some(Command::Verify {
+            year,
+            advent,
+            online,
+            include_slow,
+        }) => verify(
+            common,
+            inputs,
+            (year, advent),
+            online,
+            include_slow,
+            advent_years,
+            output,
+        ),
+    };
+
+    if runs_days && !common.no_manifest {
+        let manifest = Manifest::new(std::env::args().skip(1).collect(), output.points());
+        if let Err(err) = manifest.save(common.data_dir.join(MANIFEST_FILE)) {
+            log::warn!("{}, the run is not summarized", err);
+        }
+    }
+
+    if let Err(err) = result {
+        errln!(output, "{}", err);
+        // Exiting skips the destructors
+        output.flush();
+        std::process::exit(1);
     }
 }
 
-fn run_advent(year: u16, advent: Box<dyn advents::Advent>) {
-    if advent.skip() {
-        return println!("Skipping advent {}...", advent.get_index());
+fn run(
+    common: &CommonOptions,
+    inputs: &dyn InputProvider,
+    mut options: RunOptions,
+    advent_years: Vec<AdventYear>,
+    output: &mut Output,
+) -> Result<(), String> {
+    let last_path = common.data_dir.join(LAST_SELECTION_FILE);
+    let last = LastSelection::load(&last_path);
+    if options.last {
+        options.use_last(last.ok_or("there is no previous selection to rerun")?);
     }
-    println!("Running advent day {}...", advent.get_index());
 
-    let mut inputs = advent.get_input_names();
-    let path_prefix = ["data", &year.to_string(), &advent.get_index().to_string()]
+    if let Some(selection) = options.ask_missing(
+        &advent_years,
+        last,
+        &common.data_dir,
+        common.interactive(std::env::var_os("CI")),
+    )? {
+        if let Err(err) = selection.save(&last_path) {
+            log::warn!("{}, the selection is not remembered", err);
+        }
+
+        let advent = registered_day(&advent_years, selection.year, selection.day)
+            .expect("the prompt only lists the implemented days");
+        options.check_inputs(&[(selection.year, advent)])?;
+        if !options.repl {
+            options.ask_inputs(advent)?;
+        }
+    }
+
+    if options.repl {
+        return match (options.selected_years().as_slice(), options.advent) {
+            (&[year], Some(day)) => find_advent(advent_years, year, day)
+                .and_then(|advent| run_repl(year, &*advent, output)),
+            _ => Err("--repl only works on a single day, select one".to_owned()),
+        };
+    }
+
+    match (options.repeat, options.advent) {
+        (Some(0), _) => return Err("--repeat needs at least one run".to_owned()),
+        (Some(_), None) => return Err("--repeat only works on a single day, select one".to_owned()),
+        _ => {}
+    }
+
+    let report = Runner::new(advent_years)
+        .with_data_dir(&common.data_dir)
+        .with_provider(inputs)
+        .with_settings(options.settings())
+        .recording_answers()
+        .run_to(options.selection(), output)?;
+
+    let requested = options.selected_years();
+    let idle: Vec<_> = report
+        .years
         .iter()
-        .collect::<std::path::PathBuf>();
-
-    std::fs::create_dir_all(&path_prefix).expect("could not create missing input data folder");
-
-    for file in inputs.iter_mut() {
-        let path = path_prefix.join(&file);
-        file.clear();
-
-        std::fs::File::open(&path)
-            .and_then(|mut f| f.read_to_string(file))
-            .and(Ok(()))
-            .or_else(|err| {
-                if err.kind() == ErrorKind::NotFound {
-                    std::fs::File::create(&path).and(Ok(()))
-                } else {
-                    Err(err)
+        .filter(|(year, summary)| requested.contains(year) && summary.days == 0)
+        .map(|(year, _)| year)
+        .collect();
+    if !idle.is_empty() {
+        return Err(format!("No day of {} was run", idle.iter().join(", ")));
+    }
+
+    let summary = report.summary();
+
+    if summary.failed > 0 {
+        return Err(unreadable_days(summary.failed));
+    }
+    if summary.timed_out > 0 {
+        return Err(timed_out_days(summary.timed_out));
+    }
+    if summary.regressions > 0 && !options.update_answers {
+        return Err(format!(
+            "{} answers changed since they were recorded, rerun with --update-answers if that's expected",
+            summary.regressions
+        ));
+    }
+
+    Ok(())
+}
+
+/// Content of a missing input pasted in the editor of `$EDITOR`, once confirmed. It is asked
+/// again when the paste is empty, and none is given when the editor is closed without saving
+fn paste_input(path: &Path) -> Option<String> {
+    let theme = &dialoguer::theme::ColorfulTheme::default();
+    let confirmed = dialoguer::Confirm::with_theme(theme)
+        .with_prompt(format!(
+            "{} is missing, paste it in an editor?",
+            path.display()
+        ))
+        .default(true)
+        .interact_opt()
+        .unwrap_or_else(|err| {
+            log::warn!("could not ask for {}: {}", path.display(), err);
+            None
+        });
+    if confirmed != Some(true) {
+        return None;
+    }
+
+    loop {
+        match dialoguer::Editor::new().extension(".txt").edit("") {
+            Ok(Some(content)) if content.trim().is_empty() => {
+                eprintln!("The input is empty, paste it or close the editor without saving");
+            }
+            Ok(content) => return content,
+            Err(err) => {
+                log::warn!("could not open an editor for {}: {}", path.display(), err);
+                return None;
+            }
+        }
+    }
+}
+
+/// Hand the lines read from the terminal to the REPL of a day, printing its replies, until the
+/// end of the input. Ctrl-C only drops the line being typed
+fn run_repl(year: u16, advent: &dyn Advent, output: &mut Output) -> Result<(), String> {
+    let mut repl = advent
+        .repl()
+        .ok_or_else(|| format!("Day {} of {} has no REPL", advent.get_index(), year))?;
+    let mut editor = rustyline::DefaultEditor::new()
+        .map_err(|err| format!("could not start the REPL: {}", err))?;
+
+    loop {
+        match editor.readline(&repl.prompt()) {
+            Ok(line) => {
+                if let Err(err) = editor.add_history_entry(line.as_str()) {
+                    log::debug!("could not keep {:?} in the history: {}", line, err);
+                }
+                if let Some(reply) = repl.eval(&line) {
+                    outln!(output, "{}", reply);
                 }
+            }
+            Err(rustyline::error::ReadlineError::Interrupted) => {}
+            Err(rustyline::error::ReadlineError::Eof) => return Ok(()),
+            Err(err) => return Err(format!("could not read the line: {}", err)),
+        }
+    }
+}
+
+fn verify(
+    common: &CommonOptions,
+    inputs: &dyn InputProvider,
+    (year, advent): (Option<u16>, Option<u8>),
+    online: bool,
+    include_slow: bool,
+    advent_years: Vec<AdventYear>,
+    output: &mut Output,
+) -> Result<(), String> {
+    let selection = match (year, advent) {
+        (Some(year), Some(day)) => Selection::Day(year, day),
+        (Some(year), None) => Selection::Year(year),
+        (None, Some(day)) => Selection::Days(Vec::new(), day),
+        (None, None) => Selection::All,
+    };
+
+    let part_counts: HashMap<_, _> = advent_years
+        .iter()
+        .flat_map(|advent_year| {
+            advent_year.iter().map(move |advent| {
+                let day = (advent_year.get_year(), advent.get_index());
+                (day, advent.part_count())
             })
-            .expect("could not read input file");
+        })
+        .collect();
+
+    let runner = Runner::new(advent_years)
+        .with_data_dir(&common.data_dir)
+        .with_provider(inputs)
+        .with_settings(RunSettings {
+            include_slow,
+            ..RunSettings::default()
+        });
+    let left_out = runner.left_out(&selection);
+    let report = runner.run_to(selection, output)?;
+    let summary = report.summary();
+    outln!(
+        output,
+        "{} answers match the recorded ones, {} changed, {} were never recorded",
+        summary.matching,
+        summary.regressions,
+        summary.new
+    );
+    if !left_out.is_empty() {
+        outln!(
+            output,
+            "Slow days not verified: {}, pass --include-slow to verify them",
+            left_out
+                .iter()
+                .map(|(year, day)| format!("day {} of {}", day, year))
+                .join(", ")
+        );
+    }
+
+    let differing = if online {
+        check_online(&common.data_dir, &report, &part_counts, output)?
+    } else {
+        0
+    };
+
+    if summary.failed > 0 {
+        return Err(unreadable_days(summary.failed));
+    }
+    if summary.timed_out > 0 {
+        return Err(timed_out_days(summary.timed_out));
+    }
+    if summary.regressions > 0 {
+        return Err(format!(
+            "{} answers changed since they were recorded",
+            summary.regressions
+        ));
+    }
+    if differing > 0 {
+        return Err(format!(
+            "{} answers differ from the ones accepted by the site",
+            differing
+        ));
+    }
+
+    Ok(())
+}
+
+/// Compare the answers to the real inputs with the ones the site accepted, see
+/// [`site::site_answers`]. Parts not solved on the site yet are unknown. Returns how many answers
+/// differ
+fn check_online(
+    data_dir: &Path,
+    report: &RunReport,
+    part_counts: &HashMap<(u16, u8), u8>,
+    output: &mut Output,
+) -> Result<usize, String> {
+    // Only needed for the days whose answers are not cached yet
+    let session = std::env::var("AOC_SESSION").ok();
+    let mut accepted = HashMap::new();
+    let (mut matching, mut differing, mut unknown) = (0, 0, 0);
+
+    for point in &report.points {
+        let (part, answer) = match (point.part, &point.answer) {
+            (Some(part), Some(answer)) if point.input.as_deref() == Some(REAL_INPUT) => {
+                (part, answer)
+            }
+            _ => continue,
+        };
+
+        let day = (point.year, point.day);
+        let answers = match accepted.entry(day) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let parts = part_counts.get(&day).copied().unwrap_or(2);
+                entry.insert(site::site_answers(
+                    data_dir,
+                    day.0,
+                    day.1,
+                    parts,
+                    session.as_deref().map(str::trim),
+                )?)
+            }
+        };
+
+        match answers.get(usize::from(part) - 1) {
+            Some(site_answer) if site_answer == answer => {
+                matching += 1;
+                outln!(output, "{}: matches the site", point.name());
+            }
+            Some(site_answer) => {
+                differing += 1;
+                let colors = output.colors();
+                outln!(
+                    output,
+                    "{}: differs from the site, {}",
+                    point.name(),
+                    helper::diff::mismatch(site_answer, answer, colors)
+                );
+            }
+            None => {
+                unknown += 1;
+                outln!(output, "{}: unknown, not solved on the site", point.name());
+            }
+        }
+    }
+
+    outln!(
+        output,
+        "{} answers match the site, {} differ, {} are unknown",
+        matching,
+        differing,
+        unknown
+    );
+
+    Ok(differing)
+}
+
+/// List the implemented days of each year, with their titles when they are known
+fn list_advent_years(advent_years: &[AdventYear], data_dir: &Path, output: &mut Output) {
+    for advent_year in advent_years {
+        let year = advent_year.get_year();
+        let titles = TitleCache::load(data_dir, year);
+        let advents: Vec<_> = advent_year.iter().filter(|advent| !advent.skip()).collect();
+
+        if advents.is_empty() {
+            outln!(output, "{}: no days implemented", year);
+            continue;
+        }
+
+        outln!(output, "{}:", year);
+        for advent in advents {
+            // Slow days are left out when running every day, see `RunSettings::leaves_out`
+            let slow = advent.slow().then_some("(slow)");
+            let label = titles.title_of(&**advent).into_iter().chain(slow).join(" ");
+            match label.as_str() {
+                "" => outln!(output, "{:>4}", advent.get_index()),
+                label => outln!(output, "{:>4}  {}", advent.get_index(), label),
+            }
+        }
     }
+}
+
+/// Download the titles of the implemented days into the cache of their year, keeping the ones
+/// already cached. The session cookie in `AOC_SESSION` is sent if there is one, but the titles are
+/// public
+fn fetch_titles(
+    common: &CommonOptions,
+    advent_years: &[AdventYear],
+    year: Option<u16>,
+    output: &mut Output,
+) -> Result<(), String> {
+    let session = std::env::var("AOC_SESSION").ok();
+    let mut missing = 0;
+
+    for advent_year in advent_years {
+        let year = match year {
+            Some(year) if year != advent_year.get_year() => continue,
+            _ => advent_year.get_year(),
+        };
+
+        let mut titles = TitleCache::load(&common.data_dir, year);
+        let days = implemented_days(advent_year);
+        let not_found = titles.fetch_missing(year, &days, session.as_deref().map(str::trim));
+        titles.save()?;
+
+        outln!(
+            output,
+            "{}: {} of {} titles cached",
+            year,
+            days.len() - not_found.len(),
+            days.len()
+        );
+        missing += not_found.len();
+    }
+
+    match missing {
+        0 => {}
+        1 => return Err("Could not find the title of 1 day".to_owned()),
+        _ => return Err(format!("Could not find the titles of {} days", missing)),
+    }
+
+    Ok(())
+}
+
+/// Solve every input of a day `iterations` times and report the fastest and the mean duration
+fn bench_advent(
+    inputs: &dyn InputProvider,
+    year: u16,
+    advent: &dyn Advent,
+    iterations: u32,
+    output: &mut Output,
+) -> Result<(), String> {
+    if iterations == 0 {
+        return Err("at least one iteration is needed".to_owned());
+    }
+
+    let names = advent.get_input_names();
+    let inputs =
+        read_inputs(inputs, year, advent.get_index(), &names).map_err(|err| err.to_string())?;
+
+    for ((input, _), file_name) in inputs.into_iter().zip(names) {
+        let mut fastest = Duration::MAX;
+        let mut total = Duration::ZERO;
+
+        for _ in 0..iterations {
+            let start = Instant::now();
+            let result = advent.solve(&file_name, input.to_string(), Parts::All);
+            let elapsed = start.elapsed();
+
+            if let Err(err) = result {
+                return Err(format!("Could not parse {}:\n{}", file_name, err));
+            }
+
+            fastest = fastest.min(elapsed);
+            total += elapsed;
+        }
+
+        outln!(
+            output,
+            "{}: fastest {:?}, mean {:?} over {} iterations",
+            file_name,
+            fastest,
+            total / iterations,
+            iterations
+        );
+    }
+
+    Ok(())
+}
+
+/// Write the module of a new day from the template, and create its input folder
+fn new_advent(
+    common: &CommonOptions,
+    year: u16,
+    day: u8,
+    output: &mut Output,
+) -> Result<(), String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("there is no day {} in an advent", day));
+    }
+
+    let year_folder = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join(format!("advent_{}", year));
+    if !year_folder.is_dir() {
+        return Err(format!(
+            "{} does not exist, register the year first",
+            year_folder.display()
+        ));
+    }
+
+    let module = year_folder.join(format!("day{:02}.rs", day));
+    if module.exists() {
+        return Err(format!("{} already exists", module.display()));
+    }
+
+    let source = include_str!("../templates/day.rs").replace("XX", &format!("{:02}", day));
+    std::fs::write(&module, source)
+        .map_err(|err| format!("could not write {}: {}", module.display(), err))?;
+
+    let data = FsProvider::new(&common.data_dir).folder(year, day);
+    std::fs::create_dir_all(&data)
+        .map_err(|err| format!("could not create {}: {}", data.display(), err))?;
+
+    outln!(
+        output,
+        "Created {} and {}",
+        module.display(),
+        data.display()
+    );
+    outln!(
+        output,
+        "Register it in {} with `pub mod day{:02};` and `{} => stateful day{:02}::AdventDay{:02},` in `advent_year!`",
+        year_folder.join("mod.rs").display(),
+        day,
+        day,
+        day,
+        day
+    );
+
+    Ok(())
+}
+
+/// Download the input of a day to its input folder, leaving existing inputs alone
+fn download_input(
+    common: &CommonOptions,
+    year: u16,
+    day: u8,
+    output: &mut Output,
+) -> Result<(), String> {
+    let provider = HttpProvider::from_env(&common.data_dir)?;
+
+    let path = provider.cache_path(year, day, REAL_INPUT);
+    if std::fs::metadata(&path).is_ok_and(|metadata| metadata.len() > 0) {
+        return Err(format!("{} is already downloaded", path.display()));
+    }
+
+    provider
+        .fetch(year, day, REAL_INPUT)
+        .map_err(|err| err.to_string())?;
+
+    outln!(
+        output,
+        "Saved the input of day {} of {} to {}",
+        day,
+        year,
+        path.display()
+    );
+
+    Ok(())
+}
+
+/// Downloads of the input of a puzzle that just unlocked before giving up
+const DOWNLOAD_ATTEMPTS: usize = 5;
+
+/// Show when the next puzzle unlocks. When waiting for it, the countdown is updated in place on
+/// a terminal, then the input is downloaded as `download` does. Ctrl-C stops the wait, and the
+/// inputs being written to a temporary file before being renamed, nothing partial is left behind
+fn next_puzzle(
+    common: &CommonOptions,
+    wait: bool,
+    new: bool,
+    output: &mut Output,
+) -> Result<(), String> {
+    let (year, day, unlock) = time::next_unlock(time::now());
+    outln!(
+        output,
+        "Day {} of {} unlocks at {}, in {}",
+        day,
+        year,
+        utc_timestamp(unlock),
+        time::countdown(unlock.saturating_sub(time::now()))
+    );
+    if !wait {
+        return Ok(());
+    }
+
+    // Checked before waiting, rather than when the puzzle unlocks
+    let provider = HttpProvider::from_env(&common.data_dir)?;
+
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let handler = {
+        let interrupted = Arc::clone(&interrupted);
+        ctrlc::set_handler(move || interrupted.store(true, Ordering::SeqCst))
+    };
+    if let Err(err) = handler {
+        log::warn!("{}, Ctrl-C will stop the wait abruptly", err);
+    }
+
+    let in_place = std::io::stderr().is_terminal();
+    loop {
+        if interrupted.load(Ordering::SeqCst) {
+            if in_place {
+                eprintln!();
+            }
+            outln!(output, "Interrupted, nothing was downloaded");
+            return Ok(());
+        }
+
+        let now = time::now();
+        if now >= unlock {
+            break;
+        }
+        if in_place {
+            eprint!(
+                "\r\x1b[2KDay {} unlocks in {}",
+                day,
+                time::countdown(unlock - now)
+            );
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    if in_place {
+        eprintln!("\r\x1b[2KDay {} is unlocked", day);
+    }
+
+    // The site can take a few seconds to serve the inputs of a puzzle that just unlocked
+    let mut attempts = 1;
+    while let Err(err) = provider.fetch(year, day, REAL_INPUT) {
+        if attempts == DOWNLOAD_ATTEMPTS || interrupted.load(Ordering::SeqCst) {
+            return Err(err.to_string());
+        }
+
+        log::warn!("{}, trying again", err);
+        attempts += 1;
+        std::thread::sleep(Duration::from_secs(5));
+    }
+    outln!(
+        output,
+        "Saved the input of day {} of {} to {}",
+        day,
+        year,
+        provider.cache_path(year, day, REAL_INPUT).display()
+    );
+
+    if new {
+        new_advent(common, year, day, output)?;
+    }
+
+    Ok(())
+}
+
+/// Print the ranking of a private leaderboard, see [`leaderboard::load`]
+fn show_leaderboard(
+    common: &CommonOptions,
+    id: u64,
+    year: Option<u16>,
+    output: &mut Output,
+) -> Result<(), String> {
+    let session = std::env::var("AOC_SESSION")
+        .map_err(|_| "AOC_SESSION must hold the session cookie of adventofcode.com".to_owned())?;
+    let now = time::now();
+    let year = year.unwrap_or_else(|| time::last_event_year(now));
+
+    let (leaderboard, fetched) =
+        leaderboard::load(&common.data_dir, year, id, session.trim(), now)?;
+
+    outln!(output, "Private leaderboard {} of {}", id, year);
+    for line in leaderboard.render().lines() {
+        outln!(output, "{}", line);
+    }
+    outln!(
+        output,
+        "* both parts, + the first one. Fetched {} minutes ago",
+        now.saturating_sub(fetched) / 60
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+fn parse_cli(arguments: &[&str]) -> Cli {
+    let arguments = std::iter::once("advent-of-rust")
+        .chain(arguments.iter().copied())
+        .map(OsString::from)
+        .collect();
+
+    Cli::try_parse_from(with_implicit_run(arguments)).unwrap()
+}
+
+#[test]
+fn test_today() {
+    let expand = |arguments: &[&str], now| {
+        with_today(arguments.iter().map(OsString::from).collect(), now).map(|arguments| {
+            arguments
+                .into_iter()
+                .map(|argument| argument.into_string().unwrap())
+                .collect::<Vec<_>>()
+        })
+    };
+    let at =
+        |month, day, hour: u64| time::day_of_date(2021, month, day) as u64 * 86400 + hour * 3600;
+
+    assert_eq!(
+        expand(&["aor", "today", "--time"], at(12, 5, 10)),
+        Ok(vec![
+            "aor".into(),
+            "2021".into(),
+            "5".into(),
+            "--time".into()
+        ])
+    );
+    assert_eq!(
+        expand(&["aor", "download", "today"], at(12, 1, 5)),
+        Ok(vec![
+            "aor".into(),
+            "download".into(),
+            "2021".into(),
+            "1".into()
+        ])
+    );
+    // Still the 25th on the site, which is 5 hours behind UTC
+    assert_eq!(
+        expand(&["aor", "today"], at(12, 26, 4)),
+        Ok(vec!["aor".into(), "2021".into(), "25".into()])
+    );
+    assert_eq!(
+        expand(&["aor", "--data-dir", "today", "list"], at(6, 1, 0)),
+        Ok(vec![
+            "aor".into(),
+            "--data-dir".into(),
+            "today".into(),
+            "list".into()
+        ])
+    );
+    assert_eq!(
+        expand(&["aor", "run", "--timeout", "today"], at(12, 5, 10)),
+        Ok(vec![
+            "aor".into(),
+            "run".into(),
+            "--timeout".into(),
+            "today".into()
+        ])
+    );
+
+    let options = value_options(&Cli::command());
+    for option in [
+        "--data-dir",
+        "--output-file",
+        "--junit",
+        "--repeat",
+        "--timeout",
+    ] {
+        assert!(
+            options.iter().any(|o| o == option),
+            "{} takes a value",
+            option
+        );
+    }
+    assert!(!options.iter().any(|o| o == "--verbose" || o == "-v"));
+
+    assert_eq!(
+        expand(&["aor", "today"], at(12, 1, 4)),
+        Err("there is no puzzle today, day 1 of 2021 unlocks in 01:00:00, at 2021-12-01 05:00:00 UTC".to_owned())
+    );
+    assert!(expand(&["aor", "today"], at(11, 28, 12))
+        .unwrap_err()
+        .contains("unlocks in 2d 17:00:00"));
+}
+
+#[test]
+fn test_implicit_run() {
+    let run = |arguments: &[&str]| match parse_cli(arguments).command {
+        Some(Command::Run(options)) => {
+            Some((options.selected_years(), options.advent, options.time))
+        }
+        None => None,
+        Some(command) => panic!("{:?} is not a run", command),
+    };
+
+    assert_eq!(run(&[]), None);
+    assert_eq!(run(&["2020"]), Some((vec![2020], None, false)));
+    assert_eq!(run(&["2020", "18"]), Some((vec![2020], Some(18), false)));
+    assert_eq!(
+        run(&["run", "2020", "18"]),
+        Some((vec![2020], Some(18), false))
+    );
+    assert_eq!(run(&["--time", "2020"]), Some((vec![2020], None, true)));
+    assert_eq!(run(&["--all"]), Some((vec![], None, false)));
+    assert_eq!(
+        run(&["--year", "2021", "--year", "2020", "--time"]),
+        Some((vec![2020, 2021], None, true))
+    );
+    assert_eq!(
+        run(&["2020", "--year", "2021"]),
+        Some((vec![2020, 2021], None, false))
+    );
+    assert_eq!(run(&["--all", "2020"]), Some((vec![2020], None, false)));
+    assert_eq!(
+        run(&["2020", "3", "--repeat", "5"]),
+        Some((vec![2020], Some(3), false))
+    );
+    assert_eq!(
+        run(&["-vv", "2020", "18"]),
+        Some((vec![2020], Some(18), false))
+    );
+    assert_eq!(
+        run(&["--data-dir", "elsewhere", "2020", "18"]),
+        Some((vec![2020], Some(18), false))
+    );
+
+    assert!(matches!(parse_cli(&["list"]).command, Some(Command::List)));
+    assert!(matches!(
+        parse_cli(&["--no-interactive", "list"]).command,
+        Some(Command::List)
+    ));
+    assert!(matches!(
+        parse_cli(&["--output", "tap", "list"]).command,
+        Some(Command::List)
+    ));
+    assert!(matches!(
+        parse_cli(&["--output=tap", "-q", "list"]).command,
+        Some(Command::List)
+    ));
+    assert!(matches!(
+        parse_cli(&["--junit", "out.xml", "verify"]).command,
+        Some(Command::Verify { year: None, .. })
+    ));
+    assert!(matches!(
+        parse_cli(&["--no-manifest", "verify"]).command,
+        Some(Command::Verify { year: None, .. })
+    ));
+    assert!(matches!(
+        parse_cli(&["--output-file", "out.txt", "--no-manifest", "2020", "18"]).command,
+        Some(Command::Run(_))
+    ));
+    assert!(matches!(
+        parse_cli(&["--list"]).command,
+        Some(Command::List)
+    ));
+    assert!(matches!(
+        parse_cli(&["-q", "verify", "2020"]).command,
+        Some(Command::Verify {
+            year: Some(2020),
+            advent: None,
+            online: false,
+            include_slow: false
+        })
+    ));
+    assert!(matches!(
+        parse_cli(&["verify", "2020", "18", "--online"]).command,
+        Some(Command::Verify {
+            year: Some(2020),
+            advent: Some(18),
+            online: true,
+            include_slow: false
+        })
+    ));
+    assert!(matches!(
+        parse_cli(&["verify", "--include-slow"]).command,
+        Some(Command::Verify {
+            year: None,
+            include_slow: true,
+            ..
+        })
+    ));
+    assert!(matches!(
+        parse_cli(&["leaderboard", "12345", "2020"]).command,
+        Some(Command::Leaderboard {
+            id: 12345,
+            year: Some(2020)
+        })
+    ));
+    assert!(matches!(
+        parse_cli(&["bench", "2020", "17", "--iterations", "3"]).command,
+        Some(Command::Bench {
+            year: 2020,
+            advent: 17,
+            iterations: 3
+        })
+    ));
+}
+
+#[test]
+fn test_command_lines() {
+    Cli::command().debug_assert();
+
+    // Debug representation of the command parsed from a command line
+    let parsed = |arguments: &str| {
+        let arguments = std::iter::once("advent-of-rust")
+            .chain(arguments.split_whitespace())
+            .map(OsString::from)
+            .collect();
+        Cli::try_parse_from(with_implicit_run(arguments))
+            .map(|cli| format!("{:?}", cli.command))
+            .map_err(|err| err.kind())
+    };
+    let run = |year: &str, advent: &str, flags: &str| {
+        format!(
+            "Some(Run(RunOptions {{ year: {}, advent: {}, years: [], {} }}))",
+            year, advent, flags
+        )
+    };
+    let defaults = "time: false, part: None, update_answers: false, all: false, include_slow: false, only_real: false, only_file: [], last: false, repeat: None, parallel_parts: false, timeout: None, repl: false";
+
+    assert_eq!(parsed(""), Ok("None".to_owned()));
+    assert_eq!(
+        parsed("2020 18"),
+        Ok(run("Some(2020)", "Some(18)", defaults))
+    );
+    assert_eq!(
+        parsed("run 2020 18"),
+        Ok(run("Some(2020)", "Some(18)", defaults))
+    );
+    assert_eq!(parsed("2020"), Ok(run("Some(2020)", "None", defaults)));
+    assert_eq!(
+        parsed("-v --data-dir elsewhere 2020 18 --time --part 2"),
+        Ok(run(
+            "Some(2020)",
+            "Some(18)",
+            &defaults
+                .replace("time: false", "time: true")
+                .replace("part: None", "part: Some(2)")
+        ))
+    );
+    assert_eq!(
+        parsed("--all --include-slow"),
+        Ok(run(
+            "None",
+            "None",
+            &defaults
+                .replace("all: false", "all: true")
+                .replace("include_slow: false", "include_slow: true")
+        ))
+    );
+    assert_eq!(parsed("list"), Ok("Some(List)".to_owned()));
+    assert_eq!(
+        parsed("next --wait --new"),
+        Ok("Some(Next { wait: true, new: true })".to_owned())
+    );
+    assert_eq!(
+        parsed("new 2021 3"),
+        Ok("Some(New { year: 2021, advent: 3 })".to_owned())
+    );
+
+    assert_eq!(
+        parsed("next --new"),
+        Err(clap::error::ErrorKind::MissingRequiredArgument)
+    );
+    assert_eq!(
+        parsed("2020 18 --repl --repeat 3"),
+        Err(clap::error::ErrorKind::ArgumentConflict)
+    );
+    assert_eq!(
+        parsed("2020 18 19"),
+        Err(clap::error::ErrorKind::UnknownArgument)
+    );
+    assert_eq!(
+        parsed("download 2020"),
+        Err(clap::error::ErrorKind::MissingRequiredArgument)
+    );
+    assert_eq!(
+        parsed("--version"),
+        Err(clap::error::ErrorKind::DisplayVersion)
+    );
+}
+
+#[test]
+fn test_conflicting_selections() {
+    let error = |arguments: &[&str]| {
+        let arguments = std::iter::once("advent-of-rust")
+            .chain(arguments.iter().copied())
+            .map(OsString::from)
+            .collect();
+        Cli::try_parse_from(with_implicit_run(arguments))
+            .unwrap_err()
+            .kind()
+    };
+
+    assert_eq!(
+        error(&["--all", "2020", "3"]),
+        clap::error::ErrorKind::ArgumentConflict
+    );
+    assert_eq!(
+        error(&["--all", "--last"]),
+        clap::error::ErrorKind::ArgumentConflict
+    );
+    assert_eq!(
+        error(&["2020", "18", "--only-real", "--only-file", "test.txt"]),
+        clap::error::ErrorKind::ArgumentConflict
+    );
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_selected_inputs() {
+    let settings = |arguments: &[&str]| {
+        let arguments = std::iter::once("advent-of-rust")
+            .chain(arguments.iter().copied())
+            .map(OsString::from)
+            .collect();
+        let cli = Cli::try_parse_from(with_implicit_run(arguments)).unwrap();
+        cli.validate(&advent_years())?;
+        match cli.command {
+            Some(Command::Run(options)) => Ok(options.settings().inputs),
+            command => panic!("not a run: {:?}", command),
+        }
+    };
+
+    assert_eq!(settings(&["2020", "18"]), Ok(Vec::new()));
+    assert_eq!(settings(&["--only-real"]), Ok(vec!["input.txt".to_owned()]));
+    assert_eq!(
+        settings(&[
+            "2020",
+            "16",
+            "--only-file",
+            "test.txt",
+            "--only-file",
+            "test2.txt"
+        ]),
+        Ok(vec!["test.txt".to_owned(), "test2.txt".to_owned()])
+    );
+    assert_eq!(
+        settings(&["2020", "18", "--only-file", "test2.txt"]),
+        Err("day 18 of 2020 has no input test2.txt, its inputs are test.txt, input.txt".to_owned())
+    );
+
+    // With several days, the names are checked against the inputs of all of them
+    assert_eq!(
+        settings(&["2020", "--only-file", "test2.txt"]),
+        Ok(vec!["test2.txt".to_owned()])
+    );
+    assert!(settings(&["2020", "--only-file", "tset.txt"])
+        .unwrap_err()
+        .starts_with("none of the selected days has an input tset.txt, their inputs are "));
+    assert!(settings(&["--all", "--only-file", "tset.txt"])
+        .unwrap_err()
+        .starts_with("none of the selected days has an input tset.txt"));
+}
+
+#[test]
+fn test_argument_ranges() {
+    let error = |arguments: &[&str]| {
+        let arguments = std::iter::once("advent-of-rust")
+            .chain(arguments.iter().copied())
+            .map(OsString::from)
+            .collect();
+        Cli::try_parse_from(with_implicit_run(arguments))
+            .unwrap_err()
+            .to_string()
+    };
+
+    assert!(error(&["2020", "30"]).contains("30 is not in 1..=25"));
+    assert!(error(&["bench", "2020", "0"]).contains("0 is not in 1..=25"));
+    assert!(error(&["1999", "5"]).contains(&format!(
+        "there was no Advent of Code in 1999, {}",
+        registered_years_hint(&advent_of_rust::registered_years(), 1999)
+    )));
+    assert!(error(&["--year", "2020", "--year", "3000"]).contains("no Advent of Code in 3000"));
+    assert!(error(&["20x0"]).contains("the year must be a number, not 20x0"));
+    assert!(error(&["14", "1"]).contains("there was no Advent of Code in 14,"));
+    assert!(error(&["99", "1"]).contains("there was no Advent of Code in 2099,"));
+    assert!(error(&["2020", "18", "--part", "3"]).contains("3 is not in 1..=2"));
+    assert!(error(&["--output", "json"]).contains("invalid value 'json' for '--output <OUTPUT>'"));
+}
+
+#[cfg(all(feature = "y2020", feature = "y2021", feature = "heavy-days"))]
+#[test]
+fn test_validation() {
+    let validate = |arguments: &[&str]| parse_cli(arguments).validate(&advent_years());
+
+    assert_eq!(validate(&["2020", "18"]), Ok(()));
+    assert_eq!(validate(&["--year", "2020", "--year", "2021"]), Ok(()));
+    assert_eq!(
+        validate(&["2016", "1"]),
+        Err("No solution registered for year 2016, the registered years are 2020, 2021. Did you mean 2020?".to_owned())
+    );
+    assert_eq!(
+        validate(&["2020", "5"]),
+        Err("Day 5 of 2020 is not implemented, the implemented days are 3, 4, 7, 8, 10, 11, 13, 15, 16, 17, 18, 19, 20, 23, 24, 25 and the skipped ones 1, 2, 5, 6, 9, 12, 14, 21, 22. Did you mean 4?".to_owned())
+    );
+    assert_eq!(
+        validate(&["verify", "2021", "1"]),
+        Err("Day 1 of 2021 is not implemented, no day of 2021 is".to_owned())
+    );
+    assert_eq!(validate(&["new", "2016", "1"]), Ok(()));
+}
+
+#[test]
+fn test_two_digit_years() {
+    assert_eq!(expand_year(15), 2015);
+    assert_eq!(expand_year(25), 2025);
+    assert_eq!(expand_year(99), 2099);
+    assert_eq!(expand_year(14), 14);
+    assert_eq!(expand_year(2020), 2020);
+
+    let run = |arguments: &[&str]| match parse_cli(arguments).command {
+        Some(Command::Run(options)) => (options.year, options.advent, options.years),
+        command => panic!("{:?} is not a run", command),
+    };
+    assert_eq!(run(&["20", "18"]), (Some(2020), Some(18), vec![]));
+    // Positionally a year, even if it could be a day
+    assert_eq!(run(&["15", "25"]), (Some(2015), Some(25), vec![]));
+    assert_eq!(run(&["20", "--year", "21"]), (Some(2020), None, vec![2021]));
+    assert!(matches!(
+        parse_cli(&["bench", "20", "17"]).command,
+        Some(Command::Bench {
+            year: 2020,
+            advent: 17,
+            ..
+        })
+    ));
+    // Only the years are expanded
+    assert!(matches!(
+        parse_cli(&["leaderboard", "20", "20"]).command,
+        Some(Command::Leaderboard {
+            id: 20,
+            year: Some(2020)
+        })
+    ));
+}
+
+#[cfg(all(feature = "y2020", feature = "y2021"))]
+#[test]
+fn test_two_digit_year_validation() {
+    let validate = |arguments: &[&str]| parse_cli(arguments).validate(&advent_years());
+
+    assert_eq!(validate(&["20", "18"]), Ok(()));
+    assert_eq!(
+        validate(&["16", "1"]),
+        Err("No solution registered for year 2016, the registered years are 2020, 2021. Did you mean 2020?".to_owned())
+    );
+}
+
+#[test]
+fn test_current_year() {
+    assert!(current_year() >= 2021);
+}
+
+#[test]
+fn test_common_options() {
+    let common = |arguments: &[&str]| parse_cli(arguments).common;
+
+    assert_eq!(common(&[]).data_dir, Path::new("data"));
+    assert_eq!(
+        common(&["--data-dir", "elsewhere", "list"]).data_dir,
+        Path::new("elsewhere")
+    );
+    assert_eq!(
+        common(&["run", "2020", "--data-dir=elsewhere"]).data_dir,
+        Path::new("elsewhere")
+    );
+
+    let interactive = |arguments: &[&str], ci: Option<&str>| {
+        common(arguments).interactive(ci.map(OsString::from))
+    };
+    assert!(interactive(&[], None));
+    assert!(!interactive(&["--no-interactive"], None));
+    assert!(!interactive(&["2020", "--no-interactive"], None));
+    assert!(!interactive(&["--output", "tap"], None));
+    assert!(!interactive(&[], Some("true")));
+    assert!(!interactive(&[], Some("1")));
+    // Disabling CI doesn't override the flags
+    assert!(interactive(&[], Some("false")));
+    assert!(interactive(&[], Some("")));
+    assert!(!interactive(&["--no-interactive"], Some("0")));
+    assert!(!interactive(&["--output", "tap"], Some("false")));
+}
+
+/// Run a day on the given inputs the way `run_day` does, capturing the output with timings
+/// replaced by a placeholder
+#[cfg(all(test, feature = "y2020"))]
+fn captured_run(day: u8, inputs: &[&str], arguments: &[&str], answers: &mut AnswerStore) -> String {
+    let advent = find_advent(advent_years(), 2020, day).expect("day must be registered");
+    let settings =
+        RunOptions::parse_from(std::iter::once("run").chain(arguments.iter().copied())).settings();
+
+    let inputs = inputs
+        .iter()
+        .map(|&input| (input.to_owned().into(), Duration::ZERO))
+        .collect();
+    let mut output = Output::captured();
+    solve_inputs(
+        2020,
+        &Arc::from(advent),
+        inputs,
+        &settings,
+        answers,
+        &mut output,
+    );
+
+    output
+        .as_captured()
+        .unwrap()
+        .lines()
+        .map(|line| {
+            if line.starts_with("Solved in ") {
+                "Solved in [duration]".to_owned()
+            } else if let Some(timings) = line.find(": min ") {
+                format!("{}: [durations]", &line[..timings])
+            } else {
+                line.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(all(feature = "y2020", feature = "heavy-days"))]
+#[test]
+fn test_phase_timings() {
+    let phases = |day, input: &str| {
+        let advent = find_advent(advent_years(), 2020, day).unwrap();
+        let (result, timings) = advent.solve_timed("test.txt", input.to_owned(), Parts::All);
+        assert!(result.is_ok());
+        timings
+            .into_iter()
+            .map(|(phase, _)| phase.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let pocket = include_str!("../tests/fixtures/2020/17/test.txt");
+    assert_eq!(phases(17, pocket), ["parse", "part 1", "part 2"]);
+    assert_eq!(phases(18, "1 + 2 * 3"), ["parse", "part 1", "part 2"]);
+    // Day 16 solves both parts at once
+    let tickets = include_str!("../tests/fixtures/2020/16/test2.txt");
+    assert_eq!(phases(16, tickets), ["solve"]);
+
+    let output = captured_run(
+        18,
+        &["1 + 2 * 3"],
+        &["--time"],
+        &mut AnswerStore::in_memory(),
+    );
+    assert!(output.contains("Solved in [duration]"));
+}
+
+#[cfg(all(feature = "y2020", feature = "heavy-days"))]
+#[test]
+fn test_timeout() {
+    // The 30 millionth number takes much longer than a millisecond to find
+    let output = captured_run(
+        15,
+        &["0,3,6"],
+        &["--timeout", "0.001"],
+        &mut AnswerStore::in_memory(),
+    );
+    assert_eq!(
+        output,
+        "\nProcessing file input.txt\n\
+         TIMEOUT: day 15 did not finish within 1ms, abandoned while solving input.txt"
+    );
+
+    let output = captured_run(
+        15,
+        &["0,3,6"],
+        &["--timeout", "60", "--part", "1"],
+        &mut AnswerStore::in_memory(),
+    );
+    assert!(output.contains("Answer to step 1: 436"));
+
+    assert!(parse_timeout("0").is_err());
+    assert!(parse_timeout("soon").is_err());
+    assert_eq!(parse_timeout("1.5"), Ok(Duration::from_millis(1500)));
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_tap_output() {
+    let dir = TempDir::new("tap");
+    let data_dir = dir.join("data");
+    let common = parse_cli(&["--data-dir", data_dir.to_str().unwrap()]).common;
+    let inputs = InMemoryProvider::new()
+        .with(2020, 18, "test.txt", "1 + 2 * 3")
+        .with(2020, 18, "input.txt", "1 +");
+
+    let test_points = |arguments: &[&str]| {
+        let options =
+            RunOptions::parse_from(std::iter::once("run").chain(arguments.iter().copied()));
+        let mut output = Output::captured().tap();
+        Runner::new(advent_years())
+            .with_data_dir(&common.data_dir)
+            .with_provider(&inputs)
+            .with_settings(options.settings())
+            .run_to(options.selection(), &mut output)
+            .unwrap();
+
+        let output = output.as_captured().unwrap().to_owned();
+        output
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        test_points(&["2020", "18"]),
+        [
+            "1..4",
+            "ok 1 - 2020 day 18 part 1 (test.txt)",
+            "ok 2 - 2020 day 18 part 2 (test.txt)",
+            "not ok 3 - 2020 day 18 part 1 (input.txt)",
+            "not ok 4 - 2020 day 18 part 2 (input.txt)",
+        ]
+    );
+    assert_eq!(
+        test_points(&["2020", "25", "--part", "2"]),
+        ["1..1", "ok 1 - 2020 day 25 # SKIP no part 2"]
+    );
+    assert!(!data_dir.exists());
+}
+
+#[test]
+fn test_use_last() {
+    let last = LastSelection {
+        year: 2020,
+        day: 18,
+    };
+    let selection = |arguments: &[&str]| {
+        let mut options =
+            RunOptions::parse_from(std::iter::once("run").chain(arguments.iter().copied()));
+        options.use_last(last);
+        (options.year, options.advent)
+    };
+
+    assert_eq!(selection(&["--last"]), (Some(2020), Some(18)));
+    assert_eq!(selection(&["--last", "2020"]), (Some(2020), Some(18)));
+    assert_eq!(selection(&["--last", "2020", "3"]), (Some(2020), Some(3)));
+    assert_eq!(selection(&["--last", "2021"]), (Some(2021), None));
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_prompt_entries() {
+    let advent_years = advent_years();
+    let days: Vec<_> = advent_years
+        .iter()
+        .flat_map(|advent_year| {
+            advent_year
+                .iter()
+                .filter(|advent| !advent.skip())
+                .map(move |advent| (advent_year.get_year(), &**advent))
+        })
+        .collect();
+    let titles: HashMap<_, _> = advent_years
+        .iter()
+        .map(|advent_year| (advent_year.get_year(), TitleCache::in_memory()))
+        .collect();
+    let labels = |entries: &[PromptEntry], single_year| -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| entry.label(&titles, single_year))
+            .collect()
+    };
+
+    let entries = prompt_entries(&days, true);
+    let labels_of_all = labels(&entries, false);
+    assert_eq!(
+        labels_of_all[..3],
+        [
+            "» all years",
+            "» all days of 2020",
+            "2020 · 3 · Toboggan Trajectory"
+        ]
+    );
+    // The day entries map back to every implemented day, in order
+    let selected: Vec<_> = entries
+        .iter()
+        .filter_map(|entry| match entry {
+            PromptEntry::Day(year, advent) => Some((*year, advent.get_index())),
+            _ => None,
+        })
+        .collect();
+    let implemented: Vec<_> = days
+        .iter()
+        .map(|(year, advent)| (*year, advent.get_index()))
+        .collect();
+    assert_eq!(selected, implemented);
+
+    let days_of_2020: Vec<_> = days
+        .iter()
+        .copied()
+        .filter(|(year, _)| *year == 2020)
+        .collect();
+    let entries = prompt_entries(&days_of_2020, false);
+    assert!(matches!(entries[0], PromptEntry::AllDays(2020)));
+    assert_eq!(labels(&entries, true)[0], "» all days");
+    assert_eq!(entries.len(), days_of_2020.len() + 1);
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_entry_labels() {
+    let titles = &mut TitleCache::in_memory();
+    let label = |year, day, titles: &TitleCache| {
+        entry_label(
+            year,
+            &*find_advent(advent_years(), year, day).unwrap(),
+            titles,
+        )
+    };
+
+    assert_eq!(label(2020, 18, titles), "2020 · 18 · Operation Order");
+    assert_eq!(label(2020, 16, titles), "2020 · 16 · Ticket Translation");
+    assert_eq!(
+        entry_label(2021, &advents::SkippedAdvent::new(4), titles),
+        "2021 · 4"
+    );
+
+    // Cached titles come first
+    titles.insert(4, "Giant Squid");
+    titles.insert(18, "Math Homework");
+    assert_eq!(label(2020, 18, titles), "2020 · 18 · Math Homework");
+    assert_eq!(
+        entry_label(2021, &advents::SkippedAdvent::new(4), titles),
+        "2021 · 4 · Giant Squid"
+    );
+}
+
+#[test]
+fn test_log_levels() {
+    let level = |arguments: &[&str]| parse_cli(arguments).common.log_level();
+
+    assert_eq!(level(&[]), None);
+    assert_eq!(level(&["-v"]), Some(LevelFilter::Debug));
+    assert_eq!(level(&["-vv"]), Some(LevelFilter::Trace));
+    assert_eq!(level(&["-q"]), Some(LevelFilter::Error));
+    assert_eq!(level(&["list", "-v"]), Some(LevelFilter::Debug));
+    assert_eq!(level(&["2020", "18", "-q"]), Some(LevelFilter::Error));
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_output_snapshots() {
+    let sample = include_str!("../tests/fixtures/2020/3/test.txt");
+
+    let answers = &mut AnswerStore::in_memory();
+
+    insta::assert_snapshot!("day03", captured_run(3, &[sample, ""], &[], answers));
+    insta::assert_snapshot!(
+        "day03_time",
+        captured_run(3, &[sample], &["--time"], answers)
+    );
+    insta::assert_snapshot!(
+        "day03_part2",
+        captured_run(3, &[sample], &["--part", "2"], answers)
+    );
+    insta::assert_snapshot!(
+        "day03_repeat",
+        captured_run(3, &[sample], &["--repeat", "3", "--time"], answers)
+    );
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_regressions() {
+    let sample = include_str!("../tests/fixtures/2020/3/test.txt");
 
-    advent.process_input(inputs);
+    let answers = &mut AnswerStore::in_memory();
+    answers.check(3, 1, "test.txt", &Answer::Unsigned(8), false);
 
-    println!("\n");
+    insta::assert_snapshot!("day03_regression", captured_run(3, &[sample], &[], answers));
+    insta::assert_snapshot!(
+        "day03_regression_update",
+        captured_run(3, &[sample], &["--update-answers"], answers)
+    );
+    assert_eq!(
+        answers.check(3, 1, "test.txt", &Answer::Unsigned(7), false),
+        Check::Same,
+        "the answer was updated"
+    );
 }
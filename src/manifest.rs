@@ -0,0 +1,234 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use crate::helper::temp::TempDir;
+use crate::output::{Status, TestPoint};
+
+/// Name of the file in the data folder describing the last run
+pub const MANIFEST_FILE: &str = "last-run.json";
+
+/// Version of the manifest format, increased when it changes incompatibly
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Outcome of a day or of one of its parts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Part of a day solved on one input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartRun {
+    pub part: u8,
+    pub input: String,
+    pub status: RunStatus,
+    pub answer: Option<String>,
+    /// Why the part failed
+    pub message: Option<String>,
+    pub duration_secs: Option<f64>,
+}
+
+/// Day of the run, failed when one of its parts did
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DayRun {
+    pub year: u16,
+    pub day: u8,
+    pub status: RunStatus,
+    /// Why the whole day was skipped
+    pub message: Option<String>,
+    pub parts: Vec<PartRun>,
+}
+
+/// Counts of the parts over the whole run
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Totals {
+    pub days: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub duration_secs: f64,
+}
+
+/// Summary of a run written at its end, so tools can look at the last run without parsing what
+/// it printed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    /// Arguments of the command line, without the executable
+    pub arguments: Vec<String>,
+    /// Seconds since the Unix epoch when the run ended
+    pub timestamp: u64,
+    /// Commit the runner was built from, when it is built from a git checkout
+    pub commit: Option<String>,
+    pub days: Vec<DayRun>,
+    pub totals: Totals,
+}
+
+impl Manifest {
+    /// Manifest of the test points reported by a run, in the order they ran
+    pub fn new(arguments: Vec<String>, points: &[TestPoint]) -> Self {
+        let mut days: Vec<DayRun> = Vec::new();
+        let mut totals = Totals::default();
+
+        for point in points {
+            let (status, message) = match &point.status {
+                Status::Passed => (RunStatus::Passed, None),
+                Status::Failed(reason) => (RunStatus::Failed, Some(reason.clone())),
+                Status::Skipped(reason) => (RunStatus::Skipped, Some(reason.clone())),
+            };
+            match status {
+                RunStatus::Passed => totals.passed += 1,
+                RunStatus::Failed => totals.failed += 1,
+                RunStatus::Skipped => totals.skipped += 1,
+            }
+            let duration_secs = point.duration.map(|duration| duration.as_secs_f64());
+            totals.duration_secs += duration_secs.unwrap_or_default();
+
+            let same_day = days
+                .last()
+                .is_some_and(|day| (day.year, day.day) == (point.year, point.day));
+            if !same_day {
+                days.push(DayRun {
+                    year: point.year,
+                    day: point.day,
+                    status,
+                    message: None,
+                    parts: Vec::new(),
+                });
+            }
+            let day = days.last_mut().unwrap();
+
+            match (point.part, &point.input) {
+                (Some(part), Some(input)) => {
+                    if status == RunStatus::Failed || day.parts.is_empty() {
+                        day.status = status;
+                    }
+                    day.parts.push(PartRun {
+                        part,
+                        input: input.clone(),
+                        status,
+                        answer: point.answer.clone(),
+                        message,
+                        duration_secs,
+                    });
+                }
+                _ => {
+                    day.status = status;
+                    day.message = message;
+                }
+            }
+        }
+        totals.days = days.len();
+
+        Self {
+            version: MANIFEST_VERSION,
+            arguments,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            commit: current_commit(),
+            days,
+            totals,
+        }
+    }
+
+    /// Write the manifest through a temporary file, so readers never see half of it
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let error = |err: std::io::Error| format!("could not write {}: {}", path.display(), err);
+        let content = serde_json::to_string_pretty(self).expect("manifests are valid JSON");
+
+        let partial = path.with_file_name(format!(
+            ".{}.partial",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        std::fs::write(&partial, content + "\n")
+            .and_then(|()| std::fs::rename(&partial, path))
+            .map_err(|err| {
+                let _ = std::fs::remove_file(&partial);
+                error(err)
+            })
+    }
+}
+
+/// Commit the runner was built from, if it was built from a git checkout
+fn current_commit() -> Option<String> {
+    Some(env!("AOC_BUILD_COMMIT"))
+        .filter(|commit| !commit.is_empty())
+        .map(str::to_owned)
+}
+
+#[test]
+fn test_manifest() {
+    use std::time::Duration;
+
+    let point = |day, part: Option<u8>, status, answer: Option<&str>| TestPoint {
+        year: 2020,
+        day,
+        part,
+        input: part.map(|_| "input.txt".to_owned()),
+        status,
+        answer: answer.map(str::to_owned),
+        duration: part.map(|_| Duration::from_millis(500)),
+    };
+    let points = [
+        point(18, Some(1), Status::Passed, Some("26")),
+        point(
+            18,
+            Some(2),
+            Status::Failed("expected 46, got 45".to_owned()),
+            Some("45"),
+        ),
+        point(
+            21,
+            None,
+            Status::Skipped("not implemented".to_owned()),
+            None,
+        ),
+    ];
+
+    let manifest = Manifest::new(vec!["2020".to_owned()], &points);
+    assert_eq!(manifest.version, 1);
+    assert_eq!(
+        manifest
+            .days
+            .iter()
+            .map(|day| (day.day, day.status, day.parts.len()))
+            .collect::<Vec<_>>(),
+        [(18, RunStatus::Failed, 2), (21, RunStatus::Skipped, 0)]
+    );
+    assert_eq!(manifest.days[0].parts[1].answer.as_deref(), Some("45"));
+    assert_eq!(manifest.days[1].message.as_deref(), Some("not implemented"));
+    assert_eq!(
+        manifest.totals,
+        Totals {
+            days: 2,
+            passed: 1,
+            failed: 1,
+            skipped: 1,
+            duration_secs: 1.0,
+        }
+    );
+
+    let dir = TempDir::new("manifest");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(MANIFEST_FILE);
+    manifest.save(&path).unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(content.contains("\"version\": 1"));
+    assert!(content.contains("\"status\": \"failed\""));
+    let saved: Manifest = serde_json::from_str(&content).unwrap();
+    assert_eq!(saved, manifest);
+    assert_eq!(
+        std::fs::read_dir(&dir).unwrap().count(),
+        1,
+        "the temporary file is renamed"
+    );
+}
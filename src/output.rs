@@ -0,0 +1,604 @@
+use std::fmt::{self, Write as _};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, IsTerminal, Write as _};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use itertools::Itertools;
+
+#[cfg(test)]
+use crate::helper::temp::TempDir;
+
+/// Destination of the messages printed by the runner, so tests can capture them
+#[derive(Debug)]
+pub enum Output {
+    /// Print to stdout and stderr
+    Terminal,
+    /// Keep everything in memory, with stdout and stderr interleaved in the order they are
+    /// written
+    Captured(String),
+    /// Write to another output and to a file as well
+    Tee {
+        console: Box<Output>,
+        file: BufWriter<File>,
+        path: PathBuf,
+    },
+    /// Report the test points in the Test Anything Protocol to another output, every other
+    /// line becoming a diagnostic
+    Tap {
+        inner: Box<Output>,
+        /// Test points reported so far
+        count: usize,
+    },
+    /// Write to another output, keeping the test points reported to it. They are written as a
+    /// JUnit report to `junit`, if any, each time the output is flushed
+    Recorded {
+        inner: Box<Output>,
+        points: Vec<TestPoint>,
+        junit: Option<PathBuf>,
+    },
+}
+
+/// Result of a test point, see [`TestPoint`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Passed,
+    Failed(String),
+    Skipped(String),
+}
+
+/// Outcome of a part of a day on one input, or of a whole day when it is skipped, reported to
+/// the outputs that keep track of what ran
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestPoint {
+    pub year: u16,
+    pub day: u8,
+    pub part: Option<u8>,
+    pub input: Option<String>,
+    pub status: Status,
+    /// Answer computed by the part, even when it differs from the recorded one
+    pub answer: Option<String>,
+    /// How long the part took, when it ran
+    pub duration: Option<Duration>,
+}
+
+impl TestPoint {
+    /// Name of the point, like `2020 day 18 part 2 (input.txt)`
+    pub fn name(&self) -> String {
+        let mut name = format!("{} day {}", self.year, self.day);
+        if let Some(part) = self.part {
+            write!(name, " part {}", part).unwrap();
+        }
+        if let Some(input) = &self.input {
+            write!(name, " ({})", input).unwrap();
+        }
+        name
+    }
+}
+
+impl Output {
+    pub fn captured() -> Self {
+        Output::Captured(String::new())
+    }
+
+    /// Also write everything to the file at `path`, creating its parent folders. An existing
+    /// file is appended to, after a header with the time of the run
+    pub fn tee(self, path: &Path) -> Result<Self, String> {
+        let error = |err| format!("could not open {}: {}", path.display(), err);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(error)?;
+        }
+        let existing = path.metadata().is_ok_and(|metadata| metadata.len() > 0);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(error)?;
+
+        let mut file = BufWriter::new(file);
+        if existing {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs());
+            writeln!(file, "\n===== Run of {} =====", utc_timestamp(now)).map_err(error)?;
+        }
+
+        Ok(Output::Tee {
+            console: Box::new(self),
+            file,
+            path: path.to_owned(),
+        })
+    }
+
+    /// Report the test points in the Test Anything Protocol, see [`Output::plan`]
+    pub fn tap(self) -> Self {
+        Output::Tap {
+            inner: Box::new(self),
+            count: 0,
+        }
+    }
+
+    /// Keep the test points reported, see [`Output::points`]
+    pub fn record(self) -> Self {
+        Output::Recorded {
+            inner: Box::new(self),
+            points: Vec::new(),
+            junit: None,
+        }
+    }
+
+    /// Keep the test points reported and write them as a JUnit report to `path`, see
+    /// [`junit_report`]
+    pub fn junit(self, path: &Path) -> Self {
+        Output::Recorded {
+            inner: Box::new(self),
+            points: Vec::new(),
+            junit: Some(path.to_owned()),
+        }
+    }
+
+    /// Test points reported so far, if the output records them
+    pub fn points(&self) -> &[TestPoint] {
+        match self {
+            Output::Terminal | Output::Captured(_) => &[],
+            Output::Tee { console, .. } => console.points(),
+            Output::Tap { inner, .. } => inner.points(),
+            Output::Recorded { points, .. } => points,
+        }
+    }
+
+    /// Announce how many test points will be reported, which only matters to TAP
+    pub fn plan(&mut self, points: usize) {
+        match self {
+            Output::Terminal | Output::Captured(_) => {}
+            Output::Tee { console, .. } => console.plan(points),
+            Output::Recorded { inner, .. } => inner.plan(points),
+            Output::Tap { inner, .. } => inner.line(format_args!("1..{}", points)),
+        }
+    }
+
+    /// Report the outcome of a part, the human-readable outputs leave it to the lines printed
+    /// along with it
+    pub fn test_point(&mut self, point: &TestPoint) {
+        match self {
+            Output::Terminal | Output::Captured(_) => {}
+            Output::Tee { console, .. } => console.test_point(point),
+            Output::Recorded { inner, points, .. } => {
+                inner.test_point(point);
+                points.push(point.clone());
+            }
+            Output::Tap { inner, count } => {
+                *count += 1;
+                match &point.status {
+                    Status::Passed => inner.line(format_args!("ok {} - {}", count, point.name())),
+                    Status::Failed(reason) => {
+                        inner.line(format_args!("not ok {} - {}", count, point.name()));
+                        tap_diagnostic(inner, format_args!("{}", reason));
+                    }
+                    Status::Skipped(reason) => inner.line(format_args!(
+                        "ok {} - {} # SKIP {}",
+                        count,
+                        point.name(),
+                        reason
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Whether the lines meant for stderr can be colored, only when they go straight to a
+    /// terminal: files, captures and TAP get the plain text
+    pub fn colors(&self) -> bool {
+        match self {
+            Output::Terminal => crate::helper::diff::use_colors(
+                std::env::var_os("NO_COLOR"),
+                std::io::stderr().is_terminal(),
+            ),
+            Output::Captured(_) | Output::Tee { .. } | Output::Tap { .. } => false,
+            Output::Recorded { inner, .. } => inner.colors(),
+        }
+    }
+
+    /// Print a line meant for stdout
+    pub fn line(&mut self, args: fmt::Arguments<'_>) {
+        match self {
+            Output::Terminal => println!("{}", args),
+            Output::Captured(buffer) => writeln!(buffer, "{}", args).unwrap(),
+            Output::Tee { console, file, .. } => {
+                console.line(args);
+                // Failures are reported by the next flush
+                let _ = writeln!(file, "{}", args);
+            }
+            Output::Tap { inner, .. } => tap_diagnostic(inner, args),
+            Output::Recorded { inner, .. } => inner.line(args),
+        }
+    }
+
+    /// Print a line meant for stderr
+    pub fn error(&mut self, args: fmt::Arguments<'_>) {
+        match self {
+            Output::Terminal => eprintln!("{}", args),
+            Output::Captured(buffer) => writeln!(buffer, "{}", args).unwrap(),
+            Output::Tee { console, file, .. } => {
+                console.error(args);
+                let _ = writeln!(file, "{}", args);
+            }
+            // TAP consumers only read stdout
+            Output::Tap { inner, .. } => tap_diagnostic(inner, args),
+            Output::Recorded { inner, .. } => inner.error(args),
+        }
+    }
+
+    /// Write what is buffered to the file of a tee and the JUnit report, so a crash only loses
+    /// what followed
+    pub fn flush(&mut self) {
+        match self {
+            Output::Terminal | Output::Captured(_) => {}
+            Output::Tee {
+                console,
+                file,
+                path,
+            } => {
+                console.flush();
+                if let Err(err) = file.flush() {
+                    log::warn!("could not write to {}: {}", path.display(), err);
+                }
+            }
+            Output::Tap { inner, .. } => inner.flush(),
+            Output::Recorded {
+                inner,
+                points,
+                junit,
+            } => {
+                inner.flush();
+                let path = match junit {
+                    Some(path) => path,
+                    None => return,
+                };
+                let written = match path.parent() {
+                    Some(parent) => fs::create_dir_all(parent),
+                    None => Ok(()),
+                }
+                .and_then(|()| fs::write(&*path, junit_report(points)));
+                if let Err(err) = written {
+                    log::warn!("could not write {}: {}", path.display(), err);
+                }
+            }
+        }
+    }
+
+    /// Everything written so far, if the output is captured
+    pub fn as_captured(&self) -> Option<&str> {
+        match self {
+            Output::Terminal => None,
+            Output::Captured(buffer) => Some(buffer),
+            Output::Tee { console, .. } => console.as_captured(),
+            Output::Tap { inner, .. } | Output::Recorded { inner, .. } => inner.as_captured(),
+        }
+    }
+}
+
+/// Write each line printed in TAP mode as a diagnostic, so TAP consumers ignore it
+fn tap_diagnostic(inner: &mut Output, args: fmt::Arguments<'_>) {
+    let text = args.to_string();
+    if text.is_empty() {
+        inner.line(format_args!("#"));
+    }
+    for line in text.lines() {
+        match line {
+            "" => inner.line(format_args!("#")),
+            _ => inner.line(format_args!("# {}", line)),
+        }
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// JUnit report of test points, with a test suite per year and a test case per point, named
+/// after the part and input within the day as class
+pub fn junit_report(points: &[TestPoint]) -> String {
+    let count = |points: &[&TestPoint], failed: bool| {
+        points
+            .iter()
+            .filter(|point| match point.status {
+                Status::Passed => false,
+                Status::Failed(_) => failed,
+                Status::Skipped(_) => !failed,
+            })
+            .count()
+    };
+    let seconds = |points: &[&TestPoint]| {
+        points
+            .iter()
+            .filter_map(|point| point.duration)
+            .sum::<Duration>()
+            .as_secs_f64()
+    };
+
+    let all: Vec<_> = points.iter().collect();
+    let mut report = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        report,
+        "<testsuites name=\"advent-of-rust\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.6}\">",
+        all.len(),
+        count(&all, true),
+        count(&all, false),
+        seconds(&all)
+    )
+    .unwrap();
+
+    for (year, points) in &points.iter().group_by(|point| point.year) {
+        let points: Vec<_> = points.collect();
+        writeln!(
+            report,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.6}\">",
+            year,
+            points.len(),
+            count(&points, true),
+            count(&points, false),
+            seconds(&points)
+        )
+        .unwrap();
+
+        for point in points {
+            let name = match (point.part, &point.input) {
+                (Some(part), Some(input)) => format!("part {} ({})", part, input),
+                (Some(part), None) => format!("part {}", part),
+                (None, Some(input)) => input.clone(),
+                (None, None) => format!("day {}", point.day),
+            };
+            write!(
+                report,
+                "    <testcase classname=\"{}.day{:02}\" name=\"{}\" time=\"{:.6}\"",
+                point.year,
+                point.day,
+                xml_escape(&name),
+                point.duration.unwrap_or_default().as_secs_f64()
+            )
+            .unwrap();
+
+            match &point.status {
+                Status::Passed => report.push_str("/>\n"),
+                Status::Failed(reason) => writeln!(
+                    report,
+                    ">\n      <failure message=\"{}\"/>\n    </testcase>",
+                    xml_escape(reason)
+                )
+                .unwrap(),
+                Status::Skipped(reason) => writeln!(
+                    report,
+                    ">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                    xml_escape(reason)
+                )
+                .unwrap(),
+            }
+        }
+
+        report.push_str("  </testsuite>\n");
+    }
+
+    report.push_str("</testsuites>\n");
+    report
+}
+
+/// Escape text for XML attributes, keeping line breaks as character references since
+/// attributes normalize them to spaces
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            // Not allowed in XML 1.0, even escaped
+            c if c.is_control() && c != '\t' && c != '\r' => escaped.push('\u{fffd}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Date and time of a number of seconds since 1970-01-01, like `2020-12-01 05:00:00 UTC`
+pub fn utc_timestamp(secs: u64) -> String {
+    let (days, time) = (secs / 86400, secs % 86400);
+    let (year, month, day) = crate::time::date_of_day(days as i64);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// Like `println!`, but writing to an [`Output`]
+#[macro_export]
+macro_rules! outln {
+    ($output:expr) => {
+        $output.line(format_args!(""))
+    };
+    ($output:expr, $($arg:tt)*) => {
+        $output.line(format_args!($($arg)*))
+    };
+}
+
+/// Like `eprintln!`, but writing to an [`Output`]
+#[macro_export]
+macro_rules! errln {
+    ($output:expr, $($arg:tt)*) => {
+        $output.error(format_args!($($arg)*))
+    };
+}
+
+#[test]
+fn test_capture() {
+    let mut output = Output::captured();
+
+    outln!(output, "answer: {}", 42);
+    errln!(output, "failed");
+    outln!(output);
+
+    assert_eq!(output.as_captured(), Some("answer: 42\nfailed\n\n"));
+    assert_eq!(Output::Terminal.as_captured(), None);
+}
+
+#[test]
+fn test_tee() {
+    let root = TempDir::new("tee");
+    let path = root.join("logs").join("run.txt");
+
+    let mut output = Output::captured().tee(&path).unwrap();
+    outln!(output, "answer: {}", 42);
+    errln!(output, "failed");
+    output.flush();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "answer: 42\nfailed\n");
+    assert_eq!(output.as_captured(), Some("answer: 42\nfailed\n"));
+    drop(output);
+
+    let mut output = Output::captured().tee(&path).unwrap();
+    outln!(output, "second run");
+    drop(output);
+    let content = fs::read_to_string(&path).unwrap();
+    let (first, second) = content.split_once("\n===== Run of ").unwrap();
+    assert_eq!(first, "answer: 42\nfailed\n");
+    assert!(second.ends_with(" UTC =====\nsecond run\n"));
+}
+
+#[test]
+fn test_utc_timestamp() {
+    assert_eq!(utc_timestamp(0), "1970-01-01 00:00:00 UTC");
+    assert_eq!(utc_timestamp(1_606_798_800), "2020-12-01 05:00:00 UTC");
+    assert_eq!(utc_timestamp(951_782_400 + 3661), "2000-02-29 01:01:01 UTC");
+}
+
+#[test]
+fn test_tap() {
+    let point = |part, status| TestPoint {
+        year: 2020,
+        day: 18,
+        part,
+        input: part.map(|_| "input.txt".to_owned()),
+        status,
+        answer: None,
+        duration: None,
+    };
+
+    let mut output = Output::captured().tap();
+    output.plan(3);
+    outln!(output, "Running year 2020");
+    output.test_point(&point(Some(1), Status::Passed));
+    errln!(output, "REGRESSION: the answer was 4\n\nin previous runs");
+    outln!(output);
+    output.test_point(&point(
+        Some(2),
+        Status::Failed("expected 4, got 5".to_owned()),
+    ));
+    output.test_point(&point(None, Status::Skipped("not implemented".to_owned())));
+
+    assert_eq!(
+        output.as_captured(),
+        Some(
+            "1..3\n\
+             # Running year 2020\n\
+             ok 1 - 2020 day 18 part 1 (input.txt)\n\
+             # REGRESSION: the answer was 4\n\
+             #\n\
+             # in previous runs\n\
+             #\n\
+             not ok 2 - 2020 day 18 part 2 (input.txt)\n\
+             # expected 4, got 5\n\
+             ok 3 - 2020 day 18 # SKIP not implemented\n"
+        )
+    );
+
+    // The other outputs leave the points to their lines
+    let mut output = Output::captured();
+    output.plan(1);
+    output.test_point(&point(Some(1), Status::Passed));
+    assert_eq!(output.as_captured(), Some(""));
+}
+
+#[test]
+fn test_junit_report() {
+    let point = |year, day, part: Option<u8>, status, millis: Option<u64>| TestPoint {
+        year,
+        day,
+        part,
+        input: part.map(|_| "input.txt".to_owned()),
+        status,
+        answer: None,
+        duration: millis.map(Duration::from_millis),
+    };
+    let points = [
+        point(2020, 18, Some(1), Status::Passed, Some(250)),
+        point(
+            2020,
+            18,
+            Some(2),
+            Status::Failed("expected <a & \"b\">, got 'c'\non two lines".to_owned()),
+            Some(500),
+        ),
+        point(
+            2020,
+            21,
+            None,
+            Status::Skipped("not implemented".to_owned()),
+            None,
+        ),
+        point(2021, 1, Some(1), Status::Passed, None),
+    ];
+
+    let report = junit_report(&points);
+    let document = roxmltree::Document::parse(&report).unwrap();
+
+    let root = document.root_element();
+    assert_eq!(root.tag_name().name(), "testsuites");
+    assert_eq!(root.attribute("tests"), Some("4"));
+    assert_eq!(root.attribute("failures"), Some("1"));
+    assert_eq!(root.attribute("skipped"), Some("1"));
+    assert_eq!(root.attribute("time"), Some("0.750000"));
+
+    let suites: Vec<_> = root.children().filter(|node| node.is_element()).collect();
+    assert_eq!(
+        suites
+            .iter()
+            .map(|suite| (
+                suite.attribute("name").unwrap(),
+                suite.attribute("tests").unwrap()
+            ))
+            .collect::<Vec<_>>(),
+        [("2020", "3"), ("2021", "1")]
+    );
+
+    let cases: Vec<_> = suites[0]
+        .children()
+        .filter(|node| node.is_element())
+        .collect();
+    assert_eq!(cases[0].attribute("classname"), Some("2020.day18"));
+    assert_eq!(cases[0].attribute("name"), Some("part 1 (input.txt)"));
+    assert_eq!(cases[0].attribute("time"), Some("0.250000"));
+    assert!(!cases[0].has_children());
+
+    let failure = cases[1].first_element_child().unwrap();
+    assert_eq!(failure.tag_name().name(), "failure");
+    assert_eq!(
+        failure.attribute("message"),
+        Some("expected <a & \"b\">, got 'c'\non two lines")
+    );
+
+    let skipped = cases[2].first_element_child().unwrap();
+    assert_eq!(cases[2].attribute("name"), Some("day 21"));
+    assert_eq!(skipped.tag_name().name(), "skipped");
+    assert_eq!(skipped.attribute("message"), Some("not implemented"));
+}
@@ -0,0 +1,1687 @@
+//! Running the days of the registered years and comparing their answers with the recorded ones,
+//! as the `run` and `verify` commands do
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use itertools::Itertools;
+
+#[cfg(all(test, any(feature = "y2020", feature = "y2021")))]
+use crate::advent_years;
+#[cfg(test)]
+use crate::advents::Answer;
+use crate::advents::{Advent, AdventYear, DayResult, Parts, Phase, Timings};
+use crate::answers::{AnswerStore, Check};
+use crate::helper;
+use crate::helper::display::{render_table, Align};
+#[cfg(all(test, any(feature = "y2020", feature = "mmap")))]
+use crate::helper::temp::TempDir;
+#[cfg(test)]
+use crate::inputs::InMemoryProvider;
+use crate::inputs::{FsProvider, InputContent, InputError, InputProvider};
+use crate::output::{Output, Status, TestPoint};
+use crate::{errln, outln};
+
+/// What to run of the selected days and how, the options of the `run` command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunSettings {
+    /// Only run this day of each selected year
+    pub day: Option<u8>,
+    pub parts: Parts,
+    /// Print how long solving each input took
+    pub time: bool,
+    /// Replace the recorded answers that changed instead of failing
+    pub update_answers: bool,
+    /// Solve each day this many times after a warm-up, reporting the wall times
+    pub repeat: Option<u32>,
+    /// Give up on a day that takes longer than this
+    pub timeout: Option<Duration>,
+    /// Also run the slow days when every day of a year runs, see `Advent::slow`
+    pub include_slow: bool,
+    /// Only solve the inputs of the days with these names, all of them when empty
+    pub inputs: Vec<String>,
+    /// Solve the parts of each input at the same time, see `Advent::solve_concurrently`
+    pub parallel_parts: bool,
+}
+
+impl RunSettings {
+    /// Whether a day is left out for being slow, which it is only when running every day without
+    /// `include_slow`
+    pub fn leaves_out(&self, advent: &dyn Advent) -> bool {
+        advent.slow() && self.day.is_none() && !self.include_slow
+    }
+
+    /// Inputs of a day to solve, in the order the day declares them
+    pub fn input_names(&self, advent: &dyn Advent) -> Vec<String> {
+        advent
+            .get_input_names()
+            .into_iter()
+            .filter(|name| self.inputs.is_empty() || self.inputs.contains(name))
+            .collect()
+    }
+}
+
+impl Default for RunSettings {
+    fn default() -> Self {
+        Self {
+            day: None,
+            parts: Parts::All,
+            time: false,
+            update_answers: false,
+            repeat: None,
+            timeout: None,
+            include_slow: false,
+            inputs: Vec::new(),
+            parallel_parts: false,
+        }
+    }
+}
+
+/// Days run by a [`Runner`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    /// Every day of every registered year
+    All,
+    /// Every day of a year
+    Year(u16),
+    /// One day of a year
+    Day(u16, u8),
+    /// Every day of each of these years
+    Years(Vec<u16>),
+    /// The same day of each of these years
+    Days(Vec<u16>, u8),
+}
+
+impl Selection {
+    /// Years selected, none standing for every registered year
+    pub fn years(&self) -> Vec<u16> {
+        match self {
+            Selection::All => Vec::new(),
+            Selection::Year(year) | Selection::Day(year, _) => vec![*year],
+            Selection::Years(years) | Selection::Days(years, _) => years.clone(),
+        }
+    }
+
+    /// Day selected in each year, if not all of them
+    pub fn day(&self) -> Option<u8> {
+        match self {
+            Selection::Day(_, day) | Selection::Days(_, day) => Some(*day),
+            _ => None,
+        }
+    }
+}
+
+/// What a run reported, the same results the command line prints
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunReport {
+    /// How the answers of each year that ran compare with the recorded ones
+    pub years: Vec<(u16, Summary)>,
+    /// Each part of each input of the days, or the whole day when it was skipped, in the order
+    /// they ran
+    pub points: Vec<TestPoint>,
+}
+
+impl RunReport {
+    /// Summary of every year put together
+    pub fn summary(&self) -> Summary {
+        self.years.iter().map(|(_, summary)| *summary).sum()
+    }
+
+    /// Answer of a part of a day on one of its inputs, if it ran and found one
+    pub fn answer(&self, year: u16, day: u8, part: u8, input: &str) -> Option<&str> {
+        self.points
+            .iter()
+            .find(|point| {
+                (point.year, point.day, point.part) == (year, day, Some(part))
+                    && point.input.as_deref() == Some(input)
+            })
+            .and_then(|point| point.answer.as_deref())
+    }
+
+    /// Parts that failed, because they had no answer or it changed
+    pub fn failures(&self) -> impl Iterator<Item = &TestPoint> {
+        self.points
+            .iter()
+            .filter(|point| matches!(point.status, Status::Failed(_)))
+    }
+}
+
+/// Runs the days of the registered years, reading their inputs and the recorded answers from a
+/// data folder, `data` unless told otherwise.
+///
+/// ```no_run
+/// use advent_of_rust::runner::{Runner, Selection};
+///
+/// let report = Runner::new(advent_of_rust::advent_years())
+///     .with_data_dir("data")
+///     .run(Selection::Day(2020, 18))?;
+/// assert_eq!(report.summary().regressions, 0);
+/// # Ok::<(), String>(())
+/// ```
+pub struct Runner<'p> {
+    advent_years: Vec<AdventYear>,
+    data_dir: PathBuf,
+    /// Inputs of the days, the files of the data folder when none is given
+    provider: Option<Box<dyn InputProvider + 'p>>,
+    settings: RunSettings,
+    record_answers: bool,
+}
+
+impl<'p> Runner<'p> {
+    pub fn new(advent_years: Vec<AdventYear>) -> Self {
+        Self {
+            advent_years,
+            data_dir: PathBuf::from("data"),
+            provider: None,
+            settings: RunSettings::default(),
+            record_answers: false,
+        }
+    }
+
+    /// Folder holding the inputs and the recorded answers of each year
+    pub fn with_data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = data_dir.into();
+        self
+    }
+
+    /// Read the inputs from `provider` instead of the data folder
+    pub fn with_provider(mut self, provider: impl InputProvider + 'p) -> Self {
+        self.provider = Some(Box::new(provider));
+        self
+    }
+
+    /// Run the days with these settings, the day of the selection replacing theirs
+    pub fn with_settings(mut self, settings: RunSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Save the answers in the answer stores of the data folder after each year, the new ones
+    /// and, with `RunSettings::update_answers`, the changed ones
+    pub fn recording_answers(mut self) -> Self {
+        self.record_answers = true;
+        self
+    }
+
+    /// Days of the selection, as `(year, day)`, left out for being slow, see
+    /// `RunSettings::leaves_out`
+    pub fn left_out(&self, selection: &Selection) -> Vec<(u16, u8)> {
+        let settings = RunSettings {
+            day: selection.day(),
+            ..self.settings.clone()
+        };
+        let years = selection.years();
+
+        self.advent_years
+            .iter()
+            .filter(|advent_year| years.is_empty() || years.contains(&advent_year.get_year()))
+            .flat_map(|advent_year| {
+                advent_year
+                    .iter()
+                    .filter(|advent| !advent.skip() && settings.leaves_out(&***advent))
+                    .map(move |advent| (advent_year.get_year(), advent.get_index()))
+            })
+            .collect()
+    }
+
+    /// Run the selected days, keeping what they print in memory
+    pub fn run(self, selection: Selection) -> Result<RunReport, String> {
+        self.run_to(selection, &mut Output::captured().record())
+    }
+
+    /// Run the selected days, printing to `output`. The report only has the test points if the
+    /// output records them, see [`Output::record`]
+    pub fn run_to(self, selection: Selection, output: &mut Output) -> Result<RunReport, String> {
+        let settings = RunSettings {
+            day: selection.day(),
+            ..self.settings
+        };
+        let years = match selection.years() {
+            years if years.is_empty() && settings.day.is_some() => {
+                self.advent_years.iter().map(AdventYear::get_year).collect()
+            }
+            years => years,
+        };
+        let data_dir = &self.data_dir;
+        let provider = self
+            .provider
+            .unwrap_or_else(|| Box::new(FsProvider::new(data_dir)));
+
+        let reported = output.points().len();
+        let years = run_years(
+            data_dir,
+            &*provider,
+            &years,
+            &settings,
+            self.advent_years,
+            self.record_answers,
+            output,
+        )?;
+        print_year_summaries(&years, output);
+
+        Ok(RunReport {
+            years,
+            points: output.points()[reported..].to_vec(),
+        })
+    }
+}
+
+/// Check the years are registered and the day, if any, is implemented in each of them
+pub fn validate_selection(
+    advent_years: &[AdventYear],
+    years: &[u16],
+    day: Option<u8>,
+) -> Result<(), String> {
+    for &year in years {
+        let advent_year = advent_years
+            .iter()
+            .find(|advent_year| advent_year.get_year() == year)
+            .ok_or_else(|| unknown_year(advent_years, year))?;
+
+        if let Some(day) = day {
+            if !implemented_days(advent_year).contains(&day) {
+                return Err(unavailable_day(advent_year, day));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Error for a year that is not registered
+pub fn unknown_year(advent_years: &[AdventYear], year: u16) -> String {
+    let registered: Vec<_> = advent_years.iter().map(AdventYear::get_year).collect();
+
+    format!(
+        "No solution registered for year {}, {}",
+        year,
+        registered_years_hint(&registered, year)
+    )
+}
+
+/// End of the errors about a wrong year, listing the registered years and suggesting the closest
+pub fn registered_years_hint(registered: &[u16], year: u16) -> String {
+    match closest(year, registered) {
+        Some(closest) => format!(
+            "the registered years are {}. Did you mean {}?",
+            registered.iter().join(", "),
+            closest
+        ),
+        None => "no year is registered".to_owned(),
+    }
+}
+
+/// Error for a day of a registered year that can't run, listing the days that can and the
+/// skipped ones, registered without a solution, and suggesting the closest day that can
+pub fn unavailable_day(advent_year: &AdventYear, day: u8) -> String {
+    let year = advent_year.get_year();
+    let implemented = implemented_days(advent_year);
+    let skipped = skipped_days(advent_year);
+
+    let closest = match closest(day, &implemented) {
+        Some(closest) => closest,
+        None => {
+            return format!(
+                "Day {} of {} is not implemented, no day of {} is",
+                day, year, year
+            )
+        }
+    };
+
+    let skipped = match skipped.as_slice() {
+        [] => String::new(),
+        skipped => format!(" and the skipped ones {}", skipped.iter().join(", ")),
+    };
+    format!(
+        "Day {} of {} is not implemented, the implemented days are {}{}. Did you mean {}?",
+        day,
+        year,
+        implemented.iter().join(", "),
+        skipped,
+        closest
+    )
+}
+
+/// Candidate closest to a wrong value, the lowest one on ties
+fn closest<T: Copy + Into<i64>>(value: T, candidates: &[T]) -> Option<T> {
+    candidates
+        .iter()
+        .copied()
+        .min_by_key(|&candidate| ((candidate.into() - value.into()).abs(), candidate.into()))
+}
+
+/// How the answers of a run compare with the recorded ones
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Summary {
+    /// Days that were run, whatever their answers
+    pub days: usize,
+    pub matching: usize,
+    pub new: usize,
+    pub regressions: usize,
+    /// Days that could not run because their inputs were unreadable
+    pub failed: usize,
+    /// Days abandoned after running longer than their timeout
+    pub timed_out: usize,
+    /// Days left out because they are slow, see `RunSettings::leaves_out`
+    pub slow: usize,
+}
+
+impl std::ops::Add for Summary {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            days: self.days + other.days,
+            matching: self.matching + other.matching,
+            new: self.new + other.new,
+            regressions: self.regressions + other.regressions,
+            failed: self.failed + other.failed,
+            timed_out: self.timed_out + other.timed_out,
+            slow: self.slow + other.slow,
+        }
+    }
+}
+
+impl std::iter::Sum for Summary {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), std::ops::Add::add)
+    }
+}
+
+/// Error of a run where some days could not read their inputs
+pub fn unreadable_days(failed: usize) -> String {
+    match failed {
+        1 => "1 day could not read its inputs".to_owned(),
+        _ => format!("{} days could not read their inputs", failed),
+    }
+}
+
+/// Error of a run where some days timed out
+pub fn timed_out_days(timed_out: usize) -> String {
+    match timed_out {
+        1 => "1 day timed out".to_owned(),
+        _ => format!("{} days timed out", timed_out),
+    }
+}
+
+/// Days of a year that are not skipped, in order
+pub fn implemented_days(advent_year: &AdventYear) -> Vec<u8> {
+    let mut days: Vec<_> = advent_year
+        .iter()
+        .filter(|advent| !advent.skip())
+        .map(|advent| advent.get_index())
+        .collect();
+    days.sort_unstable();
+    days
+}
+
+/// Days of a year that are registered but skipped, in order
+pub fn skipped_days(advent_year: &AdventYear) -> Vec<u8> {
+    let mut days: Vec<_> = advent_year
+        .iter()
+        .filter(|advent| advent.skip())
+        .map(|advent| advent.get_index())
+        .collect();
+    days.sort_unstable();
+    days
+}
+
+/// Implemented day of a registered year
+pub fn find_advent(
+    advent_years: Vec<AdventYear>,
+    year: u16,
+    day: u8,
+) -> Result<Box<dyn Advent>, String> {
+    validate_selection(&advent_years, &[year], Some(day))?;
+
+    Ok(advent_years
+        .into_iter()
+        .find(|advent_year| advent_year.get_year() == year)
+        .expect("the year is registered")
+        .into_advents()
+        .into_iter()
+        .find(|advent| advent.get_index() == day && !advent.skip())
+        .expect("the day is implemented"))
+}
+
+/// Run the selected years, or all of them when `selected` is empty, summarizing each year.
+/// Answers are recorded in the answer store of their year, in `data_dir`, only when `record` is
+/// set
+pub fn run_years(
+    data_dir: &Path,
+    inputs: &dyn InputProvider,
+    selected: &[u16],
+    settings: &RunSettings,
+    advent_years: Vec<AdventYear>,
+    record: bool,
+    output: &mut Output,
+) -> Result<Vec<(u16, Summary)>, String> {
+    validate_selection(&advent_years, selected, settings.day)?;
+    output.plan(planned_points(&advent_years, selected, settings));
+
+    Ok(advent_years
+        .into_iter()
+        .filter(|y| selected.is_empty() || selected.contains(&y.get_year()))
+        .map(|y| {
+            let year = y.get_year();
+            (
+                year,
+                run_year(data_dir, inputs, settings, y, record, output),
+            )
+        })
+        .collect())
+}
+
+/// Number of test points reported by `run_years`, see `day_points`. Years with every day
+/// skipped don't run, so they have none
+fn planned_points(advent_years: &[AdventYear], selected: &[u16], settings: &RunSettings) -> usize {
+    advent_years
+        .iter()
+        .filter(|y| selected.is_empty() || selected.contains(&y.get_year()))
+        .filter(|y| !y.iter().all(|advent| advent.skip()))
+        .flat_map(|y| y.iter())
+        .filter(|advent| settings.day.is_none_or(|day| day == advent.get_index()))
+        .map(|advent| match settings.leaves_out(&**advent) {
+            true => 1,
+            false => day_points(&**advent, settings).len().max(1),
+        })
+        .sum()
+}
+
+/// Input files and parts a day reports a test point for when it runs. Days that don't run
+/// report a single point, skipped
+fn day_points(advent: &dyn Advent, settings: &RunSettings) -> Vec<(String, u8)> {
+    if advent.skip() {
+        return Vec::new();
+    }
+
+    settings
+        .input_names(advent)
+        .into_iter()
+        .cartesian_product((1..=advent.part_count()).filter(|&part| settings.parts.includes(part)))
+        .collect()
+}
+
+/// Print how the answers of each year compare with the recorded ones, when several years ran
+pub fn print_year_summaries(per_year: &[(u16, Summary)], output: &mut Output) {
+    if per_year.len() < 2 {
+        return;
+    }
+
+    let rows: Vec<_> = per_year
+        .iter()
+        .map(|(year, summary)| {
+            vec![
+                year.to_string(),
+                summary.days.to_string(),
+                summary.matching.to_string(),
+                summary.new.to_string(),
+                summary.regressions.to_string(),
+                summary.slow.to_string(),
+            ]
+        })
+        .collect();
+    let table = render_table(
+        &[
+            ("Year", Align::Left),
+            ("Days", Align::Right),
+            ("Matching", Align::Right),
+            ("New", Align::Right),
+            ("Changed", Align::Right),
+            ("Slow", Align::Right),
+        ],
+        &rows,
+    );
+    for line in table.lines() {
+        outln!(output, "{}", line);
+    }
+}
+
+/// Run the selected days of a year, summarizing how the answers compare with the recorded ones
+pub fn run_year(
+    data_dir: &Path,
+    inputs: &dyn InputProvider,
+    settings: &RunSettings,
+    y: AdventYear,
+    record: bool,
+    output: &mut Output,
+) -> Summary {
+    let year = y.get_year();
+    outln!(output, "Running year {}", year);
+
+    let mut advents = y.into_advents();
+
+    if advents.is_empty() {
+        errln!(output, "No adventures registered for year {}!", year);
+        return Summary::default();
+    }
+    if advents.iter().all(|advent| advent.skip()) {
+        errln!(
+            output,
+            "Nothing to run for year {}, its {} days are all skipped. Run `list` to see the implemented days",
+            year,
+            advents.len()
+        );
+        return Summary::default();
+    }
+
+    let answers_path = data_dir.join(year.to_string()).join("answers.json");
+    let mut answers = AnswerStore::load(answers_path).unwrap_or_else(|err| {
+        errln!(output, "{}, previous answers are not checked", err);
+        AnswerStore::in_memory()
+    });
+
+    advents.sort_by_key(|advent| advent.get_index());
+
+    let summary = if let Some(advent) = settings.day {
+        let index = advents
+            .binary_search_by_key(&advent, |advent| advent.get_index())
+            .expect("Advent index not found");
+        let target_advent = advents.swap_remove(index);
+
+        run_day(inputs, year, target_advent, settings, &mut answers, output)
+    } else {
+        let summary: Summary = advents
+            .into_iter()
+            .map(|advent| run_day(inputs, year, advent, settings, &mut answers, output))
+            .sum();
+        match summary.slow {
+            0 => {}
+            1 => outln!(
+                output,
+                "1 slow day of {} was left out, pass --include-slow or select it to run it",
+                year
+            ),
+            slow => outln!(
+                output,
+                "{} slow days of {} were left out, pass --include-slow or select them to run them",
+                slow,
+                year
+            ),
+        }
+        summary
+    };
+
+    if record {
+        if let Err(err) = answers.save() {
+            errln!(output, "{}", err);
+        }
+    }
+
+    summary
+}
+
+/// Run every input of a day, reporting the answers and how they compare with the recorded ones
+pub fn run_day(
+    inputs: &dyn InputProvider,
+    year: u16,
+    advent: Box<dyn Advent>,
+    settings: &RunSettings,
+    answers: &mut AnswerStore,
+    output: &mut Output,
+) -> Summary {
+    let skipped = |reason: String| TestPoint {
+        year,
+        day: advent.get_index(),
+        part: None,
+        input: None,
+        status: Status::Skipped(reason),
+        answer: None,
+        duration: None,
+    };
+
+    if advent.skip() {
+        outln!(output, "Skipping advent {}...", advent.get_index());
+        output.test_point(&skipped("not implemented".to_owned()));
+        return Summary::default();
+    }
+    if settings.leaves_out(&*advent) {
+        outln!(
+            output,
+            "Skipping advent {}, it is slow...",
+            advent.get_index()
+        );
+        output.test_point(&skipped("slow, pass --include-slow to run it".to_owned()));
+        return Summary {
+            slow: 1,
+            ..Summary::default()
+        };
+    }
+    if let Parts::Only(part) = settings.parts {
+        if part > advent.part_count() {
+            outln!(
+                output,
+                "Skipping advent {}, it has no part {}...",
+                advent.get_index(),
+                part
+            );
+            output.test_point(&skipped(format!("no part {}", part)));
+            return Summary::default();
+        }
+    }
+    let names = settings.input_names(&*advent);
+    if names.is_empty() {
+        outln!(
+            output,
+            "Skipping advent {}, none of its inputs is selected...",
+            advent.get_index()
+        );
+        output.test_point(&skipped("none of its inputs is selected".to_owned()));
+        return Summary::default();
+    }
+    outln!(output, "Running advent day {}...", advent.get_index());
+
+    // Shared with the threads solving the inputs with a timeout
+    let advent: Arc<dyn Advent> = Arc::from(advent);
+
+    // What the day prints itself comes as a block after the runner's report of the day
+    let day = advent.get_index();
+    let (summary, day_output) = helper::output::capture(|| match settings.repeat {
+        // Each input is read when its turn comes, so only one of them is in memory at a time
+        None => process_inputs(
+            year,
+            &advent,
+            &mut |name| Some(read_input(inputs, year, day, name)),
+            settings,
+            answers,
+            output,
+        ),
+        // The runs solve the same strings, read beforehand
+        Some(runs) => match read_inputs(inputs, year, day, &names) {
+            Ok(inputs) => repeat_inputs(year, &advent, inputs, runs, settings, answers, output),
+            Err(err) => {
+                // Reported the way the inputs read one at a time report it
+                let mut err = Some(err);
+                process_inputs(
+                    year,
+                    &advent,
+                    &mut |_| err.take().map(Err),
+                    settings,
+                    answers,
+                    output,
+                )
+            }
+        },
+    });
+    if !day_output.is_empty() {
+        outln!(output, "\nPrinted by day {}:", advent.get_index());
+        for line in day_output.lines() {
+            outln!(output, "{}", line);
+        }
+    }
+
+    outln!(output, "\n");
+    output.flush();
+
+    summary
+}
+
+/// Solve the inputs already read, once or as many times as `RunSettings::repeat` asks
+pub fn solve_inputs(
+    year: u16,
+    advent: &Arc<dyn Advent>,
+    inputs: Vec<Input>,
+    settings: &RunSettings,
+    answers: &mut AnswerStore,
+    output: &mut Output,
+) -> Summary {
+    match settings.repeat {
+        Some(runs) => repeat_inputs(year, advent, inputs, runs, settings, answers, output),
+        None => {
+            let mut inputs = inputs.into_iter();
+            let mut next_input = |_: &str| inputs.next().map(Ok);
+            process_inputs(year, advent, &mut next_input, settings, answers, output)
+        }
+    }
+}
+
+/// Process the inputs `runs` times after a warm-up, reporting the wall time of the runs. Only
+/// the first run prints anything and checks the answers, the others are measured on the same
+/// input strings
+fn repeat_inputs(
+    year: u16,
+    advent: &Arc<dyn Advent>,
+    inputs: Vec<Input>,
+    runs: u32,
+    settings: &RunSettings,
+    answers: &mut AnswerStore,
+    output: &mut Output,
+) -> Summary {
+    let timed_run = |answers: &mut AnswerStore, output: &mut Output| {
+        let mut inputs = inputs.clone().into_iter();
+        let mut next_input = |_: &str| inputs.next().map(Ok);
+        let start = Instant::now();
+        let summary = process_inputs(year, advent, &mut next_input, settings, answers, output);
+        (start.elapsed(), summary)
+    };
+
+    // The other runs don't print anything, including what the day prints itself
+    let silent_run = || {
+        helper::output::capture(|| {
+            timed_run(&mut AnswerStore::in_memory(), &mut Output::captured())
+        })
+        .0
+    };
+
+    silent_run();
+
+    let (first, summary) = timed_run(answers, output);
+    let mut durations = vec![first];
+    for _ in 1..runs {
+        durations.push(silent_run().0);
+    }
+    durations.sort_unstable();
+
+    outln!(
+        output,
+        "\nRepeated {} times: min {:?}, median {:?}, max {:?}",
+        runs,
+        durations[0],
+        median(&durations),
+        durations[durations.len() - 1]
+    );
+
+    summary
+}
+
+/// Median of sorted durations, the mean of the two middle ones for an even count
+fn median(sorted: &[Duration]) -> Duration {
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2
+    } else {
+        sorted[middle]
+    }
+}
+
+/// Content of an input and how long it took to read it
+pub type Input = (InputContent, Duration);
+
+/// Fetch an input of a day, timing it
+pub fn read_input(
+    inputs: &dyn InputProvider,
+    year: u16,
+    day: u8,
+    name: &str,
+) -> Result<Input, InputError> {
+    let start = Instant::now();
+    let content = inputs.fetch_content(year, day, name)?;
+    Ok((content, start.elapsed()))
+}
+
+/// Fetch the inputs of a day with these names, `Advent::get_input_names` or the ones selected by
+/// `RunSettings::inputs`
+pub fn read_inputs(
+    inputs: &dyn InputProvider,
+    year: u16,
+    day: u8,
+    names: &[String],
+) -> Result<Vec<Input>, InputError> {
+    names
+        .iter()
+        .map(|name| read_input(inputs, year, day, name))
+        .collect()
+}
+
+/// Solve one input, with its parts at the same time if `parallel_parts`. Mapped inputs are
+/// borrowed by the day, the ones read are handed over
+fn solve_input(
+    advent: &dyn Advent,
+    file_name: &str,
+    input: InputContent,
+    parts: Parts,
+    parallel_parts: bool,
+) -> (DayResult, Timings) {
+    match input {
+        InputContent::Read(input) if parallel_parts => {
+            advent.solve_concurrently(file_name, input, parts)
+        }
+        InputContent::Read(input) => advent.solve_timed(file_name, input, parts),
+        #[cfg(feature = "mmap")]
+        InputContent::Mapped(_) => advent.solve_borrowed(file_name, &input, parts, parallel_parts),
+    }
+}
+
+/// Solve one input on a thread of its own, giving up on it after `budget`. When the day does not
+/// finish in time, the thread is abandoned with its reference to the day, and what the day
+/// printed so far is returned as the error
+fn solve_with_timeout(
+    advent: Arc<dyn Advent>,
+    file_name: &str,
+    input: InputContent,
+    parts: Parts,
+    parallel_parts: bool,
+    budget: Duration,
+) -> Result<(DayResult, Timings), String> {
+    let day = advent.get_index();
+    let printed = helper::output::SharedBuffer::default();
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    let worker_printed = Arc::clone(&printed);
+    let worker_file_name = file_name.to_owned();
+    std::thread::spawn(move || {
+        let result = helper::output::capture_into(&worker_printed, || {
+            solve_input(&*advent, &worker_file_name, input, parts, parallel_parts)
+        });
+        // Nobody listens anymore when the day timed out
+        let _ = sender.send(result);
+    });
+
+    let result = receiver.recv_timeout(budget);
+    let printed = std::mem::take(&mut *printed.lock().unwrap());
+    match result {
+        Ok(result) => {
+            // Forward what the day printed to the capture of the runner
+            for line in printed.lines() {
+                helper::output::line(format_args!("{}", line));
+            }
+            Ok(result)
+        }
+        Err(RecvTimeoutError::Timeout) => Err(printed),
+        Err(RecvTimeoutError::Disconnected) => Ok((
+            Err(format!(
+                "{} of day {} panicked{}",
+                file_name,
+                day,
+                if printed.is_empty() {
+                    String::new()
+                } else {
+                    format!(" after printing:\n{}", printed.trim_end())
+                }
+            )),
+            Timings::new(),
+        )),
+    }
+}
+
+/// Solve the selected parts for each input file and report the answers, checking them against
+/// the recorded ones. The inputs of the files named by `RunSettings::input_names` come from
+/// `next_input`, one at a time until it has no more, so each is dropped before the next one is
+/// read, with `--time`
+/// reporting how long each phase of solving them took. The day stops at the first input that
+/// can't be read, and with `--timeout` once all its inputs took longer than that, skipping the
+/// inputs left. Each part of each input is reported as a test point
+fn process_inputs(
+    year: u16,
+    advent: &Arc<dyn Advent>,
+    next_input: &mut dyn FnMut(&str) -> Option<Result<Input, InputError>>,
+    settings: &RunSettings,
+    answers: &mut AnswerStore,
+    output: &mut Output,
+) -> Summary {
+    let mut summary = Summary::default();
+    let deadline = settings
+        .timeout
+        .map(|timeout| (Instant::now() + timeout, timeout));
+    let day = advent.get_index();
+    let parts: Vec<_> = (1..=advent.part_count())
+        .filter(|&part| settings.parts.includes(part))
+        .collect();
+    let point = |file_name: &str, part, status| TestPoint {
+        year,
+        day,
+        part: Some(part),
+        input: Some(file_name.to_owned()),
+        status,
+        answer: None,
+        duration: None,
+    };
+
+    let file_names = settings.input_names(&**advent);
+    for (idx, file_name) in file_names.iter().enumerate() {
+        let (input, read) = match next_input(file_name) {
+            Some(Ok(input)) => input,
+            None => break,
+            Some(Err(err)) => {
+                let status = match err {
+                    InputError::Cancelled { .. } => {
+                        outln!(output, "Skipping the inputs left of day {}, {}", day, err);
+                        Status::Skipped(err.to_string())
+                    }
+                    _ => {
+                        errln!(
+                            output,
+                            "Could not read the inputs of day {} of {}: {}",
+                            day,
+                            year,
+                            err
+                        );
+                        summary.failed += 1;
+                        Status::Failed(format!("could not read the inputs: {}", err))
+                    }
+                };
+                for file_name in &file_names[idx..] {
+                    for &part in &parts {
+                        output.test_point(&point(file_name, part, status.clone()));
+                    }
+                }
+                break;
+            }
+        };
+        // The day ran once one of its inputs could be read
+        summary.days = 1;
+        outln!(output, "\nProcessing file {}", file_name);
+
+        let start = Instant::now();
+        let (result, timings) = match deadline {
+            None => solve_input(
+                &**advent,
+                file_name,
+                input,
+                settings.parts,
+                settings.parallel_parts,
+            ),
+            Some((deadline, timeout)) => {
+                let budget = deadline.saturating_duration_since(start);
+                let solved = solve_with_timeout(
+                    Arc::clone(advent),
+                    file_name,
+                    input,
+                    settings.parts,
+                    settings.parallel_parts,
+                    budget,
+                );
+                match solved {
+                    Ok(result) => result,
+                    Err(printed) => {
+                        errln!(
+                            output,
+                            "TIMEOUT: day {} did not finish within {:?}, abandoned while solving {}",
+                            day,
+                            timeout,
+                            file_name
+                        );
+                        if !printed.is_empty() {
+                            outln!(output, "Printed before the timeout:");
+                            for line in printed.lines() {
+                                outln!(output, "{}", line);
+                            }
+                        }
+                        summary.timed_out += 1;
+
+                        let reason = format!("timed out after {:?}", timeout);
+                        for file_name in &file_names[idx..] {
+                            for &part in &parts {
+                                output.test_point(&point(
+                                    file_name,
+                                    part,
+                                    Status::Failed(reason.clone()),
+                                ));
+                            }
+                        }
+                        break;
+                    }
+                }
+            }
+        };
+        let elapsed = start.elapsed();
+
+        match result {
+            Ok(results) => {
+                for (part, result) in results {
+                    let computed = result.as_ref().ok().map(ToString::to_string);
+                    let status = match result {
+                        Ok(answer) => {
+                            outln!(output, "Answer to step {}: {}", part, answer);
+
+                            let update = settings.update_answers;
+                            match answers.check(day, part, file_name, &answer, update) {
+                                Check::New => {
+                                    summary.new += 1;
+                                    Status::Passed
+                                }
+                                Check::Same => {
+                                    summary.matching += 1;
+                                    Status::Passed
+                                }
+                                Check::Regression { recorded } => {
+                                    summary.regressions += 1;
+                                    let answer = answer.to_string();
+                                    let colors = output.colors();
+                                    errln!(
+                                        output,
+                                        "REGRESSION: {}{}",
+                                        helper::diff::mismatch(&recorded, &answer, colors),
+                                        if update { ", updated" } else { "" }
+                                    );
+                                    if update {
+                                        Status::Passed
+                                    } else {
+                                        Status::Failed(helper::diff::mismatch(
+                                            &recorded, &answer, false,
+                                        ))
+                                    }
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            errln!(output, "Step {} failed: {}", part, err);
+                            Status::Failed(format!("step {} failed: {}", part, err))
+                        }
+                    };
+                    // Days solving the parts together time them as a whole
+                    let duration = timings
+                        .iter()
+                        .find(|&&(phase, _)| phase == Phase::Part(part))
+                        .or_else(|| timings.iter().find(|&&(phase, _)| phase == Phase::Solve))
+                        .map(|&(_, duration)| duration);
+                    output.test_point(&TestPoint {
+                        answer: computed,
+                        duration,
+                        ..point(file_name, part, status)
+                    });
+                }
+            }
+            Err(err) => {
+                errln!(output, "Could not parse {}:\n{}", file_name, err);
+                for &part in &parts {
+                    let reason = format!("could not parse {}: {}", file_name, err);
+                    output.test_point(&point(file_name, part, Status::Failed(reason)));
+                }
+            }
+        }
+
+        if settings.time {
+            let phases = std::iter::once((Phase::Read, read))
+                .chain(timings)
+                .map(|(phase, duration)| format!("{} {:?}", phase, duration))
+                .join(", ");
+            outln!(output, "Solved in {:?} ({})", elapsed, phases);
+        }
+    }
+
+    summary
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_provided_inputs() {
+    let sample = include_str!("../tests/fixtures/2020/3/test.txt");
+    let inputs = InMemoryProvider::new()
+        .with(2020, 3, "test.txt", sample)
+        .with(2020, 3, "input.txt", sample);
+    let advent = || find_advent(advent_years(), 2020, 3).unwrap();
+    let settings = RunSettings::default();
+
+    let answers = &mut AnswerStore::in_memory();
+    let mut output = Output::captured();
+    let summary = run_day(&inputs, 2020, advent(), &settings, answers, &mut output);
+    assert_eq!(
+        summary,
+        Summary {
+            days: 1,
+            matching: 0,
+            new: 4,
+            regressions: 0,
+            failed: 0,
+            timed_out: 0,
+            slow: 0
+        }
+    );
+
+    let mut output = Output::captured();
+    let inputs = InMemoryProvider::new().with(2020, 3, "test.txt", sample);
+    let summary = run_day(&inputs, 2020, advent(), &settings, answers, &mut output);
+    // The inputs are read one at a time, the ones before the missing one are solved
+    assert_eq!(
+        summary,
+        Summary {
+            days: 1,
+            matching: 2,
+            failed: 1,
+            ..Summary::default()
+        }
+    );
+    assert!(output.as_captured().unwrap().contains(
+        "Could not read the inputs of day 3 of 2020: input.txt of day 3 of 2020 does not exist"
+    ));
+}
+
+#[test]
+fn test_day_output_block() {
+    struct Chatty;
+
+    impl Advent for Chatty {
+        fn get_index(&self) -> u8 {
+            7
+        }
+
+        fn solve(&self, input_file: &str, input: String, _parts: Parts) -> DayResult {
+            print_info!("solving {}", input_file);
+            print_answer!("lines", input.lines().count());
+            Ok(vec![(1, Ok(Answer::from(1u8)))])
+        }
+    }
+
+    let inputs = InMemoryProvider::new().with(2020, 7, "input.txt", "a\nb");
+    let mut output = Output::captured();
+    run_day(
+        &inputs,
+        2020,
+        Box::new(Chatty),
+        &RunSettings::default(),
+        &mut AnswerStore::in_memory(),
+        &mut output,
+    );
+
+    let output = output.as_captured().unwrap();
+    assert_eq!(
+        output,
+        "Running advent day 7...\n\
+         \n\
+         Processing file input.txt\n\
+         Answer to step 1: 1\n\
+         \n\
+         Printed by day 7:\n\
+         solving input.txt\n\
+         lines: 2\n\
+         \n\n"
+    );
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_unreadable_inputs() {
+    let root = TempDir::new("unreadable");
+
+    let inputs = FsProvider::new(root.path());
+    std::fs::create_dir_all(inputs.path(2020, 3, "test.txt")).unwrap();
+    let day4 = find_advent(advent_years(), 2020, 4).unwrap();
+    std::fs::create_dir_all(inputs.folder(2020, 4)).unwrap();
+    for name in day4.get_input_names() {
+        std::fs::write(inputs.path(2020, 4, &name), "").unwrap();
+    }
+    let settings = RunSettings::default();
+    let answers = &mut AnswerStore::in_memory();
+
+    let mut output = Output::captured();
+    let days: Summary = [3, 4]
+        .iter()
+        .map(|&day| {
+            let advent = find_advent(advent_years(), 2020, day).unwrap();
+            run_day(&inputs, 2020, advent, &settings, answers, &mut output)
+        })
+        .sum();
+
+    // Day 3 fails, and day 4 still runs on its empty inputs
+    assert_eq!((days.failed, days.days), (1, 1));
+    let output = output.as_captured().unwrap();
+    assert!(output.contains(&format!(
+        "Could not read the inputs of day 3 of 2020: {} is a directory instead of an input file",
+        std::path::absolute(inputs.path(2020, 3, "test.txt"))
+            .unwrap()
+            .display()
+    )));
+    assert!(output.contains("Running advent day 4..."));
+}
+
+#[cfg(all(feature = "y2020", feature = "y2021"))]
+#[test]
+fn test_unknown_years() {
+    let error = run_years(
+        Path::new("data"),
+        &InMemoryProvider::new(),
+        &[2020, 1999, 2030],
+        &RunSettings::default(),
+        advent_years(),
+        false,
+        &mut Output::captured(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error,
+        "No solution registered for year 1999, the registered years are 2020, 2021. Did you mean 2020?"
+    );
+}
+
+#[test]
+fn test_year_summaries() {
+    let summary = |days, matching| Summary {
+        days,
+        matching,
+        ..Summary::default()
+    };
+
+    let mut output = Output::captured();
+    print_year_summaries(&[(2020, summary(16, 30))], &mut output);
+    assert_eq!(output.as_captured().unwrap(), "");
+
+    print_year_summaries(
+        &[(2020, summary(16, 30)), (2021, summary(0, 0))],
+        &mut output,
+    );
+    assert_eq!(
+        output.as_captured().unwrap(),
+        "Year  Days  Matching  New  Changed  Slow\n\
+         2020    16        30    0        0     0\n\
+         2021     0         0    0        0     0\n"
+    );
+}
+
+#[test]
+fn test_slow_days() {
+    struct Slow;
+
+    impl Advent for Slow {
+        fn get_index(&self) -> u8 {
+            9
+        }
+
+        fn slow(&self) -> bool {
+            true
+        }
+
+        fn solve(&self, _input_file: &str, _input: String, _parts: Parts) -> DayResult {
+            Ok(vec![(1, Ok(Answer::from(1u8)))])
+        }
+    }
+
+    let inputs = InMemoryProvider::new().with(2020, 9, "input.txt", "");
+    let run = |settings: RunSettings| {
+        let mut output = Output::captured().record();
+        let summary = run_day(
+            &inputs,
+            2020,
+            Box::new(Slow),
+            &settings,
+            &mut AnswerStore::in_memory(),
+            &mut output,
+        );
+        (summary, output.points()[0].status.clone())
+    };
+
+    let (summary, status) = run(RunSettings::default());
+    assert_eq!((summary.days, summary.slow), (0, 1));
+    assert_eq!(
+        status,
+        Status::Skipped("slow, pass --include-slow to run it".to_owned())
+    );
+
+    // Selected explicitly or included, the day runs
+    let (summary, _) = run(RunSettings {
+        day: Some(9),
+        ..RunSettings::default()
+    });
+    assert_eq!((summary.days, summary.slow), (1, 0));
+    let (summary, _) = run(RunSettings {
+        include_slow: true,
+        ..RunSettings::default()
+    });
+    assert_eq!((summary.days, summary.slow), (1, 0));
+
+    let runner = |include_slow| {
+        Runner::new(vec![AdventYear::new(2020, vec![Box::new(Slow)])]).with_settings(RunSettings {
+            include_slow,
+            ..RunSettings::default()
+        })
+    };
+    assert_eq!(runner(false).left_out(&Selection::All), [(2020, 9)]);
+    assert_eq!(runner(false).left_out(&Selection::Year(2021)), []);
+    assert_eq!(runner(false).left_out(&Selection::Day(2020, 9)), []);
+    assert_eq!(runner(true).left_out(&Selection::Year(2020)), []);
+}
+
+#[cfg(feature = "y2021")]
+#[test]
+fn test_skipped_year() {
+    let inputs = &InMemoryProvider::new();
+    let advent_year = advent_years()
+        .into_iter()
+        .find(|advent_year| advent_year.get_year() == 2021)
+        .unwrap();
+
+    let mut output = Output::captured();
+    let summary = run_year(
+        Path::new("unused"),
+        inputs,
+        &RunSettings::default(),
+        advent_year,
+        true,
+        &mut output,
+    );
+
+    assert_eq!(summary, Summary::default());
+    assert_eq!(
+        output.as_captured().unwrap(),
+        "Running year 2021\nNothing to run for year 2021, its 25 days are all skipped. Run `list` to see the implemented days\n"
+    );
+}
+
+#[test]
+fn test_median() {
+    let durations =
+        |millis: &[u64]| -> Vec<_> { millis.iter().copied().map(Duration::from_millis).collect() };
+
+    assert_eq!(median(&durations(&[3])), Duration::from_millis(3));
+    assert_eq!(median(&durations(&[1, 2, 10])), Duration::from_millis(2));
+    assert_eq!(median(&durations(&[1, 2, 4, 10])), Duration::from_millis(3));
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_runner() {
+    let inputs = InMemoryProvider::new()
+        .with(2020, 18, "test.txt", "2 * 3 + (4 * 5)")
+        .with(2020, 18, "input.txt", "1 +");
+    let runner = || {
+        Runner::new(advent_years())
+            .with_data_dir("unused")
+            .with_provider(&inputs)
+    };
+
+    let report = runner().run(Selection::Day(2020, 18)).unwrap();
+    assert_eq!(report.answer(2020, 18, 1, "test.txt"), Some("26"));
+    assert_eq!(report.answer(2020, 18, 2, "test.txt"), Some("46"));
+    assert_eq!(report.answer(2020, 18, 1, "input.txt"), None);
+    assert_eq!(
+        report.failures().map(TestPoint::name).collect::<Vec<_>>(),
+        [
+            "2020 day 18 part 1 (input.txt)",
+            "2020 day 18 part 2 (input.txt)"
+        ]
+    );
+    assert_eq!(
+        report.summary(),
+        Summary {
+            days: 1,
+            new: 2,
+            ..Summary::default()
+        }
+    );
+
+    let settings = RunSettings {
+        parts: Parts::Only(2),
+        ..RunSettings::default()
+    };
+    let report = runner()
+        .with_settings(settings)
+        .run(Selection::Day(2020, 18))
+        .unwrap();
+    assert_eq!(report.points.len(), 2);
+    assert_eq!(report.answer(2020, 18, 1, "test.txt"), None);
+
+    if cfg!(feature = "y2021") {
+        assert_eq!(
+            runner().run(Selection::Days(Vec::new(), 18)).unwrap_err(),
+            "Day 18 of 2021 is not implemented, no day of 2021 is"
+        );
+    }
+    assert!(runner()
+        .run(Selection::Day(2020, 1))
+        .unwrap_err()
+        .starts_with("Day 1 of 2020 is not implemented"));
+}
+
+#[test]
+fn test_custom_registry_timeout() {
+    /// Day of a year the built-in registry doesn't have
+    struct Custom;
+
+    impl Advent for Custom {
+        fn get_index(&self) -> u8 {
+            1
+        }
+
+        fn part_count(&self) -> u8 {
+            1
+        }
+
+        fn solve(&self, _input_file: &str, input: String, _parts: Parts) -> DayResult {
+            Ok(vec![(1, Ok(format!("custom {}", input).into()))])
+        }
+    }
+
+    let inputs = InMemoryProvider::new().with(2015, 1, "input.txt", "(()");
+    let report = Runner::new(vec![AdventYear::new(2015, vec![Box::new(Custom)])])
+        .with_data_dir("unused")
+        .with_provider(&inputs)
+        .with_settings(RunSettings {
+            timeout: Some(Duration::from_secs(60)),
+            ..RunSettings::default()
+        })
+        .run(Selection::Day(2015, 1))
+        .unwrap();
+
+    // Solved by the day of the runner on the thread of the timeout
+    assert_eq!(report.answer(2015, 1, 1, "input.txt"), Some("custom (()"));
+    assert_eq!(report.failures().count(), 0);
+}
+
+#[test]
+fn test_unavailable_day() {
+    use crate::advents::SkippedAdvent;
+
+    struct Implemented(u8);
+
+    impl Advent for Implemented {
+        fn get_index(&self) -> u8 {
+            self.0
+        }
+
+        fn solve(&self, _input_file: &str, _input: String, _parts: Parts) -> DayResult {
+            Ok(Vec::new())
+        }
+    }
+
+    let advent_year = AdventYear::new(
+        2020,
+        vec![
+            Box::new(Implemented(8)),
+            Box::new(SkippedAdvent::new(5)),
+            Box::new(Implemented(3)),
+        ],
+    );
+    assert_eq!(
+        unavailable_day(&advent_year, 5),
+        "Day 5 of 2020 is not implemented, the implemented days are 3, 8 and the skipped ones 5. Did you mean 3?"
+    );
+    assert_eq!(
+        unavailable_day(&advent_year, 6),
+        "Day 6 of 2020 is not implemented, the implemented days are 3, 8 and the skipped ones 5. Did you mean 8?"
+    );
+    assert_eq!(
+        unavailable_day(
+            &AdventYear::new(2021, vec![Box::new(SkippedAdvent::new(1))]),
+            1
+        ),
+        "Day 1 of 2021 is not implemented, no day of 2021 is"
+    );
+
+    assert_eq!(
+        unknown_year(&[advent_year], 2002),
+        "No solution registered for year 2002, the registered years are 2020. Did you mean 2020?"
+    );
+    assert_eq!(
+        unknown_year(&[], 2020),
+        "No solution registered for year 2020, no year is registered"
+    );
+    assert_eq!(closest(2019, &[2018, 2020]), Some(2018));
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_selected_inputs() {
+    // Only the selected input is read, reading the other one would fail
+    let inputs = InMemoryProvider::new().with(2020, 18, "test.txt", "1 + 2 * 3");
+    let run = |names: &[&str]| {
+        let mut output = Output::captured().record();
+        let settings = RunSettings {
+            inputs: names.iter().map(|&name| name.to_owned()).collect(),
+            ..RunSettings::default()
+        };
+        let summary = run_day(
+            &inputs,
+            2020,
+            find_advent(advent_years(), 2020, 18).unwrap(),
+            &settings,
+            &mut AnswerStore::in_memory(),
+            &mut output,
+        );
+        (summary, output.points().to_vec())
+    };
+
+    let (summary, points) = run(&["test.txt"]);
+    assert_eq!((summary.days, summary.failed, summary.new), (1, 0, 2));
+    let labels: Vec<_> = points.iter().map(TestPoint::name).collect();
+    assert_eq!(
+        labels,
+        [
+            "2020 day 18 part 1 (test.txt)",
+            "2020 day 18 part 2 (test.txt)"
+        ]
+    );
+
+    let (summary, points) = run(&["other.txt"]);
+    assert_eq!(summary.days, 0);
+    assert_eq!(
+        points[0].status,
+        Status::Skipped("none of its inputs is selected".to_owned())
+    );
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_mapped_inputs_borrowed() {
+    use crate::inputs::MMAP_THRESHOLD;
+
+    /// Tells whether it was given its input or borrowed it
+    struct Lengths;
+
+    impl Advent for Lengths {
+        fn get_index(&self) -> u8 {
+            1
+        }
+
+        fn part_count(&self) -> u8 {
+            1
+        }
+
+        fn get_input_names(&self) -> Vec<String> {
+            vec!["test.txt".to_owned(), "input.txt".to_owned()]
+        }
+
+        fn solve(&self, _input_file: &str, input: String, _parts: Parts) -> DayResult {
+            Ok(vec![(1, Ok(format!("owned {}", input.len()).into()))])
+        }
+
+        fn solve_borrowed(
+            &self,
+            _input_file: &str,
+            input: &str,
+            _parts: Parts,
+            _concurrently: bool,
+        ) -> (DayResult, Timings) {
+            let answer = format!("borrowed {}", input.len()).into();
+            (Ok(vec![(1, Ok(answer))]), Timings::new())
+        }
+    }
+
+    let root = TempDir::new("mapped-inputs");
+    let inputs = FsProvider::new(root.path());
+    std::fs::create_dir_all(inputs.folder(2020, 1)).unwrap();
+    std::fs::write(inputs.path(2020, 1, "test.txt"), "12").unwrap();
+    std::fs::write(
+        inputs.path(2020, 1, "input.txt"),
+        "1".repeat(MMAP_THRESHOLD as usize),
+    )
+    .unwrap();
+
+    let mut output = Output::captured().record();
+    run_day(
+        &inputs,
+        2020,
+        Box::new(Lengths),
+        &RunSettings::default(),
+        &mut AnswerStore::in_memory(),
+        &mut output,
+    );
+
+    let answers: Vec<_> = output
+        .points()
+        .iter()
+        .map(|point| point.answer.as_deref())
+        .collect();
+    assert_eq!(
+        answers,
+        [
+            Some("owned 2"),
+            Some(&*format!("borrowed {}", MMAP_THRESHOLD))
+        ]
+    );
+}
+
+#[cfg(feature = "y2020")]
+#[test]
+fn test_missing_inputs_not_given() {
+    let dir = TempDir::new("not-given");
+    let root = dir.join("data");
+    let inputs = FsProvider::new(&root).asking_missing(|_| None);
+
+    let mut output = Output::captured().record();
+    let summary = run_day(
+        &inputs,
+        2020,
+        find_advent(advent_years(), 2020, 18).unwrap(),
+        &RunSettings::default(),
+        &mut AnswerStore::in_memory(),
+        &mut output,
+    );
+
+    // Skipped rather than failed, and nothing is created
+    assert_eq!((summary.days, summary.failed), (0, 0));
+    assert!(matches!(
+        &output.points()[0].status,
+        Status::Skipped(reason) if reason.ends_with("test.txt is missing and was not pasted")
+    ));
+    assert!(!root.exists());
+}
+
+#[test]
+fn test_inputs_read_one_at_a_time() {
+    use crate::helper::counting_alloc::peak_memory;
+
+    const SIZE: usize = 4 << 20;
+
+    struct Large;
+
+    impl Advent for Large {
+        fn get_index(&self) -> u8 {
+            16
+        }
+
+        fn get_input_names(&self) -> Vec<String> {
+            vec!["a.txt".to_owned(), "b.txt".to_owned(), "c.txt".to_owned()]
+        }
+
+        fn solve(&self, _input_file: &str, input: String, _parts: Parts) -> DayResult {
+            Ok(vec![(1, Ok(Answer::from(input.len() as u64)))])
+        }
+    }
+
+    let inputs = ["a.txt", "b.txt", "c.txt"]
+        .iter()
+        .fold(InMemoryProvider::new(), |inputs, name| {
+            inputs.with(2020, 16, name, "#".repeat(SIZE))
+        });
+
+    // Read beforehand, the three inputs are in memory together
+    let names = Large.get_input_names();
+    let (_, preloaded) = peak_memory(|| read_inputs(&inputs, 2020, 16, &names).unwrap());
+    assert!(preloaded >= 3 * SIZE, "peak of {} bytes", preloaded);
+
+    let mut output = Output::captured();
+    let (summary, lazy) = peak_memory(|| {
+        run_day(
+            &inputs,
+            2020,
+            Box::new(Large),
+            &RunSettings::default(),
+            &mut AnswerStore::in_memory(),
+            &mut output,
+        )
+    });
+    assert_eq!((summary.days, summary.new), (1, 3));
+    assert!(lazy < 2 * SIZE, "peak of {} bytes", lazy);
+}
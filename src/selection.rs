@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use crate::helper::temp::TempDir;
+
+/// Name of the file in the data folder remembering the last selection
+pub const LAST_SELECTION_FILE: &str = "last-selection.json";
+
+/// Day picked in the last interactive run, offered as the default of the next one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastSelection {
+    pub year: u16,
+    pub day: u8,
+}
+
+impl LastSelection {
+    /// Selection saved in a file, `None` when it is missing or unreadable since it is only a
+    /// convenience
+    pub fn load(path: impl AsRef<Path>) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let content = serde_json::to_string(self).expect("selections are valid JSON");
+
+        std::fs::write(path, content + "\n")
+            .map_err(|err| format!("could not write {}: {}", path.display(), err))
+    }
+}
+
+#[test]
+fn test_save_and_load() {
+    let dir = TempDir::new("selection");
+    let path = dir.join("selection.json");
+
+    assert_eq!(LastSelection::load(&path), None);
+
+    let selection = LastSelection {
+        year: 2020,
+        day: 18,
+    };
+    selection.save(&path).unwrap();
+    assert_eq!(LastSelection::load(&path), Some(selection));
+
+    std::fs::write(&path, "{\"year\": 2020").unwrap();
+    assert_eq!(LastSelection::load(&path), None);
+}
@@ -0,0 +1,193 @@
+use std::path::Path;
+
+#[cfg(test)]
+use crate::helper::temp::TempDir;
+use crate::inputs::{FsProvider, USER_AGENT};
+
+/// Name of the file next to the input of a day caching the answers the site accepted
+pub const SITE_ANSWERS_FILE: &str = "site-answers.json";
+
+/// Page of a day on adventofcode.com, with the puzzle and, once logged in, the answers given
+pub fn fetch_day_page(year: u16, day: u8, session: Option<&str>) -> Result<String, String> {
+    let url = format!("https://adventofcode.com/{}/day/{}", year, day);
+
+    let mut request = ureq::get(&url).set("User-Agent", USER_AGENT);
+    if let Some(session) = session {
+        request = request.set("Cookie", &format!("session={}", session));
+    }
+
+    request
+        .call()
+        .map_err(|err| {
+            format!(
+                "could not download the page of day {} of {}: {}",
+                day, year, err
+            )
+        })?
+        .into_string()
+        .map_err(|err| format!("could not read {}: {}", url, err))
+}
+
+/// Title in the `--- Day 18: Operation Order ---` heading of the page of a day
+pub fn scrape_title(page: &str, day: u8) -> Option<String> {
+    let heading = format!("--- Day {}: ", day);
+    let start = page.find(&heading)? + heading.len();
+    let end = start + page[start..].find(" ---")?;
+
+    let title = decode_entities(page[start..end].trim());
+    if title.is_empty() || title.contains('<') {
+        return None;
+    }
+
+    Some(title)
+}
+
+/// Answers accepted for each part in the page of a day, in the `Your puzzle answer was` paragraphs
+/// shown once a part is solved
+pub fn scrape_answers(page: &str) -> Vec<String> {
+    const PREFIX: &str = "Your puzzle answer was <code>";
+
+    page.match_indices(PREFIX)
+        .filter_map(|(start, _)| {
+            let answer = &page[start + PREFIX.len()..];
+            answer
+                .find("</code>")
+                .map(|end| decode_entities(&answer[..end]))
+        })
+        .collect()
+}
+
+/// Answers the site accepted for the parts of a day, from the cache next to its input or else
+/// from the page of the day, which needs the session cookie. They are only cached once every
+/// part is solved, the others can still be solved later
+pub fn site_answers(
+    data_dir: &Path,
+    year: u16,
+    day: u8,
+    parts: u8,
+    session: Option<&str>,
+) -> Result<Vec<String>, String> {
+    let path = FsProvider::new(data_dir)
+        .folder(year, day)
+        .join(SITE_ANSWERS_FILE);
+    let cached: Option<Vec<String>> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    if let Some(answers) = cached {
+        return Ok(answers);
+    }
+
+    let session = session.ok_or_else(|| {
+        format!(
+            "the answers of day {} of {} are not cached, fetching them needs the session cookie of adventofcode.com in AOC_SESSION",
+            day, year
+        )
+    })?;
+    let answers = scrape_answers(&fetch_day_page(year, day, Some(session))?);
+    if answers.len() >= usize::from(parts) {
+        let content = serde_json::to_string(&answers).expect("answers are valid JSON");
+        let saved = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, content + "\n"));
+        if let Err(err) = saved {
+            log::warn!("could not write {}: {}", path.display(), err);
+        }
+    }
+
+    Ok(answers)
+}
+
+/// Text of HTML with its character references replaced, the site only uses the common ones
+pub fn decode_entities(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('&') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').and_then(|end| {
+            let character = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                code => {
+                    let code = code.strip_prefix('#')?;
+                    let code = match code.strip_prefix('x') {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => code.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((character, end))
+        });
+
+        match decoded {
+            Some((character, end)) => {
+                text.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                text.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+
+    text
+}
+
+#[test]
+fn test_scrape_title() {
+    let page = include_str!("../tests/fixtures/pages/2020-18.html");
+
+    assert_eq!(scrape_title(page, 18).as_deref(), Some("Operation Order"));
+    assert_eq!(scrape_title(page, 17), None);
+    assert_eq!(scrape_title("<h2>--- Day 1: ---</h2>", 1), None);
+    assert_eq!(
+        scrape_title("<h2>--- Day 4: Passport &amp; Friends ---</h2>", 4).as_deref(),
+        Some("Passport & Friends")
+    );
+}
+
+#[test]
+fn test_scrape_answers() {
+    let page = include_str!("../tests/fixtures/pages/2020-18.html");
+    assert!(scrape_answers(page).is_empty());
+
+    let page = include_str!("../tests/fixtures/pages/2020-18-solved.html");
+    assert_eq!(scrape_answers(page), ["4940631886147", "283582817678281"]);
+}
+
+#[test]
+fn test_site_answers() {
+    let data_dir = TempDir::new("site-answers");
+    let folder = FsProvider::new(data_dir.path()).folder(2020, 18);
+    std::fs::create_dir_all(&folder).unwrap();
+    std::fs::write(folder.join(SITE_ANSWERS_FILE), "[\"26\", \"46\"]").unwrap();
+
+    // Cached answers don't need the site
+    assert_eq!(
+        site_answers(&data_dir, 2020, 18, 2, None),
+        Ok(vec!["26".to_owned(), "46".to_owned()])
+    );
+    assert!(site_answers(&data_dir, 2020, 19, 2, None)
+        .unwrap_err()
+        .contains("AOC_SESSION"));
+}
+
+#[test]
+fn test_decode_entities() {
+    assert_eq!(decode_entities("a &lt;b&gt; &quot;c&quot;"), "a <b> \"c\"");
+    assert_eq!(decode_entities("&#39;&#x41;"), "'A");
+    assert_eq!(
+        decode_entities("fish & chips &unknown;"),
+        "fish & chips &unknown;"
+    );
+    assert_eq!(decode_entities("trailing &"), "trailing &");
+}
@@ -0,0 +1,233 @@
+//! Dates of the advent. The puzzles unlock at midnight in UTC-5, the site keeping US/Eastern
+//! standard time all year round, so the day of the advent doesn't depend on the local time zone
+
+use crate::output::utc_timestamp;
+
+/// Offset of the midnight of the site from the one of UTC
+pub const UNLOCK_OFFSET_SECS: u64 = 5 * 3600;
+
+/// Seconds since 1970-01-01, the clock of the functions below outside of the tests
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Number of days since 1970-01-01 of a date, following the `days_from_civil` algorithm of
+/// http://howardhinnant.github.io/date_algorithms.html
+pub fn day_of_date(year: u16, month: u8, day: u8) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let march_based_month = (i64::from(month) + 9) % 12;
+    let day_of_year = (153 * march_based_month + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Year, month and day of a number of days since 1970-01-01, following the `civil_from_days`
+/// algorithm of http://howardhinnant.github.io/date_algorithms.html
+pub fn date_of_day(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // Years start in March, so January and February belong to the next one
+    let march_based_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * march_based_month + 2) / 5 + 1;
+    let month = if march_based_month < 10 {
+        march_based_month + 3
+    } else {
+        march_based_month - 9
+    };
+
+    (
+        year_of_era + era * 400 + i64::from(month <= 2),
+        month as u8,
+        day as u8,
+    )
+}
+
+/// Year of a number of days since 1970-01-01
+pub fn year_of_day(days: i64) -> u16 {
+    date_of_day(days).0 as u16
+}
+
+/// Days since 1970-01-01 in the time zone of the site
+fn site_day(now: u64) -> i64 {
+    (now.saturating_sub(UNLOCK_OFFSET_SECS) / 86400) as i64
+}
+
+/// Seconds since 1970-01-01 when the puzzle of a day unlocks
+pub fn unlock_time(year: u16, day: u8) -> u64 {
+    day_of_date(year, 12, day) as u64 * 86400 + UNLOCK_OFFSET_SECS
+}
+
+/// Whether the puzzle of a day, and so its input, is available at `now`
+pub fn is_unlocked(year: u16, day: u8, now: u64) -> bool {
+    now >= unlock_time(year, day)
+}
+
+/// Year and day of the advent at `now` on the site, none outside of the 1st to the 25th of
+/// December
+pub fn advent_day(now: u64) -> Option<(u16, u8)> {
+    let day = site_day(now);
+    let year = year_of_day(day);
+    let day_of_advent = day - day_of_date(year, 12, 1) + 1;
+
+    (1..=25)
+        .contains(&day_of_advent)
+        .then_some((year, day_of_advent as u8))
+}
+
+/// Year of the last event that started at `now`
+pub fn last_event_year(now: u64) -> u16 {
+    let day = site_day(now);
+    let year = year_of_day(day);
+
+    if day >= day_of_date(year, 12, 1) {
+        year
+    } else {
+        year - 1
+    }
+}
+
+/// Year, day and time in seconds since 1970-01-01 of the first puzzle unlocking after `now`
+pub fn next_unlock(now: u64) -> (u16, u8, u64) {
+    let (year, day) = match advent_day(now) {
+        Some((year, day)) if day < 25 => (year, day + 1),
+        Some((year, _)) => (year + 1, 1),
+        // Before the advent of the year or the 26th of December and later
+        None => {
+            let day = site_day(now);
+            let year = year_of_day(day);
+
+            if day < day_of_date(year, 12, 1) {
+                (year, 1)
+            } else {
+                (year + 1, 1)
+            }
+        }
+    };
+
+    (year, day, unlock_time(year, day))
+}
+
+/// Time left like `2d 03:14:15`, the days being left out when there are none
+pub fn countdown(secs: u64) -> String {
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    );
+
+    match secs / 86400 {
+        0 => time,
+        days => format!("{}d {}", days, time),
+    }
+}
+
+/// Why the puzzle of a day can't be fetched yet at `now`, like `day 5 of 2021 unlocks in
+/// 03:14:15, at 2021-12-05 05:00:00 UTC`
+pub fn locked_message(year: u16, day: u8, now: u64) -> String {
+    let unlock = unlock_time(year, day);
+
+    format!(
+        "day {} of {} unlocks in {}, at {}",
+        day,
+        year,
+        countdown(unlock.saturating_sub(now)),
+        utc_timestamp(unlock)
+    )
+}
+
+#[cfg(test)]
+fn at(year: u16, month: u8, day: u8, hour: u64, minute: u64) -> u64 {
+    day_of_date(year, month, day) as u64 * 86400 + hour * 3600 + minute * 60
+}
+
+#[test]
+fn test_dates() {
+    assert_eq!(year_of_day(0), 1970);
+    assert_eq!(year_of_day(-1), 1969);
+    assert_eq!(year_of_day(18627), 2020);
+    assert_eq!(year_of_day(18628), 2021);
+    assert_eq!(year_of_day(18321), 2020, "2020-02-29");
+    assert_eq!(date_of_day(18321), (2020, 2, 29));
+    assert_eq!(date_of_day(18322), (2020, 3, 1));
+
+    assert_eq!(day_of_date(1970, 1, 1), 0);
+    assert_eq!(day_of_date(2020, 2, 29), 18321);
+    assert_eq!(day_of_date(2020, 12, 31), 18627);
+    assert_eq!(unlock_time(2020, 1), 1_606_798_800);
+}
+
+#[test]
+fn test_advent_day() {
+    // Midnight on the site is 05:00 UTC
+    assert_eq!(advent_day(at(2021, 12, 1, 4, 59)), None);
+    assert_eq!(advent_day(at(2021, 12, 1, 5, 0)), Some((2021, 1)));
+    assert_eq!(advent_day(at(2021, 12, 2, 4, 59)), Some((2021, 1)));
+    assert_eq!(advent_day(at(2021, 12, 25, 12, 0)), Some((2021, 25)));
+    assert_eq!(advent_day(at(2021, 12, 26, 5, 0)), None);
+    assert_eq!(advent_day(at(2021, 11, 28, 12, 0)), None);
+
+    assert_eq!(last_event_year(at(2020, 11, 30, 23, 0)), 2019);
+    assert_eq!(last_event_year(at(2020, 12, 1, 4, 59)), 2019);
+    assert_eq!(last_event_year(at(2020, 12, 1, 5, 0)), 2020);
+    assert_eq!(last_event_year(at(2021, 1, 1, 2, 0)), 2020);
+    assert_eq!(last_event_year(at(2021, 6, 1, 0, 0)), 2020);
+}
+
+#[test]
+fn test_is_unlocked() {
+    assert!(!is_unlocked(2021, 1, at(2021, 11, 30, 23, 0)));
+    assert!(!is_unlocked(2021, 1, at(2021, 12, 1, 4, 59)));
+    assert!(is_unlocked(2021, 1, at(2021, 12, 1, 5, 0)));
+    assert!(!is_unlocked(2021, 2, at(2021, 12, 1, 5, 0)));
+    assert!(is_unlocked(2020, 25, at(2021, 6, 1, 0, 0)));
+
+    assert_eq!(
+        locked_message(2021, 5, at(2021, 12, 5, 1, 45)),
+        "day 5 of 2021 unlocks in 03:15:00, at 2021-12-05 05:00:00 UTC"
+    );
+}
+
+#[test]
+fn test_next_unlock() {
+    let unlock = |year, day| at(year, 12, day, 5, 0);
+
+    assert_eq!(
+        next_unlock(at(2021, 6, 1, 12, 0)),
+        (2021, 1, unlock(2021, 1))
+    );
+    // Still the 30th of November on the site
+    assert_eq!(
+        next_unlock(at(2021, 12, 1, 4, 0)),
+        (2021, 1, unlock(2021, 1))
+    );
+    assert_eq!(
+        next_unlock(at(2021, 12, 1, 5, 0)),
+        (2021, 2, unlock(2021, 2))
+    );
+    assert_eq!(
+        next_unlock(at(2021, 12, 24, 23, 0)),
+        (2021, 25, unlock(2021, 25))
+    );
+    assert_eq!(
+        next_unlock(at(2021, 12, 25, 5, 0)),
+        (2022, 1, unlock(2022, 1))
+    );
+    // Still the 31st of December on the site
+    assert_eq!(
+        next_unlock(at(2022, 1, 1, 2, 0)),
+        (2022, 1, unlock(2022, 1))
+    );
+
+    assert_eq!(countdown(59), "00:00:59");
+    assert_eq!(countdown(3 * 86400 + 3723), "3d 01:02:03");
+}
@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::advents::Advent;
+#[cfg(test)]
+use crate::helper::temp::TempDir;
+use crate::site;
+
+/// Name of the file in the folder of each year caching the titles of its puzzles
+pub const TITLES_FILE: &str = "titles.json";
+
+/// Titles of the puzzles of a year, downloaded once since they never change
+#[derive(Debug, Default)]
+pub struct TitleCache {
+    /// File the titles are saved to, nothing is saved when `None`
+    path: Option<PathBuf>,
+    titles: BTreeMap<u8, String>,
+    changed: bool,
+}
+
+impl TitleCache {
+    /// Cache kept in memory only
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Titles cached in the folder of a year. A missing or unreadable cache is empty, the titles
+    /// are only a convenience
+    pub fn load(data_dir: &Path, year: u16) -> Self {
+        let path = data_dir.join(year.to_string()).join(TITLES_FILE);
+        let titles = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            path: Some(path),
+            titles,
+            changed: false,
+        }
+    }
+
+    pub fn get(&self, day: u8) -> Option<&str> {
+        self.titles.get(&day).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, day: u8, title: impl Into<String>) {
+        self.titles.insert(day, title.into());
+        self.changed = true;
+    }
+
+    /// Title of a day, the cached one or else the one of its implementation
+    pub fn title_of<'a>(&'a self, advent: &'a dyn Advent) -> Option<&'a str> {
+        self.get(advent.get_index()).or_else(|| advent.title())
+    }
+
+    /// Download the titles of the given days that are not cached yet, returning the days whose
+    /// title could not be found. The downloads stop at the first one failing, the site being
+    /// unreachable or refusing them
+    pub fn fetch_missing(&mut self, year: u16, days: &[u8], session: Option<&str>) -> Vec<u8> {
+        let mut missing: Vec<_> = days
+            .iter()
+            .copied()
+            .filter(|&day| self.get(day).is_none())
+            .collect();
+
+        while let Some(&day) = missing.first() {
+            let page = match site::fetch_day_page(year, day, session) {
+                Ok(page) => page,
+                Err(err) => {
+                    log::warn!("{}", err);
+                    break;
+                }
+            };
+
+            missing.remove(0);
+            match site::scrape_title(&page, day) {
+                Some(title) => self.insert(day, title),
+                None => log::warn!("no title in the page of day {} of {}", day, year),
+            }
+        }
+
+        missing
+    }
+
+    /// Save the titles if some were added
+    pub fn save(&self) -> Result<(), String> {
+        let path = match &self.path {
+            Some(path) if self.changed => path,
+            _ => return Ok(()),
+        };
+        let content = serde_json::to_string_pretty(&self.titles).expect("titles are valid JSON");
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| format!("could not create {}: {}", parent.display(), err))?;
+        }
+        std::fs::write(path, content + "\n")
+            .map_err(|err| format!("could not write {}: {}", path.display(), err))
+    }
+}
+
+#[test]
+fn test_title_cache() {
+    let dir = TempDir::new("titles");
+    let data_dir = dir.join("data");
+
+    let mut titles = TitleCache::load(&data_dir, 2020);
+    assert_eq!(titles.get(18), None);
+    titles.save().unwrap();
+    assert!(!data_dir.exists(), "nothing to save");
+    titles.insert(18, "Operation Order");
+    // Cached titles are not downloaded again
+    assert!(titles.fetch_missing(2020, &[18], None).is_empty());
+    titles.save().unwrap();
+
+    let titles = TitleCache::load(&data_dir, 2020);
+    assert_eq!(titles.get(18), Some("Operation Order"));
+    assert_eq!(
+        std::fs::read_to_string(data_dir.join("2020").join(TITLES_FILE)).unwrap(),
+        "{\n  \"18\": \"Operation Order\"\n}\n"
+    );
+
+    let skipped = crate::advents::SkippedAdvent::new(18);
+    assert_eq!(titles.title_of(&skipped), Some("Operation Order"));
+    assert_eq!(TitleCache::in_memory().title_of(&skipped), None);
+
+    std::fs::write(data_dir.join("2020").join(TITLES_FILE), "{").unwrap();
+    assert_eq!(TitleCache::load(&data_dir, 2020).get(18), None);
+}
@@ -0,0 +1,14 @@
+pub struct AdventDayXX {
+    lines: Vec<String>,
+}
+
+stateful_day! {
+    AdventDayXX {
+        input_files: ["test.txt", "input.txt"],
+        parse(input) => Ok(Self {
+            lines: input.lines().map(str::to_owned).collect(),
+        }),
+        part1(day) => Err::<u64, _>(format!("not solved yet ({} lines of input)", day.lines.len())),
+        part2(day) => Err::<u64, _>(format!("not solved yet ({} lines of input)", day.lines.len())),
+    }
+}
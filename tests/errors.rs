@@ -0,0 +1,77 @@
+//! Errors of the command line when the selected year or day can't run
+
+use std::process::Command;
+
+use advent_of_rust::helper::temp::TempDir;
+
+/// Exit code and standard error of the command line run with `arguments`, without prompts or
+/// a summary of the run
+fn run(arguments: &[&str]) -> (Option<i32>, String) {
+    let data_dir = TempDir::new("errors");
+    let output = Command::new(env!("CARGO_BIN_EXE_advent-of-rust"))
+        .args(arguments)
+        .arg("--data-dir")
+        .arg(data_dir.path())
+        .args(["--no-interactive", "--no-manifest"])
+        .output()
+        .expect("the command line runs");
+
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn unknown_year() {
+    let (code, stderr) = run(&["2002", "18"]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("there was no Advent of Code in 2002"));
+    assert!(stderr.contains("the registered years are 2020"));
+    assert!(stderr.contains("Did you mean 2020?"));
+
+    let (code, stderr) = run(&["verify", "2016"]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("No solution registered for year 2016, the registered years are 2020"));
+}
+
+#[test]
+fn unimplemented_day() {
+    let (code, stderr) = run(&["2020", "5"]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("Day 5 of 2020 is not implemented, the implemented days are 3, 4, 7,"));
+    assert!(stderr.contains("and the skipped ones 1, 2, 5, 6,"));
+    assert!(stderr.contains("Did you mean 4?"));
+
+    let (_, stderr) = run(&["bench", "2020", "22"]);
+    assert!(stderr.contains("Day 22 of 2020 is not implemented"));
+    assert!(stderr.contains("Did you mean "));
+}
+
+#[test]
+fn out_of_range_day() {
+    let (code, stderr) = run(&["2020", "26"]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("26 is not in 1..=25"));
+}
+
+#[test]
+fn unknown_input() {
+    let (code, stderr) = run(&["2020", "18", "--only-file", "test2.txt"]);
+    assert_eq!(code, Some(2));
+    assert!(stderr
+        .contains("day 18 of 2020 has no input test2.txt, its inputs are test.txt, input.txt"));
+}
+
+#[test]
+fn missing_day() {
+    let (code, stderr) = run(&["2020"]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("Prompts are disabled, pass the year and the day, or --all."));
+    assert!(stderr.contains("The implemented days are 2020: 3, 4, 7,"));
+    assert!(stderr.contains("Usage:"));
+
+    let (code, stderr) = run(&[]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("The implemented days are 2020: 3, 4, 7,"));
+}
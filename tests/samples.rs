@@ -0,0 +1,133 @@
+//! Run each implemented day on the samples published with the puzzles
+
+use std::path::Path;
+
+use advent_of_rust::advents::Answer;
+use advent_of_rust::inputs::{FsProvider, InputProvider};
+use advent_of_rust::run_day_for_test;
+
+/// Answers of every part of a 2020 day for one of its fixtures
+fn answers(day: u8, fixture: &str) -> Vec<Answer> {
+    let fixtures = FsProvider::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"));
+    let input = fixtures
+        .fetch(2020, day, fixture)
+        .unwrap_or_else(|err| panic!("{}", err));
+
+    run_day_for_test(2020, day, &input)
+        .unwrap_or_else(|err| panic!("could not parse {} of day {}: {}", fixture, day, err))
+        .into_iter()
+        .map(|(part, answer)| {
+            answer.unwrap_or_else(|err| panic!("part {} of day {} failed: {}", part, day, err))
+        })
+        .collect()
+}
+
+#[test]
+fn day03() {
+    assert_eq!(answers(3, "test.txt"), ["7", "336"]);
+}
+
+#[test]
+fn day04() {
+    assert_eq!(answers(4, "test.txt"), ["2", "2"]);
+    assert_eq!(answers(4, "test2.txt"), ["8", "4"]);
+}
+
+#[test]
+fn day07() {
+    assert_eq!(answers(7, "test.txt"), ["4", "32"]);
+    assert_eq!(answers(7, "test2.txt"), ["0", "126"]);
+}
+
+#[test]
+fn day08() {
+    assert_eq!(answers(8, "test.txt"), ["5", "8"]);
+}
+
+#[test]
+fn day10() {
+    assert_eq!(answers(10, "test.txt"), ["35", "8"]);
+    assert_eq!(answers(10, "test2.txt"), ["220", "19208"]);
+}
+
+#[test]
+fn day11() {
+    assert_eq!(answers(11, "test.txt"), ["37", "26"]);
+}
+
+#[test]
+fn day13() {
+    assert_eq!(answers(13, "test.txt"), ["295", "1068781"]);
+}
+
+#[cfg(feature = "heavy-days")]
+#[test]
+#[ignore = "thirty million turns, run with --ignored in release mode"]
+fn day15() {
+    assert_eq!(answers(15, "test.txt"), ["436", "175594"]);
+}
+
+#[test]
+fn day16() {
+    // The samples have no departure fields, so the second part is an empty product
+    assert_eq!(answers(16, "test.txt"), ["71", "1"]);
+    assert_eq!(answers(16, "test2.txt"), ["0", "1"]);
+}
+
+#[cfg(feature = "heavy-days")]
+#[test]
+fn day17() {
+    assert_eq!(answers(17, "test.txt"), ["112", "848"]);
+}
+
+#[test]
+fn day18() {
+    assert_eq!(answers(18, "test.txt"), ["26335", "693891"]);
+}
+
+#[test]
+fn day19() {
+    // Only the first part of the first sample, which has none of the rules of the second one
+    assert_eq!(answers(19, "test1.txt")[0], "2");
+    assert_eq!(answers(19, "test2.txt"), ["3", "12"]);
+}
+
+#[cfg(feature = "heavy-days")]
+#[test]
+fn day20() {
+    assert_eq!(answers(20, "test.txt"), ["20899048083289", "273"]);
+}
+
+#[cfg(feature = "heavy-days")]
+#[test]
+fn day23() {
+    assert_eq!(answers(23, "test.txt"), ["67384529", "149245887792"]);
+}
+
+#[test]
+fn day24() {
+    assert_eq!(answers(24, "test.txt"), ["10", "2208"]);
+}
+
+#[test]
+fn day25() {
+    assert_eq!(answers(25, "test.txt"), ["14897079"]);
+}
+
+#[test]
+fn unregistered_days() {
+    assert_eq!(
+        run_day_for_test(2020, 26, ""),
+        Err("day 26 of 2020 is not registered".to_owned())
+    );
+    assert_eq!(
+        run_day_for_test(1999, 1, ""),
+        Err("year 1999 is not registered".to_owned())
+    );
+    if cfg!(feature = "y2021") {
+        assert_eq!(
+            run_day_for_test(2021, 1, ""),
+            Err("day 1 is not implemented".to_owned())
+        );
+    }
+}